@@ -0,0 +1,334 @@
+//! Analysis of the difficulty of mazes.
+//!
+//! This module measures how hard a maze is to solve by hand: how long and winding its shortest way
+//! out is, how many choices are met along it, and how many dead ends lie in wait elsewhere. These
+//! metrics are summed up in a difficulty rating from 1 to 5, shown in the map menu and printed as
+//! JSON by the `analyze` subcommand.
+
+use std::cmp::Ordering;
+
+use color_eyre::eyre::{eyre, Result};
+
+use crate::{
+    export::json_string,
+    grid::{Cell, Grid, NodeId},
+    map::Map,
+    pathfinding,
+};
+
+/// Lowest difficulty score of each rating above 1, in increasing order.
+const RATING_THRESHOLDS: [usize; 4] = [10, 30, 70, 150];
+
+/// Difficulty metrics of a maze.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Analysis {
+    /// Number of moves along the shortest way out from any entry point.
+    pub solution_length: usize,
+    /// Number of dead ends, cells other than entry points and exits that lead to a single cell.
+    pub dead_ends: usize,
+    /// Average number of ways on, not counting the way back, from the cells of the shortest way
+    /// out.
+    pub branching_factor: f64,
+    /// Share of the cells that aren't walls lying on the shortest way out, between 0 and 1.
+    pub solution_share: f64,
+    /// Number of cells of the longest corridor leading to a dead end, the dead end included.
+    pub longest_dead_end: usize,
+    /// Number of changes of direction along the shortest way out, jumps aside.
+    pub turns: usize,
+    /// Number of cells of the shortest way out offering more than one way on.
+    pub decision_points: usize,
+    /// Difficulty rating, from 1 for the easiest mazes to 5 for the hardest.
+    pub rating: u8,
+}
+
+impl Analysis {
+    /// Returns the difficulty score the rating is derived from.
+    ///
+    /// Every decision point on the way out counts for two, and every ten moves along it, every five
+    /// turns, every five dead ends and every three cells of the longest dead end count for one.
+    const fn score(&self) -> usize {
+        self.decision_points * 2
+            + self.solution_length / 10
+            + self.turns / 5
+            + self.dead_ends / 5
+            + self.longest_dead_end / 3
+    }
+
+    /// Formats the analysis of the map with the given name as a JSON object on a single line.
+    pub(crate) fn json(&self, key: &str) -> String {
+        format!(
+            "{{\"map\": {}, \"rating\": {}, \"solution_length\": {}, \"dead_ends\": {}, \
+             \"branching_factor\": {:.3}, \"solution_share\": {:.3}, \"longest_dead_end\": {}, \
+             \"turns\": {}, \"decision_points\": {}}}",
+            json_string(key),
+            self.rating,
+            self.solution_length,
+            self.dead_ends,
+            self.branching_factor,
+            self.solution_share,
+            self.longest_dead_end,
+            self.turns,
+            self.decision_points
+        )
+    }
+
+    /// Describes the rating along with the metrics that weigh the most on it, as shown to the user.
+    pub(crate) fn summary(&self) -> String {
+        format!(
+            "{}/5, out in {} moves past {} decision points, {} dead ends",
+            self.rating, self.solution_length, self.decision_points, self.dead_ends
+        )
+    }
+}
+
+/// Analyses the difficulty of the maze of a map.
+///
+/// The way out measured is the shortest one from any entry point, picking up the keys needed to
+/// open the doors on the way.
+///
+/// # Errors
+///
+/// This function returns an error if the map doesn't form a grid or if no exit can be reached.
+pub(crate) fn analyze(map: &Map) -> Result<Analysis> {
+    let grid = Grid::of(map)?;
+    let solution = grid
+        .entries()
+        .into_iter()
+        .filter_map(|entry| pathfinding::shortest_path(&grid, entry))
+        .min_by_key(Vec::len)
+        .ok_or_else(|| eyre!("no exit can be reached in {}", map.key))?;
+
+    let cells = (0..grid.width() * grid.height())
+        .filter(|&node| grid.cell(node).is_some_and(|cell| cell != Cell::Wall))
+        .collect::<Vec<_>>();
+    let dead_ends = cells
+        .iter()
+        .copied()
+        .filter(|&node| is_dead_end(&grid, node))
+        .collect::<Vec<_>>();
+    let mut on_solution = solution.clone();
+    on_solution.sort_unstable();
+    on_solution.dedup();
+
+    // Every cell but the exit leads on, and the entry point has no way back
+    let ways_on = solution
+        .iter()
+        .enumerate()
+        .take(solution.len().saturating_sub(1))
+        .map(|(index, &position)| {
+            let back = index.checked_sub(1).and_then(|back| solution.get(back));
+            ways_on(&grid, position, back.copied())
+        })
+        .collect::<Vec<_>>();
+
+    let mut analysis = Analysis {
+        solution_length: solution.len().saturating_sub(1),
+        dead_ends: dead_ends.len(),
+        branching_factor: ratio(ways_on.iter().sum(), ways_on.len()),
+        solution_share: ratio(on_solution.len(), cells.len()),
+        longest_dead_end: dead_ends
+            .iter()
+            .map(|&node| corridor(&grid, node))
+            .max()
+            .unwrap_or(0),
+        turns: turns(&grid, &solution),
+        decision_points: ways_on.iter().filter(|&&ways| ways > 1).count(),
+        rating: 1,
+    };
+    analysis.rating = rating(analysis.score());
+
+    Ok(analysis)
+}
+
+/// Returns the difficulty rating of the given score, from 1 to 5.
+fn rating(score: usize) -> u8 {
+    RATING_THRESHOLDS
+        .iter()
+        .zip(2..)
+        .filter(|&(&threshold, _)| score >= threshold)
+        .map(|(_, rating)| rating)
+        .last()
+        .unwrap_or(1)
+}
+
+/// Returns the nodes the given node leads to, whatever the direction, including the other end of
+/// its portal and the landing of its stairs.
+fn links(grid: &Grid, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    grid.neighbours(node)
+        .filter(|&next| grid.cell(next).is_some_and(|cell| cell != Cell::Wall))
+        .chain(grid.twin(node))
+        .chain(grid.landing(node))
+}
+
+/// Returns whether the given node is a dead end, a cell other than an entry point or an exit that
+/// leads to a single cell.
+fn is_dead_end(grid: &Grid, node: NodeId) -> bool {
+    !matches!(
+        grid.cell(node),
+        None | Some(Cell::Wall | Cell::Entry | Cell::Exit)
+    ) && links(grid, node).count() == 1
+}
+
+/// Returns the number of cells of the corridor leading to the given dead end, the dead end
+/// included, which ends at the first cell leading to more than two cells.
+fn corridor(grid: &Grid, dead_end: NodeId) -> usize {
+    let (mut previous, mut current, mut length) = (dead_end, dead_end, 1);
+
+    // Corridors can't be longer than the maze, which also guards against walking in circles
+    while length < grid.width() * grid.height() {
+        let Some(next) = links(grid, current).find(|&next| next != previous) else {
+            break;
+        };
+        let inside = !matches!(grid.cell(next), Some(Cell::Entry | Cell::Exit));
+        if !inside || links(grid, next).count() != 2 {
+            break;
+        }
+
+        (previous, current, length) = (current, next, length + 1);
+    }
+
+    length
+}
+
+/// Returns the number of ways on from the cell at the given `(column, row)` coordinates, not
+/// counting the way back to the given cell.
+///
+/// Crossings are only ever walked straight through, and thus never offer a choice.
+fn ways_on(grid: &Grid, position: (usize, usize), back: Option<(usize, usize)>) -> usize {
+    let Some(node) = grid.node(position) else {
+        return 0;
+    };
+    if grid.cell(node) == Some(Cell::Crossing) {
+        return 1;
+    }
+
+    let mut ways = grid
+        .moves(node)
+        .map(|next| grid.position(next))
+        .filter(|&next| Some(next) != back)
+        .collect::<Vec<_>>();
+    ways.sort_unstable();
+    ways.dedup();
+    ways.len()
+}
+
+/// Returns the number of changes of direction along the given path, counted within the stretches
+/// walked between jumps.
+fn turns(grid: &Grid, path: &[(usize, usize)]) -> usize {
+    grid.stretches(path)
+        .into_iter()
+        .map(|stretch| {
+            let directions = stretch
+                .windows(2)
+                .filter_map(|step| match *step {
+                    [from, to] if from != to => Some((to.0.cmp(&from.0), to.1.cmp(&from.1))),
+                    _ => None,
+                })
+                .collect::<Vec<(Ordering, Ordering)>>();
+            directions
+                .windows(2)
+                .filter(|pair| pair.first() != pair.last())
+                .count()
+        })
+        .sum()
+}
+
+/// Returns the ratio of the given part to the given whole, or 0 if the whole is empty.
+fn ratio(part: usize, whole: usize) -> f64 {
+    match (u32::try_from(part), u32::try_from(whole)) {
+        (Ok(part), Ok(whole)) if whole > 0 => f64::from(part) / f64::from(whole),
+        _ => 0.,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Metadata;
+
+    #[test]
+    fn test_analyze_comb() {
+        // The way out runs straight along the back of a comb, past a choice at every tooth
+        let analysis = analyze(&Map {
+            key: "comb".to_owned(),
+            data: ["2222222", "2133334", "2323232", "2323232", "2222222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        })
+        .expect("the maze has a way out");
+
+        assert_eq!(analysis.solution_length, 5);
+        assert_eq!(analysis.dead_ends, 3);
+        assert!((analysis.branching_factor - 1.6).abs() < 1e-9);
+        assert!((analysis.solution_share - 0.5).abs() < 1e-9);
+        assert_eq!(analysis.longest_dead_end, 2);
+        assert_eq!(analysis.turns, 0);
+        assert_eq!(analysis.decision_points, 3);
+        assert_eq!(analysis.rating, 1);
+        assert_eq!(
+            analysis.json("comb"),
+            "{\"map\": \"comb\", \"rating\": 1, \"solution_length\": 5, \"dead_ends\": 3, \
+             \"branching_factor\": 1.600, \"solution_share\": 0.500, \"longest_dead_end\": 2, \
+             \"turns\": 0, \"decision_points\": 3}"
+        );
+    }
+
+    #[test]
+    fn test_analyze_winding() {
+        // A single winding corridor, with a dead end branching off halfway along
+        let analysis = analyze(&Map {
+            key: "winding".to_owned(),
+            data: [
+                "2222222", "2133322", "2222322", "2333332", "2322222", "2333342", "2222222",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+            metadata: Metadata::default(),
+        })
+        .expect("the maze has a way out");
+
+        assert_eq!(analysis.solution_length, 14);
+        assert_eq!(analysis.turns, 4);
+        assert_eq!(analysis.decision_points, 1);
+        assert_eq!(analysis.dead_ends, 1);
+        assert_eq!(analysis.longest_dead_end, 1);
+    }
+
+    #[test]
+    fn test_rating() {
+        assert_eq!(
+            [0, 9, 10, 29, 30, 69, 70, 149, 150, usize::MAX].map(rating),
+            [1, 1, 2, 2, 3, 3, 4, 4, 5, 5]
+        );
+    }
+
+    #[test]
+    fn test_turns_skip_jumps() {
+        let grid = Grid::new(
+            &["22222", "2p1p2", "23234", "22222"],
+            crate::grid::Topology::Square,
+        )
+        .expect("the rows should form a grid");
+
+        // Stepping onto a portal and out of its twin turns once on either side of the jump
+        assert_eq!(turns(&grid, &[(2, 1), (1, 1), (3, 1), (3, 2), (4, 2)]), 1);
+        assert_eq!(turns(&grid, &[(2, 1), (3, 1), (3, 2)]), 1);
+    }
+
+    #[test]
+    fn test_analyze_errors() {
+        assert!(analyze(&Map {
+            key: "walled in".to_owned(),
+            data: ["22222", "21224", "22222"].map(str::to_owned).to_vec(),
+            metadata: Metadata::default()
+        })
+        .is_err());
+        assert!(analyze(&Map {
+            key: "unknown cell".to_owned(),
+            data: ["222", "2x2", "222"].map(str::to_owned).to_vec(),
+            metadata: Metadata::default()
+        })
+        .is_err());
+    }
+}
@@ -1,13 +1,24 @@
 //! Core application state and logic for the labyrinth game.
 
-use color_eyre::eyre::Result;
-use ratatui::DefaultTerminal;
+use std::{collections::VecDeque, path::Path};
+
+use color_eyre::eyre::{Report, Result};
+use ratatui::{backend::Backend, crossterm::event::Event, layout::Rect, Terminal};
 
 use crate::{
-    events,
+    analysis,
+    braid::{self, BraidSettings},
+    camera::Camera,
+    cli::{ImageExport, MapBraid, MapGeneration, MapImport},
+    error::{self, RecoverableExt as _},
+    events, export, file_loader, generate, import,
+    input::{EventSource, TerminalEvents},
+    keymap::Keymap,
     map::Map,
-    pathfinding::AnimationManager,
-    types::{MainMenuItem, Screen},
+    pathfinding::{self, AnimationManager},
+    replay::{self, Recorder, Replay},
+    session::TerminalGuard,
+    types::{MainMenuItem, OptionsMenuItem, Screen},
     ui,
 };
 
@@ -43,6 +54,12 @@ pub struct App {
     /// This field holds the map that is currently selected in the viewport by the user cursor. This
     /// means the currently selected model in the maps menu.
     pub(crate) viewport_map: Option<Map>,
+    /// Difficulty rating of the map selected in the viewport, along with the map it was worked out
+    /// for.
+    ///
+    /// This field caches the rating shown in the maps menu, so that the maze is only analysed again
+    /// once the cursor moves to another map rather than on every redraw.
+    pub(crate) viewport_rating: Option<(Map, String)>,
     /// Scrolling offset for the map list viewport.
     ///
     /// This field holds the offset by which to scroll the sliding window into the
@@ -58,6 +75,65 @@ pub struct App {
     /// This field manages the animation state including timing, current step tracking, and the
     /// coordinate path being displayed during the animated maze solving.
     pub(crate) animation_manager: AnimationManager,
+    /// Camera over the in-game maze viewport.
+    ///
+    /// This field holds the panning offset, zoom level and auto-follow state used to display mazes
+    /// that don't fit in the terminal.
+    pub(crate) camera: Camera,
+    /// Queue of recoverable errors pending acknowledgement.
+    ///
+    /// This field holds the messages of errors that didn't end the session. The first one is shown
+    /// in a modal popup until the user dismisses it, after which the next one is shown.
+    pub(crate) errors: VecDeque<String>,
+    /// Size of the terminal during the last redraw.
+    ///
+    /// This field holds the area of the last drawn frame, used to map mouse positions back to the
+    /// widgets that were on screen when the user clicked.
+    pub(crate) area: Rect,
+    /// Maze cell last clicked by the user on the in-game screen.
+    ///
+    /// This field holds the `(column, row)` coordinates of the cell whose details are shown in the
+    /// in-game tooltip, if any.
+    pub(crate) inspected_cell: Option<(usize, usize)>,
+    /// Whether the inspected cell can be reached from an entry point, along with the cell and the
+    /// map it was worked out for.
+    ///
+    /// This field caches the reachability told in the in-game tooltip, so that the maze is only
+    /// searched again once another cell is inspected or the map changes, not on every redraw.
+    pub(crate) inspected_reach: Option<((usize, usize), Map, bool)>,
+    /// Active key bindings.
+    ///
+    /// This field maps key presses to the actions handled by the event handlers, and provides the
+    /// navigation hints shown in each screen's footer.
+    pub(crate) keymap: Keymap,
+    /// Help overlay visibility flag.
+    ///
+    /// This field indicates whether the help overlay listing the key bindings of the current screen
+    /// is drawn on top of it. Any key press hides it again.
+    pub(crate) help: bool,
+    /// Statistics panel visibility flag.
+    ///
+    /// This field indicates whether the panel comparing the way out found by the solver with the
+    /// shortest and cheapest ones is drawn over the in-game maze. It starts off `true`.
+    pub(crate) stats: bool,
+    /// Settings of the next in-game braiding, if the braid panel is open.
+    ///
+    /// This field holds the share of dead ends to remove and the number of loops to add, as picked
+    /// by the user in the panel drawn over the in-game maze. The panel takes every key press until
+    /// the braiding is applied or cancelled.
+    pub(crate) braiding: Option<BraidSettings>,
+    /// Notice about the last completed in-game action, if any.
+    ///
+    /// This field holds a short message, such as where an export was written to, shown in the
+    /// in-game tooltip in place of the cell inspection until the user clicks a cell or leaves.
+    pub(crate) status: Option<String>,
+    /// Recorder logging input events, if the session is being recorded.
+    pub(crate) recorder: Option<Recorder>,
+    /// Recording being replayed, if any.
+    ///
+    /// This field holds the input events still pending to be fed to the event handlers in place of
+    /// those read from the terminal.
+    pub(crate) replay: Option<Replay>,
 }
 
 impl Default for App {
@@ -79,36 +155,397 @@ impl App {
             map: Map::default(),
             maps: Vec::new(),
             viewport_map: None,
+            viewport_rating: None,
             viewport_offset: 0,
             viewport_height: 0,
             animation_manager: AnimationManager::new(),
+            camera: Camera::new(),
+            errors: VecDeque::new(),
+            area: Rect::default(),
+            inspected_cell: None,
+            inspected_reach: None,
+            keymap: Keymap::default(),
+            help: false,
+            stats: true,
+            braiding: None,
+            status: None,
+            recorder: None,
+            replay: None,
         }
     }
 
+    /// Loads the user's key bindings from the configuration file.
+    ///
+    /// The default key bindings are kept if there's no configuration file. If the file can't be
+    /// read or parsed, the default key bindings are kept too and the error is queued to be shown to
+    /// the user once the main loop starts.
+    pub fn load_keymap(&mut self) {
+        match Keymap::load() {
+            Ok(keymap) => self.keymap = keymap,
+            Err(err) => self.errors.push_back(format!("{err:#}")),
+        }
+    }
+
+    /// Loads the map at the given path and selects it for the next game.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the map can't be read or isn't valid.
+    pub fn load_map(&mut self, path: &Path) -> Result<()> {
+        self.map = file_loader::load_map(path)?;
+
+        Ok(())
+    }
+
+    /// Writes the solver animation for the selected map to the given path as an asciicast.
+    ///
+    /// The animation is drawn on an off-screen terminal of the given `(width, height)` size, or of
+    /// 80x24 cells if none is given.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the size is too small to draw the map, or if the file
+    /// can't be written.
+    pub fn export_cast(&self, path: &Path, size: Option<(u16, u16)>) -> Result<()> {
+        export::cast::write(&self.map, size.unwrap_or(export::cast::DEFAULT_SIZE), path)
+    }
+
+    /// Writes an image of the selected map, optionally with its solution drawn in.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the image format isn't supported, if the solution can't be
+    /// found, or if the file can't be written.
+    pub fn export_image(&self, export: &ImageExport) -> Result<()> {
+        export::write_image(&self.map, export.solution, &export.style, &export.path)
+    }
+
+    /// Converts a maze drawn in another format to a labyrinth map and selects it for the next game.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the maze can't be read or converted, or if the map can't
+    /// be written.
+    pub fn import_map(&mut self, import: &MapImport) -> Result<()> {
+        self.map = import::import(&import.input, &import.output, &import.settings)?;
+
+        Ok(())
+    }
+
+    /// Generates a random maze, writes it as a labyrinth map and selects it for the next game.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the maze is too small or if the map can't be written.
+    pub fn generate_map(&mut self, generation: &MapGeneration) -> Result<()> {
+        self.map = generate::generate(&generation.output, &generation.settings)?;
+
+        Ok(())
+    }
+
+    /// Analyses the difficulty of the selected map and formats it as JSON.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if no exit of the maze can be reached.
+    pub fn analyze_map(&self) -> Result<String> {
+        Ok(analysis::analyze(&self.map)?.json(&self.map.key))
+    }
+
+    /// Knocks walls out of a maze, writes it as a labyrinth map and selects it for the next game.
+    ///
+    /// This function returns a summary of what braiding changed, such as how many dead ends were
+    /// removed.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the maze can't be loaded or braided, or if the map can't
+    /// be written.
+    pub fn braid_map(&mut self, braid: &MapBraid) -> Result<String> {
+        let braided = braid::braid_file(&braid.input, &braid.output, &braid.settings)?;
+        let summary = braided.summary();
+        self.map = braided.map;
+
+        Ok(summary)
+    }
+
+    /// Starts recording every input event of the session to the file at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file can't be created.
+    pub fn record(&mut self, path: &Path) -> Result<()> {
+        self.recorder = Some(Recorder::create(path)?);
+
+        Ok(())
+    }
+
+    /// Loads a recording whose input events are fed to the event handlers before any other input.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the recording can't be read or parsed.
+    pub fn replay(&mut self, path: &Path) -> Result<()> {
+        self.replay = Some(Replay::load(path)?);
+
+        Ok(())
+    }
+
+    /// Replays the loaded recording without a terminal and checks the final state.
+    ///
+    /// This function feeds every event of the recording loaded with [`App::replay`] to the event
+    /// handlers against an off-screen terminal, and then compares the state of the application
+    /// against the given `(field, value)` expectations.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if there's no recording loaded, if a fatal error happens
+    /// while replaying, or if any of the expectations doesn't hold.
+    pub fn run_headless(&mut self, expectations: &[(String, String)]) -> Result<()> {
+        replay::run_headless(self, expectations)
+    }
+
     /// Runs the main loop of the application.
     ///
     /// This function handles user input and updates the application state. The loop continues until
-    /// the exit condition is `true`, after which the function returns to the call site.
+    /// the exit condition is `true`, after which the function returns to the call site. Recoverable
+    /// errors are queued and shown to the user in a popup instead of ending the loop.
     ///
     /// # Errors
     ///
     /// - [`std::io::Error`]
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+    /// - Any error not marked as [`Recoverable`](error::Recoverable)
+    pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        self.run_with_events(terminal, &mut TerminalEvents)
+    }
+
+    /// Runs the main loop of the application in a guarded terminal, restoring it before returning.
+    ///
+    /// This function takes ownership of the guard and drops it as soon as the loop ends, so that an
+    /// error ending the loop only reaches the caller once the terminal is back to a sane state.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`]
+    /// - Any error not marked as [`Recoverable`](error::Recoverable)
+    pub fn run_guarded<B: Backend>(&mut self, guard: TerminalGuard<B>) -> Result<()> {
+        self.run_guarded_with_events(guard, &mut TerminalEvents)
+    }
+
+    /// Runs the main loop of the application in a guarded terminal, reading input events from the
+    /// given source, and restores the terminal before returning.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`]
+    /// - Any error not marked as [`Recoverable`](error::Recoverable)
+    pub(crate) fn run_guarded_with_events<B: Backend>(
+        &mut self,
+        mut guard: TerminalGuard<B>,
+        source: &mut dyn EventSource,
+    ) -> Result<()> {
+        let result = self.run_with_events(guard.terminal_mut(), source);
+        drop(guard);
+
+        result
+    }
+
+    /// Runs the main loop of the application, reading input events from the given source.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`]
+    /// - Any error not marked as [`Recoverable`](error::Recoverable)
+    pub(crate) fn run_with_events<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        source: &mut dyn EventSource,
+    ) -> Result<()> {
+        // Recordings start with the terminal size so that replays lay out screens the same way
+        if let Some(recorder) = self.recorder.as_mut() {
+            let size = terminal.size()?;
+            recorder.record(&Event::Resize(size.width, size.height))?;
+        }
+
         while !self.exit {
-            let _ = terminal.try_draw(|frame| {
-                ui::draw(self, frame)
-                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
-            })?;
-            events::handle_events(self)?;
+            self.tick(terminal, source)?;
         }
 
         Ok(())
     }
+
+    /// Runs a single iteration of the main loop.
+    ///
+    /// This function draws a frame and then waits for and handles the next input event, queueing
+    /// any recoverable error raised along the way.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`]
+    /// - Any error not marked as [`Recoverable`](error::Recoverable)
+    pub(crate) fn tick<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        source: &mut dyn EventSource,
+    ) -> Result<()> {
+        self.draw(terminal)?;
+
+        if let Err(err) = events::handle_events(self, source) {
+            self.handle_error(err)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws a single frame of the current screen.
+    ///
+    /// If drawing fails with a recoverable error, the error is queued and the screen the
+    /// application moved to is drawn instead.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`]
+    /// - Any error not marked as [`Recoverable`](error::Recoverable)
+    pub(crate) fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let _ = terminal.try_draw(|frame| {
+            if let Err(err) = ui::draw(self, frame).recoverable() {
+                self.handle_error(err).map_err(std::io::Error::other)?;
+                ui::draw(self, frame).map_err(std::io::Error::other)?;
+            }
+
+            Ok::<_, std::io::Error>(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Classifies an error and either queues it for display or hands it back.
+    ///
+    /// Recoverable errors are pushed to the [`errors`](App::errors) queue, and the application is
+    /// moved out of the screen that failed so that the same failure isn't hit again on every frame.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the given error back if it isn't recoverable.
+    pub(crate) fn handle_error(&mut self, err: Report) -> Result<()> {
+        if !error::is_recoverable(&err) {
+            return Err(err);
+        }
+
+        self.errors.push_back(err.to_string());
+
+        match self.screen {
+            Screen::InGame => {
+                self.animation_manager.clear();
+                self.camera = Camera::new();
+                self.inspected_cell = None;
+                self.status = None;
+                self.screen = Screen::MainMenu(MainMenuItem::StartGame);
+            }
+            Screen::MapMenu => {
+                self.screen = Screen::OptionsMenu(OptionsMenuItem::Map);
+            }
+            Screen::MainMenu(_) | Screen::OptionsMenu(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Rates the map selected in the viewport, unless the cached rating was worked out for it.
+    ///
+    /// This function analyses the difficulty of the cursor-selected map only when the selection
+    /// has changed since the last call, and drops the cached rating once no map is selected.
+    pub(crate) fn rate_viewport_map(&mut self) {
+        let Some(map) = &self.viewport_map else {
+            self.viewport_rating = None;
+            return;
+        };
+        if self
+            .viewport_rating
+            .as_ref()
+            .is_some_and(|(rated, _)| rated == map)
+        {
+            return;
+        }
+
+        let rating = match analysis::analyze(map) {
+            Ok(analysis) => format!("rating: {}", analysis.summary()),
+            Err(err) => format!("rating: unknown, {err}"),
+        };
+        self.viewport_rating = Some((map.clone(), rating));
+    }
+
+    /// Works out whether the inspected cell can be reached, unless the cached answer was worked out
+    /// for it.
+    ///
+    /// This function searches the maze from every entry point only when another cell has been
+    /// inspected or the map has changed since the last call, a crossing being reached when either
+    /// its bridge or its tunnel is. The cached answer is dropped once no cell is inspected.
+    pub(crate) fn reach_inspected_cell(&mut self) {
+        let Some(cell) = self.inspected_cell else {
+            self.inspected_reach = None;
+            return;
+        };
+        if self
+            .inspected_reach
+            .as_ref()
+            .is_some_and(|(reached, map, _)| *reached == cell && *map == self.map)
+        {
+            return;
+        }
+
+        let grid = &self.animation_manager.grid;
+        let reachable = grid.node(cell).is_some_and(|node| {
+            let reached = pathfinding::reachable(grid, &grid.entries());
+            reached.contains(&node)
+                || grid
+                    .tunnel(node)
+                    .is_some_and(|tunnel| reached.contains(&tunnel))
+        });
+        self.inspected_reach = Some((cell, self.map.clone(), reachable));
+    }
+
+    /// Clamps the map list scrolling offset to the current viewport height.
+    ///
+    /// This function keeps the cursor-selected map within the visible window of the map list and
+    /// prevents the window from scrolling past the end of the list, which may otherwise happen
+    /// after the terminal is resized.
+    pub(crate) fn clamp_viewport_offset(&mut self) {
+        let selected = self
+            .viewport_map
+            .as_ref()
+            .and_then(|viewport_map| self.maps.iter().position(|map| map == viewport_map));
+
+        if let Some(selected) = selected {
+            if selected < self.viewport_offset {
+                self.viewport_offset = selected;
+            } else if self.viewport_height > 0
+                && selected >= self.viewport_offset + self.viewport_height
+            {
+                self.viewport_offset = selected + 1 - self.viewport_height;
+            }
+        }
+
+        self.viewport_offset = self
+            .viewport_offset
+            .min(self.maps.len().saturating_sub(self.viewport_height));
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{rc::Rc, time::Duration};
+
+    use ratatui::{backend::TestBackend, crossterm::event::KeyCode};
+
     use super::*;
+    use crate::{
+        grid::Grid,
+        input::EventQueue,
+        map::Metadata,
+        pathfinding::ANIMATION_FRAME_DELAY_MS,
+        time::{Clock, ManualClock},
+    };
 
     #[test]
     fn test_app_new_initialization() {
@@ -121,6 +558,10 @@ mod tests {
         assert!(app.viewport_map.is_none());
         assert_eq!(app.viewport_offset, 0);
         assert_eq!(app.viewport_height, 0);
+        assert_eq!(app.camera, Camera::new());
+        assert!(app.errors.is_empty());
+        assert_eq!(app.keymap, Keymap::default());
+        assert!(!app.help);
     }
 
     #[test]
@@ -185,4 +626,209 @@ mod tests {
         app.viewport_height = 10;
         assert_eq!(app.viewport_height, 10);
     }
+
+    #[test]
+    fn test_app_clamp_viewport_offset_past_end() {
+        let mut app = App::new();
+        app.maps = vec![Map::default(); 5];
+        app.viewport_offset = 4;
+        app.viewport_height = 3;
+
+        app.clamp_viewport_offset();
+
+        assert_eq!(app.viewport_offset, 2);
+    }
+
+    #[test]
+    fn test_app_clamp_viewport_offset_follows_selection() {
+        let mut app = App::new();
+        app.maps = (0..10)
+            .map(|idx| Map {
+                key: idx.to_string(),
+                data: Vec::new(),
+                metadata: Metadata::default(),
+            })
+            .collect();
+        app.viewport_map = app.maps.get(7).cloned();
+        app.viewport_offset = 0;
+        app.viewport_height = 4;
+
+        app.clamp_viewport_offset();
+        assert_eq!(app.viewport_offset, 4);
+
+        app.viewport_map = app.maps.get(1).cloned();
+        app.clamp_viewport_offset();
+        assert_eq!(app.viewport_offset, 1);
+    }
+
+    #[test]
+    fn test_app_rate_viewport_map_once() {
+        let mut app = App::new();
+        let comb = Map {
+            key: "comb".to_owned(),
+            data: ["2222222", "2133334", "2323232", "2323232", "2222222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        };
+        app.viewport_map = Some(comb.clone());
+
+        app.rate_viewport_map();
+        assert!(app
+            .viewport_rating
+            .as_ref()
+            .is_some_and(|(map, rating)| *map == comb && rating.starts_with("rating: 1/5")));
+
+        // The rating of a map still selected is kept rather than worked out again
+        app.viewport_rating = Some((comb, "rating: cached".to_owned()));
+        app.rate_viewport_map();
+        assert!(app
+            .viewport_rating
+            .as_ref()
+            .is_some_and(|(_, rating)| rating == "rating: cached"));
+
+        app.viewport_map = Some(Map {
+            key: "walled in".to_owned(),
+            data: ["22222", "21224", "22222"].map(str::to_owned).to_vec(),
+            metadata: Metadata::default(),
+        });
+        app.rate_viewport_map();
+        assert!(app
+            .viewport_rating
+            .as_ref()
+            .is_some_and(|(_, rating)| rating.starts_with("rating: unknown")));
+
+        app.viewport_map = None;
+        app.rate_viewport_map();
+        assert!(app.viewport_rating.is_none());
+    }
+
+    #[test]
+    fn test_app_reach_inspected_cell_once() {
+        let mut app = App::new();
+        app.map = Map {
+            key: "pocket".to_owned(),
+            data: ["222222", "213422", "222232", "222222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        };
+        let grid = Grid::of(&app.map).expect("the map should form a grid");
+        app.animation_manager
+            .record(grid)
+            .expect("the maze has an entry point");
+
+        app.inspected_cell = Some((4, 2));
+        app.reach_inspected_cell();
+        assert_eq!(
+            app.inspected_reach
+                .as_ref()
+                .map(|&(cell, _, reachable)| (cell, reachable)),
+            Some(((4, 2), false))
+        );
+
+        // The answer for a cell still inspected is kept rather than worked out again
+        app.inspected_reach = Some(((4, 2), app.map.clone(), true));
+        app.reach_inspected_cell();
+        assert!(app
+            .inspected_reach
+            .as_ref()
+            .is_some_and(|&(_, _, reachable)| reachable));
+
+        app.inspected_cell = Some((2, 1));
+        app.reach_inspected_cell();
+        assert_eq!(
+            app.inspected_reach
+                .as_ref()
+                .map(|&(cell, _, reachable)| (cell, reachable)),
+            Some(((2, 1), true))
+        );
+
+        app.inspected_cell = None;
+        app.reach_inspected_cell();
+        assert!(app.inspected_reach.is_none());
+    }
+
+    #[test]
+    fn test_app_handle_error_recoverable() {
+        let mut app = App::new();
+        app.screen = Screen::InGame;
+
+        app.handle_error(error::Recoverable("failed to read map".to_owned()).into())
+            .expect("recoverable errors should be queued");
+
+        assert_eq!(
+            app.errors.front().map(String::as_str),
+            Some("failed to read map")
+        );
+        assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::StartGame));
+    }
+
+    #[test]
+    fn test_app_handle_error_map_menu_returns_to_options() {
+        let mut app = App::new();
+        app.screen = Screen::MapMenu;
+
+        app.handle_error(error::Recoverable("broken layout".to_owned()).into())
+            .expect("recoverable errors should be queued");
+
+        assert_eq!(app.screen, Screen::OptionsMenu(OptionsMenuItem::Map));
+    }
+
+    #[test]
+    fn test_app_handle_error_fatal() {
+        let mut app = App::new();
+
+        let result = app.handle_error(color_eyre::eyre::eyre!("terminal went away"));
+
+        assert!(result.is_err());
+        assert!(app.errors.is_empty());
+    }
+
+    #[test]
+    fn test_app_full_flow_through_event_queue() {
+        let mut terminal =
+            Terminal::new(TestBackend::new(80, 24)).expect("failed to create test terminal");
+        let clock = Rc::new(ManualClock::new());
+        let animation_clock: Rc<dyn Clock> = Rc::<ManualClock>::clone(&clock);
+        let mut app = App::new();
+        app.animation_manager = AnimationManager::with_clock(animation_clock);
+
+        // Menu, options, map menu, select the default map, then back out and start the game
+        let keys = [
+            (KeyCode::Char('j'), Screen::MainMenu(MainMenuItem::Options)),
+            (KeyCode::Enter, Screen::OptionsMenu(OptionsMenuItem::Map)),
+            (KeyCode::Enter, Screen::MapMenu),
+            (KeyCode::Enter, Screen::MapMenu),
+            (KeyCode::Esc, Screen::OptionsMenu(OptionsMenuItem::Map)),
+            (KeyCode::Down, Screen::OptionsMenu(OptionsMenuItem::Back)),
+            (KeyCode::Enter, Screen::MainMenu(MainMenuItem::StartGame)),
+            (KeyCode::Enter, Screen::InGame),
+        ];
+        for (key, screen) in keys {
+            app.tick(
+                &mut terminal,
+                &mut EventQueue::new([Event::Key(key.into())]),
+            )
+            .expect("the loop iteration should succeed");
+            assert_eq!(app.screen, screen, "{key:?} should lead to {screen:?}");
+        }
+        assert_eq!(app.map, Map::default());
+
+        // The first in-game frame records the animation, and every later frame advances it a step
+        for _ in 0..5 {
+            clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+            app.tick(&mut terminal, &mut EventQueue::default())
+                .expect("the loop iteration should succeed");
+        }
+        assert_eq!(app.animation_manager.current_index, 4);
+        assert_eq!(app.animation_manager.current_path.len(), 4);
+
+        app.tick(
+            &mut terminal,
+            &mut EventQueue::new([Event::Key(KeyCode::Char('q').into())]),
+        )
+        .expect("the loop iteration should succeed");
+        assert!(app.exit);
+    }
 }
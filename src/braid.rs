@@ -0,0 +1,367 @@
+//! Braiding of mazes.
+//!
+//! This module knocks walls out of mazes to give them more than one way through. Braiding removes
+//! dead ends, open paths or terrain with a single way on, by opening a wall next to them that leads
+//! to another passage, while loops open walls between passages anywhere in the maze. The walls
+//! around every floor are left standing, so that braided maps are still valid.
+
+use std::{fs, path::Path};
+
+use color_eyre::eyre::{ensure, eyre, Result, WrapErr as _};
+
+use crate::{
+    file_loader,
+    generate::{self, Rng},
+    grid::{Cell, Grid, NodeId},
+    map::Map,
+};
+
+/// Settings of a braiding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BraidSettings {
+    /// Share of the dead ends to remove, in percent.
+    pub dead_ends: usize,
+    /// Number of loops to add once the dead ends are removed.
+    pub loops: usize,
+    /// Seed picking the walls to knock out, or `None` to pick one from the clock.
+    pub seed: Option<u64>,
+}
+
+impl Default for BraidSettings {
+    fn default() -> Self {
+        Self {
+            dead_ends: 100,
+            loops: 0,
+            seed: None,
+        }
+    }
+}
+
+/// Maze braided out of another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Braid {
+    /// Map of the braided maze.
+    pub map: Map,
+    /// Number of dead ends of the maze before braiding.
+    pub dead_ends: usize,
+    /// Number of dead ends removed.
+    pub removed: usize,
+    /// Number of loops added.
+    pub loops: usize,
+}
+
+impl Braid {
+    /// Describes what braiding changed, as shown to the user.
+    pub(crate) fn summary(&self) -> String {
+        format!(
+            "removed {} of {} dead ends and added {} loops",
+            self.removed, self.dead_ends, self.loops
+        )
+    }
+}
+
+/// Braids the maze of the input map file and writes it to the output file.
+///
+/// # Errors
+///
+/// This function returns an error if the input can't be loaded, if the settings are invalid, or if
+/// the output isn't a .labmap file or can't be written.
+pub(crate) fn braid_file(input: &Path, output: &Path, settings: &BraidSettings) -> Result<Braid> {
+    let file_name = output
+        .file_name()
+        .ok_or_else(|| eyre!("{} isn't a file", output.display()))?;
+    ensure!(
+        file_name.as_encoded_bytes().ends_with(b".labmap"),
+        "{} lacks the .labmap extension",
+        output.display()
+    );
+
+    let map = file_loader::load_map(input)?;
+    let mut braid = braid(&map, settings)?;
+    braid.map = Map::new(file_name.to_owned(), &braid.map.to_labmap())?;
+    fs::write(output, braid.map.to_labmap())
+        .wrap_err_with(|| format!("failed to write {}", output.display()))?;
+
+    Ok(braid)
+}
+
+/// Braids a maze, removing its dead ends and then adding loops as the settings ask.
+///
+/// Each dead end is removed by opening one of the walls next to it that lead to another passage,
+/// picked at random, and each loop by opening a wall between passages. Dead ends that no wall can
+/// be opened next to are left alone, as are loops once no wall is left to open.
+///
+/// # Errors
+///
+/// This function returns an error if the share of dead ends isn't a percentage, or if the map
+/// doesn't form a grid.
+pub(crate) fn braid(map: &Map, settings: &BraidSettings) -> Result<Braid> {
+    ensure!(
+        settings.dead_ends <= 100,
+        "the share of dead ends to remove must be a percentage, found {}",
+        settings.dead_ends
+    );
+
+    let grid = Grid::of(map)?;
+    let mut rng = Rng::new(settings.seed.unwrap_or_else(generate::clock_seed));
+    let mut open = (0..grid.width() * grid.height())
+        .map(|node| grid.cell(node).is_some_and(|cell| cell != Cell::Wall))
+        .collect::<Vec<_>>();
+    let mut rows = map.data.clone();
+
+    let mut dead_ends = (0..open.len())
+        .filter(|&node| is_dead_end(&grid, &open, node))
+        .collect::<Vec<_>>();
+    let total = dead_ends.len();
+    let target = total - (total * settings.dead_ends).div_ceil(100);
+    let mut left = total;
+    rng.shuffle(&mut dead_ends);
+    for node in dead_ends {
+        if left <= target {
+            break;
+        }
+        // Opening a wall next to an earlier dead end may have removed this one already
+        if !is_dead_end(&grid, &open, node) {
+            continue;
+        }
+
+        let walls = grid
+            .neighbours(node)
+            .filter(|&wall| is_knockable(&grid, &open, wall, node))
+            .collect::<Vec<_>>();
+        if let Some(&wall) = walls.get(rng.below(walls.len())) {
+            // Only the cells next to the wall can stop or start being dead ends
+            let around = |open: &[bool]| {
+                grid.neighbours(wall)
+                    .filter(|&next| is_dead_end(&grid, open, next))
+                    .count()
+            };
+            let before = around(&open);
+            knock(&grid, &mut open, &mut rows, wall);
+            left = left + around(&open) - before;
+        }
+    }
+    let removed = total.saturating_sub(left);
+
+    let mut walls = (0..open.len())
+        .filter(|&wall| is_knockable(&grid, &open, wall, wall))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut walls);
+    let mut loops = 0;
+    for wall in walls {
+        if loops == settings.loops {
+            break;
+        }
+        if is_knockable(&grid, &open, wall, wall) {
+            knock(&grid, &mut open, &mut rows, wall);
+            loops += 1;
+        }
+    }
+
+    let braided = Map {
+        data: rows,
+        ..map.clone()
+    };
+    ensure!(
+        file_loader::parse_file_contents(&braided.to_labmap()),
+        "the braided maze isn't a valid labyrinth map"
+    );
+
+    Ok(Braid {
+        map: braided,
+        dead_ends: total,
+        removed,
+        loops,
+    })
+}
+
+/// Returns whether the given node is an open path or terrain with a single way on.
+fn is_dead_end(grid: &Grid, open: &[bool], node: NodeId) -> bool {
+    matches!(grid.cell(node), Some(Cell::Open | Cell::Terrain(_)))
+        && grid
+            .neighbours(node)
+            .filter(|&next| open.get(next).copied().unwrap_or(false))
+            .count()
+            == 1
+}
+
+/// Returns whether the given wall can be knocked out to join a passage other than the one on the
+/// given node, which is the wall itself to join any two passages.
+///
+/// Walls on the edge of a floor are never knocked out.
+fn is_knockable(grid: &Grid, open: &[bool], wall: NodeId, from: NodeId) -> bool {
+    let (x, y) = grid.local(grid.position(wall));
+    let interior = x > 0 && y > 0 && x + 1 < grid.floor_width() && y + 1 < grid.height();
+    let passages = grid
+        .neighbours(wall)
+        .filter(|&next| next != from && open.get(next).copied().unwrap_or(false))
+        .count();
+
+    interior
+        && grid.cell(wall) == Some(Cell::Wall)
+        && !open.get(wall).copied().unwrap_or(true)
+        && passages >= if from == wall { 2 } else { 1 }
+}
+
+/// Knocks out the given wall, turning it into an open path.
+fn knock(grid: &Grid, open: &mut [bool], rows: &mut [String], wall: NodeId) {
+    let position = grid.position(wall);
+    let (x, y) = grid.local(position);
+    // Floors are written one after the other, each followed by a delimiter line
+    let row = grid.floor_of(position) * (grid.height() + 1) + y;
+
+    if let Some(cell) = open.get_mut(wall) {
+        *cell = true;
+    }
+    if let Some(line) = rows.get_mut(row) {
+        line.replace_range(x..=x, "3");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{map::Metadata, pathfinding, testing::TempDir};
+
+    /// Counts the dead ends of the given map.
+    fn dead_ends(map: &Map) -> usize {
+        let grid = Grid::of(map).expect("the map should form a grid");
+        let open = (0..grid.width() * grid.height())
+            .map(|node| grid.cell(node).is_some_and(|cell| cell != Cell::Wall))
+            .collect::<Vec<_>>();
+        (0..open.len())
+            .filter(|&node| is_dead_end(&grid, &open, node))
+            .count()
+    }
+
+    #[test]
+    fn test_braid_dead_ends() {
+        // A comb, each of whose three teeth ends in a dead end
+        let maze = Map {
+            key: "comb".to_owned(),
+            data: ["2222222", "2133334", "2323232", "2323232", "2222222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        };
+        assert_eq!(dead_ends(&maze), 3);
+
+        for seed in 0..10 {
+            let braid = braid(
+                &maze,
+                &BraidSettings {
+                    seed: Some(seed),
+                    ..BraidSettings::default()
+                },
+            )
+            .expect("the maze should be braided");
+
+            assert_eq!((braid.dead_ends, braid.removed, braid.loops), (3, 3, 0));
+            assert_eq!(dead_ends(&braid.map), 0);
+            assert!(file_loader::parse_file_contents(&braid.map.to_labmap()));
+        }
+
+        // Knocking out the wall between two teeth removes both of their dead ends at once
+        let half = braid(
+            &maze,
+            &BraidSettings {
+                dead_ends: 50,
+                seed: Some(1),
+                ..BraidSettings::default()
+            },
+        )
+        .expect("the maze should be braided");
+        assert_eq!((half.removed, dead_ends(&half.map)), (2, 1));
+        assert!(braid(
+            &maze,
+            &BraidSettings {
+                dead_ends: 101,
+                ..BraidSettings::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_braid_loops() {
+        let maze = Map {
+            key: "shortcut".to_owned(),
+            data: ["2222", "2132", "2232", "4332", "2222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        };
+        let braid = braid(
+            &maze,
+            &BraidSettings {
+                dead_ends: 0,
+                loops: 2,
+                seed: Some(7),
+            },
+        )
+        .expect("the maze should be braided");
+
+        // The only wall inside the border is knocked out, which makes for a shorter way out
+        assert_eq!(braid.loops, 1);
+        assert_eq!(braid.map.data, vec!["2222", "2132", "2332", "4332", "2222"]);
+        let grid = Grid::of(&braid.map).expect("the map should form a grid");
+        let entry = grid.node((1, 1)).expect("the entry lies in the maze");
+        assert_eq!(
+            pathfinding::shortest_path(&grid, entry).map(|path| path.len()),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_braid_file() {
+        let dir = TempDir::new("braid");
+        let input = dir.join("comb.labmap");
+        fs::write(
+            &input,
+            "labmap 2\ntitle: Comb\n---\n2222222\n2133334\n2323232\n2323232\n2222222\n",
+        )
+        .expect("failed to write the test map");
+
+        let settings = BraidSettings {
+            seed: Some(1),
+            ..BraidSettings::default()
+        };
+        let braid = braid_file(&input, &dir.join("braided.labmap"), &settings);
+        let written = file_loader::load_map(&dir.join("braided.labmap"));
+        let text = braid_file(&input, &dir.join("braided.txt"), &settings);
+
+        let braid = braid.expect("the maze should be braided");
+        assert_eq!(braid.map.key, "braided");
+        assert_eq!(braid.map.metadata.title.as_deref(), Some("Comb"));
+        assert_eq!(written.expect("the braided map should load"), braid.map);
+        assert!(text.is_err());
+    }
+
+    #[test]
+    fn test_braid_floors() {
+        let maze = Map {
+            key: "floors".to_owned(),
+            data: [
+                "2222", "21+2", "2322", "2222", "===", "2222", "23-2", "2232", "2242",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+            metadata: Metadata::default(),
+        };
+        let braid = braid(
+            &maze,
+            &BraidSettings {
+                seed: Some(3),
+                ..BraidSettings::default()
+            },
+        )
+        .expect("the maze should be braided");
+
+        // Each floor has a dead end, joined to the passage next to it on its own floor
+        assert_eq!((braid.dead_ends, braid.removed), (2, 2));
+        assert_eq!(
+            braid.map.data,
+            vec!["2222", "21+2", "2332", "2222", "===", "2222", "23-2", "2332", "2242"]
+        );
+    }
+}
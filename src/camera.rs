@@ -0,0 +1,315 @@
+//! Camera and zoom handling for the in-game maze viewport.
+//!
+//! This module contains the camera used to pan across and zoom into mazes that are bigger than the
+//! terminal, and to look at the floors of multi-level mazes, along with the zoom levels that pick
+//! the canvas marker used to draw the maze.
+
+use ratatui::symbols::Marker;
+
+/// Zoom levels available for the in-game viewport.
+///
+/// This enumeration holds the different zoom levels of the maze canvas. Zooming out picks a denser
+/// marker so that more maze cells fit in a single terminal cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ZoomLevel {
+    /// One maze cell per terminal cell.
+    ///
+    /// This variant represents the closest zoom level, drawn with the [`Marker::Dot`] marker.
+    Close,
+    /// Two maze rows per terminal cell.
+    ///
+    /// This variant represents the intermediate zoom level, drawn with the [`Marker::HalfBlock`]
+    /// marker.
+    Medium,
+    /// Two maze columns and four maze rows per terminal cell.
+    ///
+    /// This variant represents the farthest zoom level, drawn with the [`Marker::Braille`] marker.
+    Far,
+}
+
+impl ZoomLevel {
+    /// Returns the canvas marker used to draw the maze at this zoom level.
+    pub(crate) const fn marker(self) -> Marker {
+        match self {
+            Self::Close => Marker::Dot,
+            Self::Medium => Marker::HalfBlock,
+            Self::Far => Marker::Braille,
+        }
+    }
+
+    /// Returns the number of maze cells per terminal cell as a `(columns, rows)` pair.
+    ///
+    /// This function mirrors the resolution of the marker returned by [`ZoomLevel::marker`], so
+    /// that each maze cell lands exactly on one dot of the canvas grid.
+    pub(crate) const fn resolution(self) -> (usize, usize) {
+        match self {
+            Self::Close => (1, 1),
+            Self::Medium => (1, 2),
+            Self::Far => (2, 4),
+        }
+    }
+
+    /// Returns the next zoom level closer to the maze, if any.
+    pub(crate) const fn zoom_in(self) -> Self {
+        match self {
+            Self::Close | Self::Medium => Self::Close,
+            Self::Far => Self::Medium,
+        }
+    }
+
+    /// Returns the next zoom level farther from the maze, if any.
+    pub(crate) const fn zoom_out(self) -> Self {
+        match self {
+            Self::Close => Self::Medium,
+            Self::Medium | Self::Far => Self::Far,
+        }
+    }
+}
+
+/// Camera over the maze being displayed in-game.
+///
+/// This structure holds the top-left maze cell shown in the viewport, the current zoom level, the
+/// floor shown and whether the camera should automatically follow the head of the pathfinding
+/// animation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Camera {
+    /// Leftmost maze column shown in the viewport.
+    ///
+    /// This field is only meaningful when the maze is wider than the viewport; narrower mazes are
+    /// centered instead.
+    pub column: usize,
+    /// Topmost maze row shown in the viewport.
+    ///
+    /// This field is only meaningful when the maze is taller than the viewport; shorter mazes are
+    /// centered instead.
+    pub row: usize,
+    /// Current zoom level of the viewport.
+    pub zoom: ZoomLevel,
+    /// Floor of a multi-level maze shown in the viewport.
+    ///
+    /// This field holds the floor shown on its own, counted from the ground floor, or `None` to
+    /// show every floor side by side. Mazes with a single floor are always shown whole.
+    pub floor: Option<usize>,
+    /// Auto-follow flag.
+    ///
+    /// This field indicates whether the camera should keep the solver's head cell in view. It is
+    /// turned off whenever the user pans manually.
+    pub follow: bool,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Camera {
+    /// Creates a new camera at the top-left corner of the ground floor of the maze with the closest
+    /// zoom level.
+    pub(crate) const fn new() -> Self {
+        Self {
+            column: 0,
+            row: 0,
+            zoom: ZoomLevel::Close,
+            floor: Some(0),
+            follow: true,
+        }
+    }
+
+    /// Shows the floor above the current one out of the given number of floors, every floor side
+    /// by side after the top one, and the ground floor after that.
+    ///
+    /// Showing another floor manually disables auto-follow, which would otherwise bring the floor
+    /// the solver is on back into view.
+    pub(crate) const fn cycle_floor(&mut self, floors: usize) {
+        self.floor = match self.floor {
+            Some(floor) if floor + 1 < floors => Some(floor + 1),
+            Some(_) => None,
+            None => Some(0),
+        };
+        self.follow = false;
+    }
+
+    /// Pans the camera by the given amount of terminal cells.
+    ///
+    /// The amount is scaled by the resolution of the current zoom level, so that a single pan step
+    /// always moves the view by one terminal cell. Panning manually disables auto-follow.
+    pub(crate) fn pan(&mut self, columns: isize, rows: isize) {
+        let (column_resolution, row_resolution) = self.zoom.resolution();
+        let column_resolution = isize::try_from(column_resolution).unwrap_or(isize::MAX);
+        let row_resolution = isize::try_from(row_resolution).unwrap_or(isize::MAX);
+
+        self.column = self
+            .column
+            .saturating_add_signed(columns.saturating_mul(column_resolution));
+        self.row = self
+            .row
+            .saturating_add_signed(rows.saturating_mul(row_resolution));
+        self.follow = false;
+    }
+
+    /// Returns the amount of maze cells that fit in an area of the given terminal size.
+    pub(crate) const fn visible_cells(&self, width: u16, height: u16) -> (usize, usize) {
+        let (column_resolution, row_resolution) = self.zoom.resolution();
+
+        (
+            width as usize * column_resolution,
+            height as usize * row_resolution,
+        )
+    }
+
+    /// Moves the camera so that the given maze cell lies within the visible region.
+    ///
+    /// The cell is kept at least a quarter of the viewport away from its edges when possible, so
+    /// that the upcoming cells of the path are visible before the solver reaches them.
+    pub(crate) const fn keep_in_view(&mut self, cell: (usize, usize), visible: (usize, usize)) {
+        let (column, row) = cell;
+        let (visible_columns, visible_rows) = visible;
+
+        self.column = scroll_into_view(self.column, column, visible_columns);
+        self.row = scroll_into_view(self.row, row, visible_rows);
+    }
+
+    /// Clamps the camera position so that the viewport never goes past the maze boundaries.
+    pub(crate) fn clamp(&mut self, maze: (usize, usize), visible: (usize, usize)) {
+        self.column = self.column.min(maze.0.saturating_sub(visible.0));
+        self.row = self.row.min(maze.1.saturating_sub(visible.1));
+    }
+
+    /// Returns the leftmost and topmost maze coordinates shown in the viewport.
+    ///
+    /// Mazes smaller than the viewport along an axis are centered along that axis, which results in
+    /// a negative origin.
+    pub(crate) fn origin(&self, maze: (usize, usize), visible: (usize, usize)) -> (i64, i64) {
+        (
+            axis_origin(self.column, maze.0, visible.0),
+            axis_origin(self.row, maze.1, visible.1),
+        )
+    }
+}
+
+/// Returns the offset that keeps a position within a window along a single axis.
+const fn scroll_into_view(offset: usize, position: usize, visible: usize) -> usize {
+    let margin = visible / 4;
+
+    if position < offset + margin {
+        position.saturating_sub(margin)
+    } else if position + margin >= offset + visible {
+        (position + margin + 1).saturating_sub(visible)
+    } else {
+        offset
+    }
+}
+
+/// Returns the origin of the viewport along a single axis.
+fn axis_origin(offset: usize, maze: usize, visible: usize) -> i64 {
+    let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+    let maze = i64::try_from(maze).unwrap_or(i64::MAX);
+    let visible = i64::try_from(visible).unwrap_or(i64::MAX);
+
+    if maze <= visible {
+        -((visible - maze) / 2)
+    } else {
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom_level_cycle() {
+        assert_eq!(ZoomLevel::Close.zoom_out(), ZoomLevel::Medium);
+        assert_eq!(ZoomLevel::Medium.zoom_out(), ZoomLevel::Far);
+        assert_eq!(ZoomLevel::Far.zoom_out(), ZoomLevel::Far);
+        assert_eq!(ZoomLevel::Far.zoom_in(), ZoomLevel::Medium);
+        assert_eq!(ZoomLevel::Medium.zoom_in(), ZoomLevel::Close);
+        assert_eq!(ZoomLevel::Close.zoom_in(), ZoomLevel::Close);
+    }
+
+    #[test]
+    fn test_zoom_level_marker_matches_resolution() {
+        assert_eq!(ZoomLevel::Close.marker(), Marker::Dot);
+        assert_eq!(ZoomLevel::Close.resolution(), (1, 1));
+        assert_eq!(ZoomLevel::Medium.marker(), Marker::HalfBlock);
+        assert_eq!(ZoomLevel::Medium.resolution(), (1, 2));
+        assert_eq!(ZoomLevel::Far.marker(), Marker::Braille);
+        assert_eq!(ZoomLevel::Far.resolution(), (2, 4));
+    }
+
+    #[test]
+    fn test_camera_pan_disables_follow() {
+        let mut camera = Camera::new();
+        assert!(camera.follow);
+
+        camera.pan(3, 2);
+
+        assert_eq!((camera.column, camera.row), (3, 2));
+        assert!(!camera.follow);
+    }
+
+    #[test]
+    fn test_camera_pan_scales_with_zoom() {
+        let mut camera = Camera::new();
+        camera.zoom = ZoomLevel::Far;
+
+        camera.pan(1, 1);
+        assert_eq!((camera.column, camera.row), (2, 4));
+
+        camera.pan(-5, -5);
+        assert_eq!((camera.column, camera.row), (0, 0));
+    }
+
+    #[test]
+    fn test_camera_cycle_floor() {
+        let mut camera = Camera::new();
+
+        camera.cycle_floor(2);
+        assert_eq!(camera.floor, Some(1));
+        assert!(!camera.follow);
+        camera.cycle_floor(2);
+        assert_eq!(camera.floor, None);
+        camera.cycle_floor(2);
+        assert_eq!(camera.floor, Some(0));
+    }
+
+    #[test]
+    fn test_camera_clamp() {
+        let mut camera = Camera::new();
+        camera.column = 100;
+        camera.row = 100;
+
+        camera.clamp((50, 30), (20, 10));
+
+        assert_eq!((camera.column, camera.row), (30, 20));
+    }
+
+    #[test]
+    fn test_camera_keep_in_view() {
+        let mut camera = Camera::new();
+
+        camera.keep_in_view((35, 3), (20, 10));
+        assert_eq!(camera.column, 21);
+        assert_eq!(camera.row, 0);
+
+        camera.keep_in_view((2, 3), (20, 10));
+        assert_eq!(camera.column, 0);
+    }
+
+    #[test]
+    fn test_camera_origin_centers_small_mazes() {
+        let camera = Camera::new();
+
+        assert_eq!(camera.origin((10, 6), (20, 10)), (-5, -2));
+    }
+
+    #[test]
+    fn test_camera_origin_uses_offset_for_large_mazes() {
+        let mut camera = Camera::new();
+        camera.column = 7;
+        camera.row = 4;
+
+        assert_eq!(camera.origin((100, 50), (20, 10)), (7, 4));
+    }
+}
@@ -0,0 +1,845 @@
+//! Command-line argument parsing.
+//!
+//! This module contains the options the binary accepts and the parser turning the raw arguments
+//! into them.
+
+use std::{ffi::OsString, path::PathBuf};
+
+use color_eyre::eyre::{bail, eyre, OptionExt as _, Result};
+
+use crate::{
+    braid::BraidSettings,
+    export::ImageStyle,
+    generate::{DungeonSettings, GenerateSettings},
+    grid::Topology,
+    import::ImportSettings,
+};
+
+/// Usage message printed by `--help`.
+pub const USAGE: &str = "\
+Usage: labyrintuine [OPTIONS]
+       labyrintuine export [EXPORT OPTIONS] <FILE>
+       labyrintuine import [IMPORT OPTIONS] <INPUT> <OUTPUT>
+       labyrintuine generate [GENERATE OPTIONS] <OUTPUT>
+       labyrintuine braid [BRAID OPTIONS] <INPUT> <OUTPUT>
+       labyrintuine analyze [FILE]
+
+Options:
+  --record <FILE>        Log every input event of the session to FILE
+  --replay <FILE>        Feed the input events logged in FILE before reading the terminal
+  --headless             Replay without a terminal and exit, requires --replay
+  --expect <FIELD=VALUE> Fail a headless replay unless FIELD ends up as VALUE, may be repeated
+  --map <FILE>           Play or export the map in FILE instead of the default one
+  --export-cast <FILE>   Write the solver animation to FILE as an asciicast and exit
+  --size <WIDTHxHEIGHT>  Terminal size of an exported asciicast, 80x24 by default
+  -h, --help             Print this message
+
+Export options, for writing an image of the maze to FILE, which must end in .svg or .png:
+  --map <FILE>           Export the map in FILE instead of the default one
+  --solution             Draw the path found by the solver
+  --cell-size <PIXELS>   Side length of a maze cell, 16 by default
+  --color <PART=#RRGGBB> Colour of background, wall, path, entry or exit, may be repeated
+
+Import options, for converting the maze in INPUT, a PBM or PGM image or else ASCII art, to the
+.labmap file OUTPUT:
+  --entry <COL,ROW>      Cell of the entry point, inside the first border opening by default
+  --exit <COL,ROW>       Cell of the exit, the last border opening by default
+  --threshold <PERCENT>  Brightness below which image pixels are walls, 50 by default
+  --scale <PIXELS>       Side length of a maze cell in the image, detected by default
+  --wall <CHARS>         Characters of walls in ASCII art, \"#+-|\" by default
+  --path <CHARS>         Characters of paths in ASCII art, \" .\" by default
+
+Generate options, for carving a random maze into the .labmap file OUTPUT:
+  --topology <NAME>      Shape of the cells, square, hex or triangle, square by default
+  --size <COLSxROWS>     Size of the maze, border included, 31x21 by default
+  --seed <NUMBER>        Seed of the maze, picked from the clock by default
+  --weave                Let corridors pass under each other, in square mazes only
+  --dungeon              Lay out rooms joined by corridors instead, in square mazes only
+  --rooms <NUMBER>       Number of rooms of a dungeon, 6 by default
+  --room-size <MIN-MAX>  Size range of the rooms of a dungeon, 3x3-7x5 by default
+  --loop-percent <N>     Extra corridors of a dungeon making loops, 25 by default
+  --exits <NUMBER>       Number of exits of a dungeon, next to the farthest rooms, 1 by default
+
+Giving any of the dungeon options lays out a dungeon.
+
+Braid options, for knocking walls out of the maze of the .labmap file INPUT into the .labmap file
+OUTPUT:
+  --dead-ends <PERCENT>  Share of the dead ends to remove, 100 by default
+  --loops <NUMBER>       Number of loops to add between passages, 0 by default
+  --seed <NUMBER>        Seed picking the walls, picked from the clock by default
+
+Analyze prints the difficulty rating and metrics of the map in FILE, or of the default one, as JSON.
+";
+
+/// Options given to the binary on the command line.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    /// File to record the input events of the session to, if any.
+    pub record: Option<PathBuf>,
+    /// File to replay input events from, if any.
+    pub replay: Option<PathBuf>,
+    /// Whether to replay without a terminal and exit.
+    pub headless: bool,
+    /// Expected `(field, value)` pairs of the application state at the end of a headless replay.
+    pub expectations: Vec<(String, String)>,
+    /// File to load the map from, if any.
+    pub map: Option<PathBuf>,
+    /// File to export the solver animation to as an asciicast, if any.
+    pub export_cast: Option<PathBuf>,
+    /// Size of the exported asciicast, as a `(width, height)` pair of terminal cells, if given.
+    pub size: Option<(u16, u16)>,
+    /// Image of the maze to write, if the `export` subcommand was given.
+    pub image: Option<ImageExport>,
+    /// Maze to convert to a labyrinth map, if the `import` subcommand was given.
+    pub import: Option<MapImport>,
+    /// Maze to generate, if the `generate` subcommand was given.
+    pub generate: Option<MapGeneration>,
+    /// Maze to braid, if the `braid` subcommand was given.
+    pub braid: Option<MapBraid>,
+    /// Whether to print the analysis of the map, if the `analyze` subcommand was given.
+    pub analyze: bool,
+    /// Whether to print the usage message and exit.
+    pub help: bool,
+}
+
+/// Image of a maze to write, as requested with the `export` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageExport {
+    /// File to write the image to, whose extension picks the image format.
+    pub path: PathBuf,
+    /// Whether to draw the path found by the solver.
+    pub solution: bool,
+    /// Appearance of the image.
+    pub style: ImageStyle,
+}
+
+/// Maze to convert to a labyrinth map, as requested with the `import` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapImport {
+    /// File holding the maze to convert.
+    pub input: PathBuf,
+    /// File to write the labyrinth map to.
+    pub output: PathBuf,
+    /// How the maze is read and where its entry point and exit go.
+    pub settings: ImportSettings,
+}
+
+/// Maze to generate, as requested with the `generate` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapGeneration {
+    /// File to write the labyrinth map to.
+    pub output: PathBuf,
+    /// Shape, size and seed of the maze.
+    pub settings: GenerateSettings,
+}
+
+/// Maze to knock walls out of, as requested with the `braid` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapBraid {
+    /// Labyrinth map holding the maze to braid.
+    pub input: PathBuf,
+    /// File to write the braided labyrinth map to.
+    pub output: PathBuf,
+    /// Dead ends to remove, loops to add and seed picking the walls.
+    pub settings: BraidSettings,
+}
+
+impl Options {
+    /// Parses the options from the given arguments, excluding the name of the binary.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if an argument is unknown, is missing its value, isn't valid
+    /// UTF-8 where it should be, or conflicts with another argument.
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter().peekable();
+
+        if args.next_if(|arg| arg == "export").is_some() {
+            return Self::parse_export(args);
+        }
+        if args.next_if(|arg| arg == "import").is_some() {
+            return Self::parse_import(args);
+        }
+        if args.next_if(|arg| arg == "generate").is_some() {
+            return Self::parse_generate(args);
+        }
+        if args.next_if(|arg| arg == "braid").is_some() {
+            return Self::parse_braid(args);
+        }
+        if args.next_if(|arg| arg == "analyze").is_some() {
+            return Self::parse_analyze(args);
+        }
+
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| eyre!("missing value for {name}"));
+
+            match arg.to_str() {
+                Some("--record") => options.record = Some(value("--record")?.into()),
+                Some("--replay") => options.replay = Some(value("--replay")?.into()),
+                Some("--headless") => options.headless = true,
+                Some("--expect") => {
+                    let expectation = utf8(value("--expect")?, "--expect")?;
+                    let (field, expected) = expectation
+                        .split_once('=')
+                        .ok_or_eyre("--expect must be of the form FIELD=VALUE")?;
+
+                    options
+                        .expectations
+                        .push((field.to_owned(), expected.to_owned()));
+                }
+                Some("--map") => options.map = Some(value("--map")?.into()),
+                Some("--export-cast") => {
+                    options.export_cast = Some(value("--export-cast")?.into());
+                }
+                Some("--size") => {
+                    options.size = Some(parse_size(&utf8(value("--size")?, "--size")?, "--size")?);
+                }
+                Some("-h" | "--help") => options.help = true,
+                _ => bail!("unknown argument {}\n\n{USAGE}", arg.to_string_lossy()),
+            }
+        }
+
+        if options.headless && options.replay.is_none() {
+            bail!("--headless requires --replay");
+        }
+        if options.headless && options.record.is_some() {
+            bail!("--record can't be used with --headless");
+        }
+        if !options.expectations.is_empty() && !options.headless {
+            bail!("--expect requires --headless");
+        }
+
+        if options.export_cast.is_some() && (options.record.is_some() || options.replay.is_some()) {
+            bail!("--export-cast can't be used with --record or --replay");
+        }
+        if options.size.is_some() && options.export_cast.is_none() {
+            bail!("--size requires --export-cast");
+        }
+
+        Ok(options)
+    }
+
+    /// Parses the arguments following the `export` subcommand.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if an argument is unknown or invalid, or if the file to write
+    /// the image to is missing.
+    fn parse_export<I: Iterator<Item = OsString>>(mut args: I) -> Result<Self> {
+        let mut options = Self::default();
+        let mut path = None;
+        let mut solution = false;
+        let mut style = ImageStyle::default();
+
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| eyre!("missing value for {name}"));
+
+            match arg.to_str() {
+                Some("--map") => options.map = Some(value("--map")?.into()),
+                Some("--solution") => solution = true,
+                Some("--cell-size") => {
+                    let size = utf8(value("--cell-size")?, "--cell-size")?;
+                    style.cell_size =
+                        size.parse().ok().filter(|&size| size > 0).ok_or_else(|| {
+                            eyre!("--cell-size must be a positive integer, found {size}")
+                        })?;
+                }
+                Some("--color") => {
+                    let color = utf8(value("--color")?, "--color")?;
+                    let (part, color) = color
+                        .split_once('=')
+                        .ok_or_eyre("--color must be of the form PART=#RRGGBB")?;
+
+                    style.set_color(part, color)?;
+                }
+                Some("-h" | "--help") => options.help = true,
+                Some(flag) if flag.starts_with('-') => {
+                    bail!("unknown export argument {flag}\n\n{USAGE}");
+                }
+                _ if path.is_none() => path = Some(PathBuf::from(arg)),
+                _ => bail!("unexpected argument {}\n\n{USAGE}", arg.to_string_lossy()),
+            }
+        }
+
+        if !options.help {
+            options.image = Some(ImageExport {
+                path: path.ok_or_eyre("export requires the FILE to write the image to")?,
+                solution,
+                style,
+            });
+        }
+
+        Ok(options)
+    }
+
+    /// Parses the arguments following the `import` subcommand.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if an argument is unknown or invalid, or if the input or
+    /// output file is missing.
+    fn parse_import<I: Iterator<Item = OsString>>(mut args: I) -> Result<Self> {
+        let mut options = Self::default();
+        let mut files = Vec::new();
+        let mut settings = ImportSettings::default();
+
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| eyre!("missing value for {name}"));
+
+            match arg.to_str() {
+                Some("--entry") => settings.entry = Some(parse_cell(&value("--entry")?)?),
+                Some("--exit") => settings.exit = Some(parse_cell(&value("--exit")?)?),
+                Some("--threshold") => {
+                    let threshold = utf8(value("--threshold")?, "--threshold")?;
+                    settings.threshold = threshold
+                        .parse()
+                        .ok()
+                        .filter(|&threshold| threshold <= 100)
+                        .ok_or_else(|| {
+                            eyre!("--threshold must be a percentage, found {threshold}")
+                        })?;
+                }
+                Some("--scale") => {
+                    let scale = utf8(value("--scale")?, "--scale")?;
+                    settings.scale = Some(
+                        scale
+                            .parse()
+                            .ok()
+                            .filter(|&scale| scale > 0)
+                            .ok_or_else(|| {
+                                eyre!("--scale must be a positive integer, found {scale}")
+                            })?,
+                    );
+                }
+                Some("--wall") => settings.walls = utf8(value("--wall")?, "--wall")?,
+                Some("--path") => settings.paths = utf8(value("--path")?, "--path")?,
+                Some("-h" | "--help") => options.help = true,
+                Some(flag) if flag.starts_with('-') => {
+                    bail!("unknown import argument {flag}\n\n{USAGE}");
+                }
+                _ => files.push(PathBuf::from(arg)),
+            }
+        }
+
+        if !options.help {
+            let mut files = files.into_iter();
+            let (Some(input), Some(output), None) = (files.next(), files.next(), files.next())
+            else {
+                bail!("import requires exactly an INPUT and an OUTPUT file\n\n{USAGE}");
+            };
+
+            options.import = Some(MapImport {
+                input,
+                output,
+                settings,
+            });
+        }
+
+        Ok(options)
+    }
+
+    /// Parses the arguments following the `generate` subcommand.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if an argument is unknown or invalid, or if the output file
+    /// is missing.
+    fn parse_generate<I: Iterator<Item = OsString>>(mut args: I) -> Result<Self> {
+        let mut options = Self::default();
+        let mut output = None;
+        let mut settings = GenerateSettings::default();
+
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| eyre!("missing value for {name}"));
+
+            match arg.to_str() {
+                Some("--topology") => {
+                    let name = utf8(value("--topology")?, "--topology")?;
+                    settings.topology = Topology::from_name(&name).ok_or_else(|| {
+                        eyre!("--topology must be square, hex or triangle, found {name}")
+                    })?;
+                }
+                Some("--size") => {
+                    let (columns, rows) = parse_size(&utf8(value("--size")?, "--size")?, "--size")?;
+                    settings.size = (usize::from(columns), usize::from(rows));
+                }
+                Some("--seed") => {
+                    let seed = utf8(value("--seed")?, "--seed")?;
+                    settings.seed =
+                        Some(seed.parse().map_err(|err| {
+                            eyre!("--seed must be a number, found {seed}: {err}")
+                        })?);
+                }
+                Some("--weave") => settings.weave = true,
+                Some("--dungeon") => {
+                    let _ = settings
+                        .dungeon
+                        .get_or_insert_with(DungeonSettings::default);
+                }
+                Some("--rooms") => {
+                    let rooms = parse_number(value("--rooms")?, "--rooms")?;
+                    settings
+                        .dungeon
+                        .get_or_insert_with(DungeonSettings::default)
+                        .rooms = rooms;
+                }
+                Some("--room-size") => {
+                    let range = utf8(value("--room-size")?, "--room-size")?;
+                    let (min, max) = range.split_once('-').ok_or_else(|| {
+                        eyre!("--room-size must be of the form MINxMIN-MAXxMAX, found {range}")
+                    })?;
+                    let ((min_columns, min_rows), (max_columns, max_rows)) = (
+                        parse_size(min, "--room-size")?,
+                        parse_size(max, "--room-size")?,
+                    );
+                    let dungeon = settings
+                        .dungeon
+                        .get_or_insert_with(DungeonSettings::default);
+                    dungeon.min_room = (usize::from(min_columns), usize::from(min_rows));
+                    dungeon.max_room = (usize::from(max_columns), usize::from(max_rows));
+                }
+                Some("--loop-percent") => {
+                    let loop_percent = parse_number(value("--loop-percent")?, "--loop-percent")?;
+                    settings
+                        .dungeon
+                        .get_or_insert_with(DungeonSettings::default)
+                        .loop_percent = loop_percent;
+                }
+                Some("--exits") => {
+                    let exits = parse_number(value("--exits")?, "--exits")?;
+                    settings
+                        .dungeon
+                        .get_or_insert_with(DungeonSettings::default)
+                        .exits = exits;
+                }
+                Some("-h" | "--help") => options.help = true,
+                Some(flag) if flag.starts_with('-') => {
+                    bail!("unknown generate argument {flag}\n\n{USAGE}");
+                }
+                _ if output.is_none() => output = Some(PathBuf::from(arg)),
+                _ => bail!("unexpected argument {}\n\n{USAGE}", arg.to_string_lossy()),
+            }
+        }
+
+        if !options.help {
+            options.generate = Some(MapGeneration {
+                output: output
+                    .ok_or_eyre("generate requires the OUTPUT file to write the map to")?,
+                settings,
+            });
+        }
+
+        Ok(options)
+    }
+
+    /// Parses the arguments following the `analyze` subcommand.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if an argument is unknown or if more than one file is given.
+    fn parse_analyze<I: Iterator<Item = OsString>>(args: I) -> Result<Self> {
+        let mut options = Self {
+            analyze: true,
+            ..Self::default()
+        };
+
+        for arg in args {
+            match arg.to_str() {
+                Some("-h" | "--help") => options.help = true,
+                Some(flag) if flag.starts_with('-') => {
+                    bail!("unknown analyze argument {flag}\n\n{USAGE}");
+                }
+                _ if options.map.is_none() => options.map = Some(PathBuf::from(arg)),
+                _ => bail!("unexpected argument {}\n\n{USAGE}", arg.to_string_lossy()),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Parses the arguments following the `braid` subcommand.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if an argument is unknown or invalid, or if the input or
+    /// output file is missing.
+    fn parse_braid<I: Iterator<Item = OsString>>(mut args: I) -> Result<Self> {
+        let mut options = Self::default();
+        let mut files = Vec::new();
+        let mut settings = BraidSettings::default();
+
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| eyre!("missing value for {name}"));
+
+            match arg.to_str() {
+                Some("--dead-ends") => {
+                    let dead_ends = utf8(value("--dead-ends")?, "--dead-ends")?;
+                    settings.dead_ends = dead_ends
+                        .parse()
+                        .ok()
+                        .filter(|&dead_ends| dead_ends <= 100)
+                        .ok_or_else(|| {
+                            eyre!("--dead-ends must be a percentage, found {dead_ends}")
+                        })?;
+                }
+                Some("--loops") => settings.loops = parse_number(value("--loops")?, "--loops")?,
+                Some("--seed") => {
+                    let seed = utf8(value("--seed")?, "--seed")?;
+                    settings.seed =
+                        Some(seed.parse().map_err(|err| {
+                            eyre!("--seed must be a number, found {seed}: {err}")
+                        })?);
+                }
+                Some("-h" | "--help") => options.help = true,
+                Some(flag) if flag.starts_with('-') => {
+                    bail!("unknown braid argument {flag}\n\n{USAGE}");
+                }
+                _ => files.push(PathBuf::from(arg)),
+            }
+        }
+
+        if !options.help {
+            let mut files = files.into_iter();
+            let (Some(input), Some(output), None) = (files.next(), files.next(), files.next())
+            else {
+                bail!("braid requires exactly an INPUT and an OUTPUT file\n\n{USAGE}");
+            };
+
+            options.braid = Some(MapBraid {
+                input,
+                output,
+                settings,
+            });
+        }
+
+        Ok(options)
+    }
+}
+
+/// Converts an argument value to a string.
+fn utf8(arg: OsString, name: &str) -> Result<String> {
+    arg.into_string().map_err(|arg| {
+        eyre!(
+            "{name} must be valid UTF-8, found {}",
+            arg.to_string_lossy()
+        )
+    })
+}
+
+/// Parses the value of the option with the given name as a number.
+fn parse_number(arg: OsString, name: &str) -> Result<usize> {
+    let number = utf8(arg, name)?;
+    number
+        .parse()
+        .map_err(|err| eyre!("{name} must be a number, found {number}: {err}"))
+}
+
+/// Parses maze cell coordinates of the form `COL,ROW`.
+fn parse_cell(arg: &OsString) -> Result<(usize, usize)> {
+    let cell = arg.to_string_lossy();
+
+    cell.split_once(',')
+        .and_then(|(column, row)| Some((column.trim().parse().ok()?, row.trim().parse().ok()?)))
+        .ok_or_else(|| eyre!("cells must be of the form COL,ROW, found {cell}"))
+}
+
+/// Parses a size of the form `WIDTHxHEIGHT`, given to the option with the given name.
+fn parse_size(size: &str, name: &str) -> Result<(u16, u16)> {
+    let (width, height) = size
+        .split_once('x')
+        .ok_or_else(|| eyre!("{name} must be of the form WIDTHxHEIGHT, found {size}"))?;
+
+    let parse = |value: &str| {
+        value
+            .parse::<u16>()
+            .ok()
+            .filter(|&value| value > 0)
+            .ok_or_else(|| eyre!("{name} must be made of positive integers, found {size}"))
+    };
+
+    Ok((parse(width)?, parse(height)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the options from a list of string arguments.
+    fn parse(args: &[&str]) -> Result<Options> {
+        Options::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn test_parse_no_arguments() {
+        assert_eq!(
+            parse(&[]).expect("no arguments should parse"),
+            Options::default()
+        );
+    }
+
+    #[test]
+    fn test_parse_headless_replay() {
+        let options = parse(&[
+            "--replay",
+            "bug.rec",
+            "--headless",
+            "--expect",
+            "screen=MapMenu",
+            "--expect",
+            "offset=3",
+        ])
+        .expect("a headless replay should parse");
+
+        assert_eq!(options.replay, Some(PathBuf::from("bug.rec")));
+        assert!(options.headless);
+        assert_eq!(
+            options.expectations,
+            vec![
+                ("screen".to_owned(), "MapMenu".to_owned()),
+                ("offset".to_owned(), "3".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_export_cast() {
+        let options = parse(&[
+            "--map",
+            "maze.labmap",
+            "--export-cast",
+            "maze.cast",
+            "--size",
+            "120x40",
+        ])
+        .expect("an asciicast export should parse");
+
+        assert_eq!(options.map, Some(PathBuf::from("maze.labmap")));
+        assert_eq!(options.export_cast, Some(PathBuf::from("maze.cast")));
+        assert_eq!(options.size, Some((120, 40)));
+    }
+
+    #[test]
+    fn test_parse_export_subcommand() {
+        let options = parse(&[
+            "export",
+            "--map",
+            "maze.labmap",
+            "--solution",
+            "--cell-size",
+            "8",
+            "--color",
+            "path=#0000ff",
+            "maze.svg",
+        ])
+        .expect("an image export should parse");
+
+        assert_eq!(options.map, Some(PathBuf::from("maze.labmap")));
+        assert_eq!(
+            options.image,
+            Some(ImageExport {
+                path: PathBuf::from("maze.svg"),
+                solution: true,
+                style: ImageStyle {
+                    cell_size: 8,
+                    path: [0, 0, 0xff],
+                    ..ImageStyle::default()
+                },
+            })
+        );
+        assert!(
+            parse(&["export", "--help"])
+                .expect("asking for help should parse")
+                .help
+        );
+    }
+
+    #[test]
+    fn test_parse_import_subcommand() {
+        let options = parse(&[
+            "import",
+            "--entry",
+            "1,2",
+            "--exit",
+            "0,5",
+            "--threshold",
+            "30",
+            "--scale",
+            "4",
+            "--wall",
+            "X",
+            "maze.pgm",
+            "maze.labmap",
+        ])
+        .expect("an import should parse");
+
+        assert_eq!(
+            options.import,
+            Some(MapImport {
+                input: PathBuf::from("maze.pgm"),
+                output: PathBuf::from("maze.labmap"),
+                settings: ImportSettings {
+                    entry: Some((1, 2)),
+                    exit: Some((0, 5)),
+                    threshold: 30,
+                    scale: Some(4),
+                    walls: "X".to_owned(),
+                    ..ImportSettings::default()
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_generate_subcommand() {
+        let options = parse(&[
+            "generate",
+            "--topology",
+            "hex",
+            "--size",
+            "21x11",
+            "--seed",
+            "42",
+            "--weave",
+            "hive.labmap",
+        ])
+        .expect("a generation should parse");
+
+        assert_eq!(
+            options.generate,
+            Some(MapGeneration {
+                output: PathBuf::from("hive.labmap"),
+                settings: GenerateSettings {
+                    topology: Topology::Hex,
+                    size: (21, 11),
+                    seed: Some(42),
+                    weave: true,
+                    dungeon: None,
+                },
+            })
+        );
+
+        let options = parse(&[
+            "generate",
+            "--rooms",
+            "9",
+            "--room-size",
+            "4x3-8x6",
+            "--loop-percent",
+            "50",
+            "--exits",
+            "2",
+            "dungeon.labmap",
+        ])
+        .expect("a dungeon generation should parse");
+        assert_eq!(
+            options
+                .generate
+                .map(|generation| generation.settings.dungeon),
+            Some(Some(DungeonSettings {
+                rooms: 9,
+                min_room: (4, 3),
+                max_room: (8, 6),
+                loop_percent: 50,
+                exits: 2,
+            }))
+        );
+        assert_eq!(
+            parse(&["generate", "--dungeon", "dungeon.labmap"])
+                .expect("a dungeon generation should parse")
+                .generate
+                .map(|generation| generation.settings.dungeon),
+            Some(Some(DungeonSettings::default()))
+        );
+
+        // Loops of a dungeon are a share of its corridors, unlike the loops added by a braid
+        assert!(parse(&["generate", "--loops", "50", "dungeon.labmap"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_braid_subcommand() {
+        let options = parse(&[
+            "braid",
+            "--dead-ends",
+            "50",
+            "--loops",
+            "3",
+            "--seed",
+            "7",
+            "maze.labmap",
+            "braided.labmap",
+        ])
+        .expect("a braiding should parse");
+
+        assert_eq!(
+            options.braid,
+            Some(MapBraid {
+                input: PathBuf::from("maze.labmap"),
+                output: PathBuf::from("braided.labmap"),
+                settings: BraidSettings {
+                    dead_ends: 50,
+                    loops: 3,
+                    seed: Some(7),
+                },
+            })
+        );
+        assert_eq!(
+            parse(&["braid", "a.labmap", "b.labmap"])
+                .expect("a braiding should parse")
+                .braid
+                .map(|braid| braid.settings),
+            Some(BraidSettings::default())
+        );
+        assert!(parse(&["braid", "a.labmap"]).is_err());
+        assert!(parse(&["braid", "--dead-ends", "101", "a.labmap", "b.labmap"]).is_err());
+        assert!(parse(&["braid", "--loops", "-1", "a.labmap", "b.labmap"]).is_err());
+        assert!(parse(&["braid", "--weave", "a.labmap", "b.labmap"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_analyze_subcommand() {
+        let options = parse(&["analyze", "maze.labmap"]).expect("an analysis should parse");
+        assert!(options.analyze);
+        assert_eq!(options.map, Some(PathBuf::from("maze.labmap")));
+
+        let options = parse(&["analyze"]).expect("an analysis should parse");
+        assert!(options.analyze);
+        assert_eq!(options.map, None);
+
+        assert!(parse(&["analyze", "a.labmap", "b.labmap"]).is_err());
+        assert!(parse(&["analyze", "--json", "a.labmap"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&["--record"]).is_err());
+        assert!(parse(&["--frobnicate"]).is_err());
+        assert!(parse(&["--headless"]).is_err());
+        assert!(parse(&["--replay", "a", "--record", "b", "--headless"]).is_err());
+        assert!(parse(&["--expect", "screen=InGame"]).is_err());
+        assert!(parse(&["--replay", "a", "--headless", "--expect", "screen"]).is_err());
+        assert!(parse(&["--size", "80x24"]).is_err());
+        assert!(parse(&["--export-cast", "a", "--size", "80"]).is_err());
+        assert!(parse(&["--export-cast", "a", "--size", "0x24"]).is_err());
+        assert!(parse(&["--export-cast", "a", "--replay", "b"]).is_err());
+        assert!(parse(&["export"]).is_err());
+        assert!(parse(&["export", "a.png", "b.png"]).is_err());
+        assert!(parse(&["export", "--cell-size", "0", "a.png"]).is_err());
+        assert!(parse(&["export", "--color", "floor=#000000", "a.png"]).is_err());
+        assert!(parse(&["export", "--record", "a", "b.png"]).is_err());
+        assert!(parse(&["import", "maze.txt"]).is_err());
+        assert!(parse(&["import", "a", "b", "c"]).is_err());
+        assert!(parse(&["import", "--entry", "1", "a", "b"]).is_err());
+        assert!(parse(&["import", "--threshold", "101", "a", "b"]).is_err());
+        assert!(parse(&["generate"]).is_err());
+        assert!(parse(&["generate", "--topology", "round", "a.labmap"]).is_err());
+        assert!(parse(&["generate", "--seed", "-1", "a.labmap"]).is_err());
+        assert!(parse(&["generate", "--room-size", "3x3", "a.labmap"]).is_err());
+        assert!(parse(&["generate", "--rooms", "many", "a.labmap"]).is_err());
+    }
+}
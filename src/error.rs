@@ -0,0 +1,103 @@
+//! Error classification for failures that shouldn't end the session.
+//!
+//! This module contains the marker error type used to tell apart recoverable failures, which are
+//! reported to the user through a popup, from fatal ones, which bubble out of the main loop and end
+//! the session.
+
+use std::fmt::{self, Display, Formatter};
+
+use color_eyre::eyre::{Report, Result};
+
+/// Error that can be reported to the user without ending the session.
+///
+/// This structure holds the fully rendered message of the original error, including its chain of
+/// causes, so that it can be displayed in a popup once the error has been classified.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Recoverable(pub String);
+
+impl Display for Recoverable {
+    #[expect(
+        clippy::renamed_function_params,
+        reason = "The default name of the parameter is too short for the `min_ident_chars` lint."
+    )]
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Recoverable {}
+
+/// Extension trait marking the error of a result as recoverable.
+pub(crate) trait RecoverableExt<T> {
+    /// Converts the error of the result, if any, into a [`Recoverable`] error.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the original error, marked as recoverable.
+    fn recoverable(self) -> Result<T>;
+}
+
+impl<T> RecoverableExt<T> for Result<T> {
+    fn recoverable(self) -> Self {
+        self.map_err(|err| {
+            if is_recoverable(&err) {
+                err
+            } else {
+                Recoverable(format!("{err:#}")).into()
+            }
+        })
+    }
+}
+
+/// Returns whether the given error was marked as recoverable.
+pub(crate) fn is_recoverable(err: &Report) -> bool {
+    err.downcast_ref::<Recoverable>().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre::{eyre, WrapErr as _};
+
+    use super::*;
+
+    #[test]
+    fn test_recoverable_keeps_error_chain() {
+        let result: Result<()> = Err(eyre!("root cause")).wrap_err("failed to load map");
+
+        let err = result
+            .recoverable()
+            .expect_err("the error should be preserved");
+
+        assert!(is_recoverable(&err));
+        assert_eq!(err.to_string(), "failed to load map: root cause");
+    }
+
+    #[test]
+    fn test_recoverable_is_idempotent() {
+        let result: Result<()> = Err(eyre!("broken layout"));
+
+        let err = result
+            .recoverable()
+            .recoverable()
+            .expect_err("the error should be preserved");
+
+        assert_eq!(err.to_string(), "broken layout");
+    }
+
+    #[test]
+    fn test_unmarked_errors_are_fatal() {
+        let err = eyre!("terminal went away");
+
+        assert!(!is_recoverable(&err));
+    }
+
+    #[test]
+    fn test_recoverable_passes_through_ok() {
+        let result: Result<u8> = Ok(4);
+
+        assert_eq!(
+            result.recoverable().expect("ok values should pass through"),
+            4
+        );
+    }
+}
@@ -3,31 +3,64 @@
 use std::time::Duration;
 
 use color_eyre::eyre::{OptionExt as _, Result};
-use ratatui::crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind},
+    layout::{Position, Rect},
+    widgets::Block,
+};
 
 use crate::{
+    braid::{self, BraidSettings},
+    camera::Camera,
+    error::RecoverableExt as _,
+    export::{self, ImageStyle},
     file_loader,
+    input::EventSource,
+    keymap::Action,
     map::Map,
-    types::{MainMenuItem, OptionsMenuItem, Screen},
-    App,
+    replay::Replay,
+    types::{MainMenuItem, MenuType, OptionsMenuItem, Screen},
+    ui, App,
 };
 
+/// Maximum time to wait for an input event before redrawing the screen.
+pub(crate) const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Step by which the braid panel raises or lowers the share of dead ends to remove, in percent.
+pub(crate) const BRAID_PANEL_STEP: usize = 10;
+
 /// Handles input events and updates the application state accordingly.
 ///
-/// This function polls for keyboard events and dispatches them to the appropriate handler
-/// functions based on the key pressed. It uses a timeout to avoid blocking the UI.
-pub(crate) fn handle_events(app: &mut App) -> Result<()> {
-    if event::poll(Duration::from_millis(100))? {
-        if let Event::Key(key) = event::read()? {
-            match key.code {
-                KeyCode::Char('q') => app.exit = true,
-                KeyCode::Char('j') => handle_j_events(app)?,
-                KeyCode::Char('k') => handle_k_events(app)?,
-                KeyCode::Char('l') => handle_l_events(app)?,
-                KeyCode::Char('h') => handle_h_events(app),
-                _ => {}
+/// This function waits for the next input event, either from the recording being replayed or from
+/// the given source, logs it if a recording is in progress and dispatches it through
+/// [`handle_event`]. It uses a timeout to avoid blocking the UI.
+///
+/// # Errors
+///
+/// This function returns fatal errors if the input events can't be read, and recoverable errors
+/// if a handler fails or the event can't be recorded.
+pub(crate) fn handle_events(app: &mut App, source: &mut dyn EventSource) -> Result<()> {
+    let timeout = app
+        .replay
+        .as_ref()
+        .and_then(Replay::time_until_next)
+        .map_or(POLL_TIMEOUT, |time| time.min(POLL_TIMEOUT));
+
+    // Replayed events take precedence, but the user can still take over at any point
+    let event = match app.replay.as_mut().and_then(Replay::next_due) {
+        Some(event) => Some(event),
+        None => source.next_event(timeout)?,
+    };
+
+    if let Some(event) = event {
+        if let Some(recorder) = app.recorder.as_mut() {
+            if let Err(err) = recorder.record(&event) {
+                app.recorder = None;
+                return Err(err).recoverable();
             }
         }
+
+        handle_event(app, event)?;
     }
 
     // Update animation if in-game
@@ -38,12 +71,83 @@ pub(crate) fn handle_events(app: &mut App) -> Result<()> {
     Ok(())
 }
 
-/// Handles 'j' key press events for downward navigation.
+/// Dispatches a single input event to the appropriate handler functions.
+///
+/// Key presses are translated into actions through the active [`Keymap`](crate::keymap::Keymap)
+/// before being dispatched, to the braid panel instead while it's open. While an error popup or the
+/// help overlay is shown, any key press or click dismisses it instead of reaching the handlers.
 ///
-/// This function processes the 'j' key press which is used for moving down in menus and lists.
-/// The behavior varies depending on the current screen, handling menu navigation and viewport
-/// scrolling appropriately.
-pub(crate) fn handle_j_events(app: &mut App) -> Result<()> {
+/// # Errors
+///
+/// This function returns recoverable errors if a handler fails.
+pub(crate) fn handle_event(app: &mut App, event: Event) -> Result<()> {
+    match event {
+        Event::Key(_)
+        | Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(_),
+            ..
+        }) if !app.errors.is_empty() => {
+            let _ = app.errors.pop_front();
+        }
+        Event::Key(_)
+        | Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(_),
+            ..
+        }) if app.help => app.help = false,
+        Event::Key(key) => {
+            if let Some(action) = app.keymap.action(key.code) {
+                if app.braiding.is_some() {
+                    handle_braid_panel_action(app, action);
+                } else {
+                    handle_action(app, action).recoverable()?;
+                }
+            }
+        }
+        Event::Mouse(mouse) => handle_mouse_events(app, mouse).recoverable()?,
+        Event::Resize(width, height) => handle_resize_events(app, width, height),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Dispatches an action to the handler in charge of it.
+///
+/// # Errors
+///
+/// This function returns the errors of the handler the action is dispatched to.
+pub(crate) fn handle_action(app: &mut App, action: Action) -> Result<()> {
+    match action {
+        Action::Quit => app.exit = true,
+        Action::Help => app.help = true,
+        Action::Down => handle_down_action(app)?,
+        Action::Up => handle_up_action(app)?,
+        Action::Select => handle_select_action(app)?,
+        Action::Back => handle_back_action(app),
+        Action::PanLeft
+        | Action::PanDown
+        | Action::PanUp
+        | Action::PanRight
+        | Action::ZoomIn
+        | Action::ZoomOut
+        | Action::ToggleFollow => handle_camera_action(app, action),
+        Action::ToggleStats => handle_toggle_stats_action(app),
+        Action::CycleFloor => handle_cycle_floor_action(app),
+        Action::ExportCast => handle_export_cast_action(app),
+        Action::ExportImage => handle_export_image_action(app),
+        Action::CycleSolver => handle_cycle_solver_action(app),
+        Action::Braid => handle_braid_action(app),
+    }
+
+    Ok(())
+}
+
+/// Handles the down action for downward navigation.
+///
+/// This function processes the action used for moving down in menus and lists. The behavior
+/// varies depending on the current screen, handling menu navigation and viewport scrolling
+/// appropriately.
+pub(crate) fn handle_down_action(app: &mut App) -> Result<()> {
     match app.screen {
         Screen::MainMenu(MainMenuItem::StartGame) => {
             app.screen = Screen::MainMenu(MainMenuItem::Options);
@@ -54,7 +158,7 @@ pub(crate) fn handle_j_events(app: &mut App) -> Result<()> {
         Screen::OptionsMenu(OptionsMenuItem::Map) => {
             app.screen = Screen::OptionsMenu(OptionsMenuItem::Back);
         }
-        Screen::MapMenu => {
+        Screen::MapMenu if app.viewport_height > 0 => {
             let viewport_map = app
                 .viewport_map
                 .clone()
@@ -99,12 +203,12 @@ pub(crate) fn handle_j_events(app: &mut App) -> Result<()> {
     Ok(())
 }
 
-/// Handles 'k' key press events for upward navigation.
+/// Handles the up action for upward navigation.
 ///
-/// This function processes the 'k' key press which is used for moving up in menus and lists.
-/// Like the 'j' handler, behavior varies by screen and includes proper viewport management for
-/// scrollable content.
-pub(crate) fn handle_k_events(app: &mut App) -> Result<()> {
+/// This function processes the action used for moving up in menus and lists. Like the down
+/// handler, behavior varies by screen and includes proper viewport management for scrollable
+/// content.
+pub(crate) fn handle_up_action(app: &mut App) -> Result<()> {
     match app.screen {
         Screen::MainMenu(MainMenuItem::Quit) => {
             app.screen = Screen::MainMenu(MainMenuItem::Options);
@@ -115,7 +219,7 @@ pub(crate) fn handle_k_events(app: &mut App) -> Result<()> {
         Screen::OptionsMenu(OptionsMenuItem::Back) => {
             app.screen = Screen::OptionsMenu(OptionsMenuItem::Map);
         }
-        Screen::MapMenu => {
+        Screen::MapMenu if app.viewport_height > 0 => {
             let viewport_map = app
                 .viewport_map
                 .clone()
@@ -159,12 +263,12 @@ pub(crate) fn handle_k_events(app: &mut App) -> Result<()> {
     Ok(())
 }
 
-/// Handles 'l' key press events for selection and forward navigation.
+/// Handles the select action for selection and forward navigation.
 ///
-/// This function processes the 'l' key press which is used for selecting menu items and moving
-/// forward in the application flow. It handles screen transitions, map loading, and selection
+/// This function processes the action used for selecting menu items and moving forward in the
+/// application flow. It handles screen transitions, map loading, and selection
 /// confirmation across different contexts.
-pub(crate) fn handle_l_events(app: &mut App) -> Result<()> {
+pub(crate) fn handle_select_action(app: &mut App) -> Result<()> {
     match app.screen {
         Screen::MainMenu(MainMenuItem::StartGame) => {
             app.screen = Screen::InGame;
@@ -181,9 +285,13 @@ pub(crate) fn handle_l_events(app: &mut App) -> Result<()> {
             let first = Map::default();
             app.maps.clear();
             app.maps.push(first.clone());
-            file_loader::fetch_files(&mut app.maps)?;
             app.viewport_map = Some(first);
             app.viewport_offset = 0;
+
+            // Files that couldn't be loaded are reported without leaving the map menu
+            for err in file_loader::fetch_files(&mut app.maps)? {
+                app.errors.push_back(err.to_string());
+            }
         }
         Screen::OptionsMenu(OptionsMenuItem::Back) => {
             app.screen = Screen::MainMenu(MainMenuItem::StartGame);
@@ -200,150 +308,811 @@ pub(crate) fn handle_l_events(app: &mut App) -> Result<()> {
     Ok(())
 }
 
-/// Handles 'h' key press events for backward navigation.
+/// Handles the back action for backward navigation.
 ///
-/// This function processes the 'h' key press which is used for moving back or returning to
-/// previous screens. It handles returning from the in-game screen to the main menu and from the
+/// This function processes the action used for moving back or returning to previous screens. It
+/// handles returning from the in-game screen and the options menu to the main menu, and from the
 /// map menu to the options menu.
-pub(crate) fn handle_h_events(app: &mut App) {
+pub(crate) fn handle_back_action(app: &mut App) {
     match app.screen {
         Screen::InGame => {
-            // Reset animation state and return to main menu
+            // Reset animation and camera state and return to main menu
             app.animation_manager.clear();
+            app.camera = Camera::new();
+            app.inspected_cell = None;
+            app.status = None;
+            app.screen = Screen::MainMenu(MainMenuItem::StartGame);
+        }
+        Screen::OptionsMenu(_) => {
             app.screen = Screen::MainMenu(MainMenuItem::StartGame);
         }
         Screen::MapMenu => {
             app.screen = Screen::OptionsMenu(OptionsMenuItem::Map);
         }
+        Screen::MainMenu(_) => {}
+    }
+}
+
+/// Handles camera actions for the in-game viewport.
+///
+/// This function processes the actions used to move around mazes bigger than the terminal: the
+/// pan actions move the camera, the zoom actions change the zoom level, and the follow action
+/// toggles auto-follow of the solver's head cell. Actions triggered outside of the in-game screen
+/// are ignored.
+pub(crate) fn handle_camera_action(app: &mut App, action: Action) {
+    if app.screen != Screen::InGame {
+        return;
+    }
+
+    match action {
+        Action::PanLeft => app.camera.pan(-1, 0),
+        Action::PanDown => app.camera.pan(0, 1),
+        Action::PanUp => app.camera.pan(0, -1),
+        Action::PanRight => app.camera.pan(1, 0),
+        Action::ZoomIn => app.camera.zoom = app.camera.zoom.zoom_in(),
+        Action::ZoomOut => app.camera.zoom = app.camera.zoom.zoom_out(),
+        Action::ToggleFollow => app.camera.follow = !app.camera.follow,
+        Action::Up
+        | Action::Down
+        | Action::Select
+        | Action::Back
+        | Action::Quit
+        | Action::ExportCast
+        | Action::ExportImage
+        | Action::CycleSolver
+        | Action::ToggleStats
+        | Action::CycleFloor
+        | Action::Braid
+        | Action::Help => {}
+    }
+}
+
+/// Handles the action showing the next floor of a multi-level maze on its own.
+///
+/// The floors are shown in turn from the ground floor up, every floor side by side after the top
+/// one. Actions triggered outside of the in-game screen are ignored.
+pub(crate) fn handle_cycle_floor_action(app: &mut App) {
+    if app.screen == Screen::InGame {
+        app.camera.cycle_floor(app.animation_manager.grid.floors());
+    }
+}
+
+/// Handles the action showing or hiding the statistics panel drawn over the in-game maze.
+///
+/// Actions triggered outside of the in-game screen are ignored.
+pub(crate) fn handle_toggle_stats_action(app: &mut App) {
+    if app.screen == Screen::InGame {
+        app.stats = !app.stats;
+    }
+}
+
+/// Handles the action switching the in-game solver to the next search algorithm.
+///
+/// This function selects the next solver and clears the recorded animation, so that the maze is
+/// solved again from the start with the new algorithm on the next redraw. Actions triggered outside
+/// of the in-game screen are ignored.
+pub(crate) fn handle_cycle_solver_action(app: &mut App) {
+    if app.screen != Screen::InGame {
+        return;
+    }
+
+    let solver = app.animation_manager.solver.next();
+    app.animation_manager.solver = solver;
+    app.animation_manager.clear();
+    app.status = Some(format!("solving with {}", solver.name()));
+}
+
+/// Handles the action opening the braid panel over the in-game maze.
+///
+/// The panel starts with the default settings of the `braid` subcommand, removing every dead end
+/// without adding loops. Actions triggered outside of the in-game screen are ignored.
+pub(crate) fn handle_braid_action(app: &mut App) {
+    if app.screen == Screen::InGame {
+        app.braiding = Some(BraidSettings::default());
+    }
+}
+
+/// Handles an action while the braid panel is open.
+///
+/// The up and down actions raise and lower the share of dead ends to remove by
+/// [`BRAID_PANEL_STEP`] percent, while zooming in and out adds and takes away a loop. Selecting
+/// braids the maze with the settings picked, while going back or braiding again closes the panel
+/// without changing the maze. Quitting and asking for help still work, and every other action is
+/// ignored.
+pub(crate) fn handle_braid_panel_action(app: &mut App, action: Action) {
+    let Some(settings) = app.braiding.as_mut() else {
+        return;
+    };
+
+    match action {
+        Action::Up => settings.dead_ends = (settings.dead_ends + BRAID_PANEL_STEP).min(100),
+        Action::Down => settings.dead_ends = settings.dead_ends.saturating_sub(BRAID_PANEL_STEP),
+        Action::ZoomIn => settings.loops = settings.loops.saturating_add(1),
+        Action::ZoomOut => settings.loops = settings.loops.saturating_sub(1),
+        Action::Select => {
+            let settings = *settings;
+            app.braiding = None;
+            braid_maze(app, settings);
+        }
+        Action::Back | Action::Braid => app.braiding = None,
+        Action::Quit => app.exit = true,
+        Action::Help => app.help = true,
+        _ => {}
+    }
+}
+
+/// Knocks walls out of the in-game maze with the given settings.
+///
+/// This function braids the current map in memory only, and clears the recorded animation so that
+/// the braided maze is solved again from the start on the next redraw. The walls are picked from
+/// the seed of the map, so that replays braid the same way. What braiding changed is reported in
+/// the tooltip, and a failed braiding is queued for the error popup.
+fn braid_maze(app: &mut App, settings: BraidSettings) {
+    let settings = BraidSettings {
+        seed: Some(app.map.metadata.seed.unwrap_or(0)),
+        ..settings
+    };
+    match braid::braid(&app.map, &settings) {
+        Ok(braided) => {
+            app.status = Some(braided.summary());
+            app.map = braided.map;
+            app.animation_manager.clear();
+        }
+        Err(err) => app.errors.push_back(format!("{err:#}")),
+    }
+}
+
+/// Handles the export action for writing the solver animation to an asciicast.
+///
+/// This function writes the animation of the current map, drawn at the current terminal size, to a
+/// file named after the map in the current working directory, and reports where it went in the
+/// tooltip. A failed export is queued for the error popup without leaving the game. Actions
+/// triggered outside of the in-game screen are ignored.
+pub(crate) fn handle_export_cast_action(app: &mut App) {
+    if app.screen != Screen::InGame {
+        return;
+    }
+
+    let path = export::default_path(&app.map, "cast");
+    match export::cast::write(&app.map, (app.area.width, app.area.height), &path) {
+        Ok(()) => app.status = Some(format!("exported the animation to {}", path.display())),
+        Err(err) => app.errors.push_back(format!("{err:#}")),
+    }
+}
+
+/// Handles the export action for writing images of the solved maze.
+///
+/// This function writes an SVG and a PNG image of the current map, with the path found by the
+/// solver drawn in, to files named after the map in the current working directory, and reports
+/// where they went in the tooltip. A failed export is queued for the error popup without leaving
+/// the game. Actions triggered outside of the in-game screen are ignored.
+pub(crate) fn handle_export_image_action(app: &mut App) {
+    if app.screen != Screen::InGame {
+        return;
+    }
+
+    let style = ImageStyle::default();
+    let paths = [
+        export::default_path(&app.map, "svg"),
+        export::default_path(&app.map, "png"),
+    ];
+    let written = paths
+        .iter()
+        .try_for_each(|path| export::write_image(&app.map, true, &style, path));
+
+    match (written, paths) {
+        (Ok(()), [svg, png]) => {
+            app.status = Some(format!(
+                "exported the maze to {} and {}",
+                svg.display(),
+                png.display()
+            ));
+        }
+        (Err(err), _) => app.errors.push_back(format!("{err:#}")),
+    }
+}
+
+/// Handles mouse events across menus, the map list and the in-game canvas.
+///
+/// This function maps the mouse position back to the widgets drawn in the last frame. Clicking a
+/// menu item selects it, scrolling the wheel over the map menu scrolls the map list, clicking a map
+/// moves the cursor to it (or selects it if the cursor was already there), and clicking a maze cell
+/// in-game shows its details in the tooltip.
+pub(crate) fn handle_mouse_events(app: &mut App, mouse: MouseEvent) -> Result<()> {
+    let position = Position::new(mouse.column, mouse.row);
+
+    match (mouse.kind, &app.screen) {
+        (MouseEventKind::Down(MouseButton::Left), Screen::MainMenu(_)) => {
+            let (_, items) = ui::menu_layout(app.area, &MenuType::MainMenu(3), &app.keymap);
+            let clicked = [
+                MainMenuItem::StartGame,
+                MainMenuItem::Options,
+                MainMenuItem::Quit,
+            ]
+            .into_iter()
+            .zip(items.iter())
+            .find_map(|(item, area)| area.contains(position).then_some(item));
+
+            if let Some(item) = clicked {
+                app.screen = Screen::MainMenu(item);
+                handle_select_action(app)?;
+            }
+        }
+        (MouseEventKind::Down(MouseButton::Left), Screen::OptionsMenu(_)) => {
+            let (_, items) = ui::menu_layout(app.area, &MenuType::OptionsMenu(2), &app.keymap);
+            let clicked = [OptionsMenuItem::Map, OptionsMenuItem::Back]
+                .into_iter()
+                .zip(items.iter())
+                .find_map(|(item, area)| area.contains(position).then_some(item));
+
+            if let Some(item) = clicked {
+                app.screen = Screen::OptionsMenu(item);
+                handle_select_action(app)?;
+            }
+        }
+        (MouseEventKind::Down(MouseButton::Left), Screen::MapMenu) => {
+            let list = Block::bordered().inner(ui::map_menu_area(app.area, &app.keymap));
+
+            if list.contains(position) {
+                let index = app.viewport_offset + usize::from(position.y - list.y);
+
+                if let Some(map) = app.maps.get(index) {
+                    if app.viewport_map.as_ref() == Some(map) {
+                        handle_select_action(app)?;
+                    } else {
+                        app.viewport_map = Some(map.clone());
+                    }
+                }
+            }
+        }
+        (MouseEventKind::ScrollDown, Screen::MapMenu) => scroll_map_list(app, 1),
+        (MouseEventKind::ScrollUp, Screen::MapMenu) => scroll_map_list(app, -1),
+        (MouseEventKind::Down(MouseButton::Left), Screen::InGame) => {
+            app.inspected_cell = ui::maze_cell_at(app, position);
+            app.status = None;
+        }
         _ => {}
     }
+
+    Ok(())
+}
+
+/// Scrolls the map list by the given amount of entries.
+///
+/// This function moves the viewport over the map list and, if the cursor-selected map falls out of
+/// view, moves the cursor to the closest visible map so that it stays on screen.
+pub(crate) fn scroll_map_list(app: &mut App, amount: isize) {
+    let last_offset = app.maps.len().saturating_sub(app.viewport_height);
+    app.viewport_offset = app
+        .viewport_offset
+        .saturating_add_signed(amount)
+        .min(last_offset);
+
+    let selected = app
+        .viewport_map
+        .as_ref()
+        .and_then(|viewport_map| app.maps.iter().position(|map| map == viewport_map));
+
+    if let Some(selected) = selected {
+        let first_visible = app.viewport_offset;
+        let last_visible = (app.viewport_offset + app.viewport_height).saturating_sub(1);
+        let clamped = selected.clamp(first_visible, last_visible.max(first_visible));
+
+        if clamped != selected {
+            app.viewport_map = app.maps.get(clamped).cloned();
+        }
+    }
+}
+
+/// Handles terminal resize events.
+///
+/// This function recomputes the height of the map list viewport for the new terminal size and
+/// clamps the scrolling offset accordingly, so that the cursor-selected map stays visible and the
+/// navigation handlers never work with a stale viewport.
+pub(crate) fn handle_resize_events(app: &mut App, width: u16, height: u16) {
+    app.viewport_height = ui::map_list_height(Rect::new(0, 0, width, height), &app.keymap);
+    app.clamp_viewport_offset();
 }
 
 #[cfg(test)]
 mod tests {
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
     use super::*;
+    use crate::{camera::ZoomLevel, grid::Grid, map::Metadata, pathfinding::Solver};
 
     fn create_test_app() -> App {
         App::new()
     }
 
     #[test]
-    fn test_handle_j_events_main_menu_start_game() {
+    fn test_handle_down_action_main_menu_start_game() {
         let mut app = create_test_app();
         app.screen = Screen::MainMenu(MainMenuItem::StartGame);
 
-        handle_j_events(&mut app).expect("j event handling should succeed in test");
+        handle_down_action(&mut app).expect("down action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::Options));
     }
 
     #[test]
-    fn test_handle_j_events_main_menu_options() {
+    fn test_handle_down_action_main_menu_options() {
         let mut app = create_test_app();
         app.screen = Screen::MainMenu(MainMenuItem::Options);
 
-        handle_j_events(&mut app).expect("j event handling should succeed in test");
+        handle_down_action(&mut app).expect("down action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::Quit));
     }
 
     #[test]
-    fn test_handle_j_events_options_menu_map() {
+    fn test_handle_down_action_options_menu_map() {
         let mut app = create_test_app();
         app.screen = Screen::OptionsMenu(OptionsMenuItem::Map);
 
-        handle_j_events(&mut app).expect("j event handling should succeed in test");
+        handle_down_action(&mut app).expect("down action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::OptionsMenu(OptionsMenuItem::Back));
     }
 
     #[test]
-    fn test_handle_k_events_main_menu_quit() {
+    fn test_handle_up_action_main_menu_quit() {
         let mut app = create_test_app();
         app.screen = Screen::MainMenu(MainMenuItem::Quit);
 
-        handle_k_events(&mut app).expect("k event handling should succeed in test");
+        handle_up_action(&mut app).expect("up action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::Options));
     }
 
     #[test]
-    fn test_handle_k_events_main_menu_options() {
+    fn test_handle_up_action_main_menu_options() {
         let mut app = create_test_app();
         app.screen = Screen::MainMenu(MainMenuItem::Options);
 
-        handle_k_events(&mut app).expect("k event handling should succeed in test");
+        handle_up_action(&mut app).expect("up action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::StartGame));
     }
 
     #[test]
-    fn test_handle_k_events_options_menu_back() {
+    fn test_handle_up_action_options_menu_back() {
         let mut app = create_test_app();
         app.screen = Screen::OptionsMenu(OptionsMenuItem::Back);
 
-        handle_k_events(&mut app).expect("k event handling should succeed in test");
+        handle_up_action(&mut app).expect("up action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::OptionsMenu(OptionsMenuItem::Map));
     }
 
     #[test]
-    fn test_handle_l_events_main_menu_start_game() {
+    fn test_handle_select_action_main_menu_start_game() {
         let mut app = create_test_app();
         app.screen = Screen::MainMenu(MainMenuItem::StartGame);
 
-        handle_l_events(&mut app).expect("l event handling should succeed in test");
+        handle_select_action(&mut app).expect("select action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::InGame);
     }
 
     #[test]
-    fn test_handle_l_events_main_menu_options() {
+    fn test_handle_select_action_main_menu_options() {
         let mut app = create_test_app();
         app.screen = Screen::MainMenu(MainMenuItem::Options);
 
-        handle_l_events(&mut app).expect("l event handling should succeed in test");
+        handle_select_action(&mut app).expect("select action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::OptionsMenu(OptionsMenuItem::Map));
     }
 
     #[test]
-    fn test_handle_l_events_main_menu_quit() {
+    fn test_handle_select_action_main_menu_quit() {
         let mut app = create_test_app();
         app.screen = Screen::MainMenu(MainMenuItem::Quit);
 
-        handle_l_events(&mut app).expect("l event handling should succeed in test");
+        handle_select_action(&mut app).expect("select action handling should succeed in test");
 
         assert!(app.exit);
     }
 
     #[test]
-    fn test_handle_l_events_options_menu_back() {
+    fn test_handle_select_action_options_menu_back() {
         let mut app = create_test_app();
         app.screen = Screen::OptionsMenu(OptionsMenuItem::Back);
 
-        handle_l_events(&mut app).expect("l event handling should succeed in test");
+        handle_select_action(&mut app).expect("select action handling should succeed in test");
 
         assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::StartGame));
     }
 
     #[test]
-    fn test_handle_h_events_in_game() {
+    fn test_handle_back_action_in_game() {
         let mut app = create_test_app();
         app.screen = Screen::InGame;
 
-        handle_h_events(&mut app);
+        handle_back_action(&mut app);
 
         assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::StartGame));
     }
 
     #[test]
-    fn test_handle_h_events_map_menu() {
+    fn test_handle_back_action_map_menu() {
+        let mut app = create_test_app();
+        app.screen = Screen::MapMenu;
+
+        handle_back_action(&mut app);
+
+        assert_eq!(app.screen, Screen::OptionsMenu(OptionsMenuItem::Map));
+    }
+
+    #[test]
+    fn test_handle_back_action_options_menu() {
+        let mut app = create_test_app();
+        app.screen = Screen::OptionsMenu(OptionsMenuItem::Back);
+
+        handle_back_action(&mut app);
+
+        assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::StartGame));
+    }
+
+    #[test]
+    fn test_handle_action_through_default_keymap() {
+        let mut app = create_test_app();
+
+        for key in [KeyCode::Down, KeyCode::Char('s'), KeyCode::Enter] {
+            let action = app.keymap.action(key).expect("the key should be bound");
+            handle_action(&mut app, action).expect("action handling should succeed in test");
+        }
+
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn test_handle_action_help() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+
+        handle_action(&mut app, Action::Help).expect("action handling should succeed in test");
+
+        assert!(app.help);
+        assert_eq!(app.screen, Screen::InGame);
+    }
+
+    #[test]
+    fn test_handle_event_dismisses_overlays_first() {
+        let mut app = create_test_app();
+        app.help = true;
+        app.errors.push_back("failed to read map".to_owned());
+        let quit = Event::Key(KeyCode::Char('q').into());
+
+        handle_event(&mut app, quit.clone()).expect("event handling should succeed in test");
+        assert!(app.errors.is_empty());
+        assert!(app.help);
+
+        handle_event(&mut app, quit.clone()).expect("event handling should succeed in test");
+        assert!(!app.help);
+        assert!(!app.exit);
+
+        handle_event(&mut app, quit).expect("event handling should succeed in test");
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn test_handle_action_quit() {
+        let mut app = create_test_app();
+
+        handle_action(&mut app, Action::Quit).expect("action handling should succeed in test");
+
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn test_handle_camera_action_in_game() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+
+        handle_camera_action(&mut app, Action::PanRight);
+        handle_camera_action(&mut app, Action::PanDown);
+        handle_camera_action(&mut app, Action::ZoomOut);
+
+        assert_eq!((app.camera.column, app.camera.row), (1, 1));
+        assert_eq!(app.camera.zoom, ZoomLevel::Medium);
+        assert!(!app.camera.follow);
+
+        handle_camera_action(&mut app, Action::ToggleFollow);
+        assert!(app.camera.follow);
+    }
+
+    #[test]
+    fn test_handle_cycle_solver_action() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+        app.animation_manager
+            .record(Grid::of(&app.map).expect("the default map forms a grid"))
+            .expect("the default map has an entry point");
+
+        handle_cycle_solver_action(&mut app);
+
+        assert_eq!(app.animation_manager.solver, Solver::Dijkstra);
+        assert!(app.animation_manager.steps.is_empty());
+        assert_eq!(app.status.as_deref(), Some("solving with Dijkstra"));
+    }
+
+    #[test]
+    fn test_handle_cycle_floor_action() {
+        let mut app = create_test_app();
+        app.screen = Screen::MapMenu;
+
+        handle_cycle_floor_action(&mut app);
+        assert_eq!(app.camera, Camera::new());
+
+        // The default map has a single floor, after which every floor is shown
+        app.screen = Screen::InGame;
+        handle_cycle_floor_action(&mut app);
+        assert_eq!(app.camera.floor, None);
+        assert!(!app.camera.follow);
+    }
+
+    #[test]
+    fn test_handle_toggle_stats_action() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+
+        handle_toggle_stats_action(&mut app);
+        assert!(!app.stats);
+        handle_toggle_stats_action(&mut app);
+        assert!(app.stats);
+
+        app.screen = Screen::MapMenu;
+        handle_toggle_stats_action(&mut app);
+        assert!(app.stats);
+    }
+
+    #[test]
+    fn test_handle_braid_action() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+        app.map = Map {
+            key: "comb".to_owned(),
+            data: ["2222222", "2133334", "2323232", "2323232", "2222222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        };
+        app.animation_manager
+            .record(Grid::of(&app.map).expect("the comb forms a grid"))
+            .expect("the comb has an entry point");
+
+        let map = app.map.clone();
+
+        // Going back closes the panel without braiding
+        handle_braid_action(&mut app);
+        assert_eq!(app.braiding, Some(BraidSettings::default()));
+        handle_braid_panel_action(&mut app, Action::Back);
+        assert_eq!((app.braiding, &app.map), (None, &map));
+
+        // Key presses reach the panel rather than the camera while it's open
+        handle_braid_action(&mut app);
+        for _ in 0..6 {
+            handle_event(
+                &mut app,
+                Event::Key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)),
+            )
+            .expect("handling the key press should succeed");
+        }
+        handle_braid_panel_action(&mut app, Action::Up);
+        handle_braid_panel_action(&mut app, Action::ZoomIn);
+        handle_braid_panel_action(&mut app, Action::ZoomOut);
+        assert_eq!(
+            app.braiding,
+            Some(BraidSettings {
+                dead_ends: 50,
+                ..BraidSettings::default()
+            })
+        );
+
+        handle_braid_panel_action(&mut app, Action::Select);
+        assert_eq!(app.braiding, None);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("removed 2 of 3 dead ends and added 0 loops")
+        );
+        assert!(app.animation_manager.steps.is_empty());
+        assert!(file_loader::parse_file_contents(&app.map.to_labmap()));
+
+        app.screen = Screen::MapMenu;
+        handle_braid_action(&mut app);
+        assert_eq!(app.braiding, None);
+    }
+
+    #[test]
+    fn test_handle_braid_panel_limits() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+        handle_braid_action(&mut app);
+
+        for action in [Action::Up, Action::ZoomOut, Action::PanLeft, Action::Help] {
+            handle_braid_panel_action(&mut app, action);
+        }
+        assert_eq!(app.braiding, Some(BraidSettings::default()));
+        assert_eq!(app.camera, Camera::new());
+        assert!(app.help);
+
+        handle_braid_panel_action(&mut app, Action::ZoomIn);
+        handle_braid_panel_action(&mut app, Action::Braid);
+        assert_eq!(app.braiding, None);
+    }
+
+    #[test]
+    fn test_handle_camera_action_outside_game() {
+        let mut app = create_test_app();
+        app.screen = Screen::MainMenu(MainMenuItem::StartGame);
+
+        handle_camera_action(&mut app, Action::PanRight);
+
+        assert_eq!(app.camera, Camera::new());
+    }
+
+    #[test]
+    fn test_handle_back_action_in_game_resets_camera() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+        app.camera.pan(4, 4);
+
+        handle_back_action(&mut app);
+
+        assert_eq!(app.camera, Camera::new());
+    }
+
+    #[test]
+    fn test_handle_resize_events_clamps_offset() {
         let mut app = create_test_app();
         app.screen = Screen::MapMenu;
+        app.maps = (0..20)
+            .map(|idx| Map {
+                key: idx.to_string(),
+                data: Vec::new(),
+                metadata: Metadata::default(),
+            })
+            .collect();
+        app.viewport_map = app.maps.get(19).cloned();
+        app.viewport_offset = 17;
+        app.viewport_height = 3;
+
+        handle_resize_events(&mut app, 200, 60);
+
+        assert_eq!(app.viewport_height, 10);
+        assert_eq!(app.viewport_offset, 10);
+    }
+
+    #[test]
+    fn test_handle_down_action_map_menu_empty_viewport() {
+        let mut app = create_test_app();
+        app.screen = Screen::MapMenu;
+        app.maps = vec![Map::default(), Map::default()];
+        app.viewport_map = app.maps.first().cloned();
+        app.viewport_height = 0;
+
+        handle_down_action(&mut app)
+            .expect("down action handling should succeed on empty viewports");
+        handle_up_action(&mut app).expect("up action handling should succeed on empty viewports");
+
+        assert_eq!(app.viewport_offset, 0);
+    }
+
+    fn create_mouse_event(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    fn create_test_maps(count: usize) -> Vec<Map> {
+        (0..count)
+            .map(|idx| Map {
+                key: idx.to_string(),
+                data: Vec::new(),
+                metadata: Metadata::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_handle_mouse_events_main_menu_click() {
+        let mut app = create_test_app();
+        app.area = Rect::new(0, 0, 80, 24);
+        let (_, items) = ui::menu_layout(app.area, &MenuType::MainMenu(3), &app.keymap);
+        let options = items.get(1).expect("the main menu should have three items");
+        let click = create_mouse_event(
+            MouseEventKind::Down(MouseButton::Left),
+            options.x + options.width / 2,
+            options.y,
+        );
 
-        handle_h_events(&mut app);
+        handle_mouse_events(&mut app, click).expect("mouse event handling should succeed in test");
 
         assert_eq!(app.screen, Screen::OptionsMenu(OptionsMenuItem::Map));
     }
+
+    #[test]
+    fn test_handle_mouse_events_click_outside_menu() {
+        let mut app = create_test_app();
+        app.area = Rect::new(0, 0, 80, 24);
+        let click = create_mouse_event(MouseEventKind::Down(MouseButton::Left), 0, 0);
+
+        handle_mouse_events(&mut app, click).expect("mouse event handling should succeed in test");
+
+        assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::StartGame));
+    }
+
+    #[test]
+    fn test_handle_mouse_events_map_menu_scroll() {
+        let mut app = create_test_app();
+        app.screen = Screen::MapMenu;
+        app.maps = create_test_maps(10);
+        app.viewport_map = app.maps.first().cloned();
+        app.viewport_height = 4;
+
+        let scroll_down = create_mouse_event(MouseEventKind::ScrollDown, 0, 0);
+        for _ in 0..10 {
+            handle_mouse_events(&mut app, scroll_down)
+                .expect("mouse event handling should succeed in test");
+        }
+
+        assert_eq!(app.viewport_offset, 6);
+        assert_eq!(app.viewport_map, app.maps.get(6).cloned());
+
+        let scroll_up = create_mouse_event(MouseEventKind::ScrollUp, 0, 0);
+        handle_mouse_events(&mut app, scroll_up)
+            .expect("mouse event handling should succeed in test");
+
+        assert_eq!(app.viewport_offset, 5);
+        assert_eq!(app.viewport_map, app.maps.get(6).cloned());
+    }
+
+    #[test]
+    fn test_handle_mouse_events_map_menu_click() {
+        let mut app = create_test_app();
+        app.screen = Screen::MapMenu;
+        app.area = Rect::new(0, 0, 80, 24);
+        app.maps = create_test_maps(5);
+        app.viewport_map = app.maps.first().cloned();
+        app.viewport_height = ui::map_list_height(app.area, &app.keymap);
+        let list = Block::bordered().inner(ui::map_menu_area(app.area, &app.keymap));
+        let click = create_mouse_event(MouseEventKind::Down(MouseButton::Left), list.x, list.y + 1);
+
+        handle_mouse_events(&mut app, click).expect("mouse event handling should succeed in test");
+
+        assert_eq!(app.screen, Screen::MapMenu);
+        assert_eq!(app.viewport_map, app.maps.get(1).cloned());
+
+        handle_mouse_events(&mut app, click).expect("mouse event handling should succeed in test");
+
+        assert_eq!(app.screen, Screen::MapMenu);
+        assert_eq!(Some(app.map), app.maps.get(1).cloned());
+    }
+
+    #[test]
+    fn test_handle_mouse_events_in_game_inspects_cell() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+        app.area = Rect::new(0, 0, 80, 24);
+        let click = create_mouse_event(MouseEventKind::Down(MouseButton::Left), 40, 10);
+
+        handle_mouse_events(&mut app, click).expect("mouse event handling should succeed in test");
+
+        assert!(app.inspected_cell.is_some());
+        assert_eq!(
+            app.inspected_cell,
+            ui::maze_cell_at(&app, Position::new(40, 10))
+        );
+
+        let outside = create_mouse_event(MouseEventKind::Down(MouseButton::Left), 0, 0);
+        handle_mouse_events(&mut app, outside)
+            .expect("mouse event handling should succeed in test");
+
+        assert!(app.inspected_cell.is_none());
+    }
 }
@@ -0,0 +1,286 @@
+//! Exporting of mazes and their solutions to other formats.
+//!
+//! This module contains the writers turning a labyrinth map, and the way the solver explores it,
+//! into files that can be used outside of the terminal, along with the helpers they share.
+
+pub(crate) mod cast;
+pub(crate) mod png;
+pub(crate) mod svg;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{bail, ensure, eyre, Result, WrapErr as _};
+
+use crate::{
+    grid::{Cell, Grid},
+    map::Map,
+    oneway,
+    pathfinding::AnimationManager,
+    portal, puzzle, stairs, weave,
+};
+
+/// Appearance of an exported maze image.
+///
+/// Colours are given as `[red, green, blue]` triples, and every maze cell is drawn as a square of
+/// `cell_size` pixels, or user units in SVG images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageStyle {
+    /// Side length of a maze cell.
+    pub cell_size: u32,
+    /// Colour of open paths and of the area around the maze.
+    pub background: [u8; 3],
+    /// Colour of walls.
+    pub wall: [u8; 3],
+    /// Colour of the solution path.
+    pub path: [u8; 3],
+    /// Colour of the entry point.
+    pub entry: [u8; 3],
+    /// Colour of exits.
+    pub exit: [u8; 3],
+}
+
+impl Default for ImageStyle {
+    fn default() -> Self {
+        Self {
+            cell_size: 16,
+            background: [0xff, 0xff, 0xff],
+            wall: [0x00, 0x00, 0x00],
+            path: [0xd0, 0x30, 0x30],
+            entry: [0x30, 0xa0, 0x30],
+            exit: [0x30, 0x60, 0xd0],
+        }
+    }
+}
+
+impl ImageStyle {
+    /// Names of the parts of the image whose colour can be set with [`ImageStyle::set_color`].
+    pub(crate) const PARTS: [&'static str; 5] = ["background", "wall", "path", "entry", "exit"];
+
+    /// Sets the colour of the named part of the image from a `#RRGGBB` string.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the part is unknown or the colour isn't valid.
+    pub(crate) fn set_color(&mut self, part: &str, color: &str) -> Result<()> {
+        let color = parse_color(color)?;
+
+        match part {
+            "background" => self.background = color,
+            "wall" => self.wall = color,
+            "path" => self.path = color,
+            "entry" => self.entry = color,
+            "exit" => self.exit = color,
+            _ => bail!(
+                "unknown image part {part}, expected one of {}",
+                Self::PARTS.join(", ")
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the colour a maze cell is filled with, if it isn't left as background.
+    pub(crate) const fn cell_color(&self, cell: Cell) -> Option<[u8; 3]> {
+        match cell {
+            Cell::Entry => Some(self.entry),
+            Cell::Wall => Some(self.wall),
+            Cell::Exit => Some(self.exit),
+            Cell::Key(_) => Some(puzzle::KEY_COLOUR),
+            Cell::Door(_) => Some(puzzle::DOOR_COLOUR),
+            Cell::Portal(_) => Some(portal::PORTAL_COLOUR),
+            Cell::Arrow(_) => Some(oneway::ARROW_COLOUR),
+            Cell::Stairs(_) => Some(stairs::STAIRS_COLOUR),
+            Cell::Crossing => Some(weave::CROSSING_COLOUR),
+            Cell::Terrain(terrain) => Some(terrain.colour),
+            Cell::Open => None,
+        }
+    }
+}
+
+/// Parses a colour of the form `#RRGGBB`.
+///
+/// # Errors
+///
+/// This function returns an error if the colour isn't made of a '#' followed by six hexadecimal
+/// digits.
+pub(crate) fn parse_color(color: &str) -> Result<[u8; 3]> {
+    let digits = color
+        .strip_prefix('#')
+        .filter(|digits| digits.len() == 6 && digits.is_ascii())
+        .ok_or_else(|| eyre!("colours must be of the form #RRGGBB, found {color}"))?;
+
+    let mut rgb = [0; 3];
+    for (channel, index) in rgb.iter_mut().zip((0..6).step_by(2)) {
+        *channel = digits
+            .get(index..index + 2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| eyre!("colours must be of the form #RRGGBB, found {color}"))?;
+    }
+
+    Ok(rgb)
+}
+
+/// Formats a colour as a `#rrggbb` string.
+pub(crate) fn hex_color([red, green, blue]: [u8; 3]) -> String {
+    format!("#{red:02x}{green:02x}{blue:02x}")
+}
+
+/// Builds the grid of a maze to draw.
+///
+/// # Errors
+///
+/// This function returns an error if the maze has no rows or if they don't form a grid.
+pub(crate) fn maze_grid(map: &Map) -> Result<Grid> {
+    let grid = Grid::of(map)?;
+    ensure!(grid.height() > 0, "failed to retrieve maze in selected map");
+
+    Ok(grid)
+}
+
+/// Runs the pathfinding algorithm on a maze and returns the path it found to an exit.
+///
+/// # Errors
+///
+/// This function returns an error if the maze has no entry point, or if no exit can be reached.
+pub(crate) fn solution(map: &Map) -> Result<Vec<(usize, usize)>> {
+    let grid = Grid::of(map)?;
+    ensure!(!grid.entries().is_empty(), "{} has no entry point", map.key);
+
+    let mut manager = AnimationManager::new();
+    manager.record(grid)?;
+
+    manager
+        .found
+        .ok_or_else(|| eyre!("the solver found no exit in {}", map.key))
+}
+
+/// Writes an image of a maze, optionally with its solution drawn in, to the given path.
+///
+/// The format of the image is picked from the extension of the path, either `.svg` or `.png`.
+///
+/// # Errors
+///
+/// This function returns an error if the extension isn't supported, if the solution can't be
+/// found, if the image is too large or if the file can't be written.
+pub(crate) fn write_image(
+    map: &Map,
+    solution: bool,
+    style: &ImageStyle,
+    path: &Path,
+) -> Result<()> {
+    ensure!(style.cell_size > 0, "the cell size must be positive");
+
+    let solution = if solution {
+        Some(self::solution(map)?)
+    } else {
+        None
+    };
+    let solution = solution.as_deref();
+
+    let contents = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => svg::render(map, solution, style)?.into_bytes(),
+        Some("png") => png::render(map, solution, style)?,
+        _ => bail!(
+            "unsupported image format for {}, expected a .svg or .png file",
+            path.display()
+        ),
+    };
+
+    fs::write(path, contents).wrap_err_with(|| format!("failed to write {}", path.display()))
+}
+
+/// Formats a string as a JSON string literal, including the surrounding quotes.
+///
+/// Quotes, backslashes and control characters are escaped, while every other character is written
+/// as is, since JSON documents are UTF-8 encoded.
+pub(crate) fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            control if control.is_control() && u32::from(control) < 0x20 => {
+                escaped.extend(format!("\\u{:04x}", u32::from(control)).chars());
+            }
+            other => escaped.push(other),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+/// Returns the path of a file named after the given map, with the given extension, in the current
+/// working directory.
+pub(crate) fn default_path(map: &Map, extension: &str) -> PathBuf {
+    Path::new(&map.key).with_extension(extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string_escapes_special_characters() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(
+            json_string("\u{1b}[0m \"quoted\" \\ \n"),
+            "\"\\u001b[0m \\\"quoted\\\" \\\\ \\n\""
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(
+            parse_color("#ff8000").expect("the colour should parse"),
+            [0xff, 0x80, 0x00]
+        );
+        assert_eq!(hex_color([0xff, 0x80, 0x00]), "#ff8000");
+        assert!(parse_color("ff8000").is_err());
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("#gg8000").is_err());
+    }
+
+    #[test]
+    fn test_set_color() {
+        let mut style = ImageStyle::default();
+
+        style
+            .set_color("wall", "#123456")
+            .expect("the wall colour should be set");
+
+        assert_eq!(style.wall, [0x12, 0x34, 0x56]);
+        assert!(style.set_color("floor", "#123456").is_err());
+    }
+
+    #[test]
+    fn test_solution_of_default_map() {
+        let map = Map::default();
+
+        let grid = Grid::of(&map).expect("the default map should form a grid");
+        let solution = solution(&map).expect("the default map should be solvable");
+
+        assert_eq!(
+            solution.first().copied(),
+            grid.entries().first().map(|&entry| grid.position(entry))
+        );
+        assert!(solution
+            .last()
+            .is_some_and(|&position| grid.at(position) == Some(Cell::Exit)));
+    }
+
+    #[test]
+    fn test_default_path_uses_map_key() {
+        let map = Map::default();
+
+        assert_eq!(default_path(&map, "cast"), PathBuf::from("Default.cast"));
+    }
+}
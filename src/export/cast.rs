@@ -0,0 +1,289 @@
+//! Asciicast recordings of the solver animation.
+//!
+//! This module renders every frame of the solver animation through the in-game screen into an
+//! off-screen terminal, and writes the frames as an [asciicast v2] file that can be played back
+//! with standard tools such as `asciinema play`.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::{fmt::Write as _, fs, path::Path, rc::Rc, time::Duration};
+
+use color_eyre::eyre::{ensure, Result, WrapErr as _};
+use ratatui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier},
+    Terminal,
+};
+
+use super::json_string;
+use crate::{
+    map::Map,
+    pathfinding::{AnimationManager, ANIMATION_FRAME_DELAY_MS},
+    time::{Clock, ManualClock},
+    types::Screen,
+    ui, App,
+};
+
+/// Size of the recording, as a `(width, height)` pair of terminal cells, if none is given.
+pub(crate) const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// Time the last frame of the recording is held for before the recording ends.
+pub(crate) const FINAL_FRAME_HOLD: Duration = Duration::from_secs(1);
+
+/// Renders the solver animation for a map as the contents of an asciicast v2 file.
+///
+/// The recording starts with the maze before the solver moves, followed by one frame per animation
+/// step, spaced by the same delay the animation uses on screen. Frames after the first only contain
+/// the cells that changed since the previous one.
+///
+/// # Errors
+///
+/// This function returns an error if the given size is too small to draw the maze, or if the maze
+/// can't be drawn.
+pub(crate) fn render(map: &Map, (width, height): (u16, u16)) -> Result<String> {
+    let clock = Rc::new(ManualClock::new());
+    let animation_clock: Rc<dyn Clock> = Rc::<ManualClock>::clone(&clock);
+
+    let mut app = App::new();
+    app.map = map.clone();
+    app.screen = Screen::InGame;
+    app.animation_manager = AnimationManager::with_clock(animation_clock);
+
+    let (required_width, required_height) = ui::required_size(&app);
+    ensure!(
+        width >= required_width && height >= required_height,
+        "a {width}x{height} recording is too small to draw {}, \
+         need {required_width}x{required_height}",
+        map.key
+    );
+
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut previous = Buffer::empty(Rect::new(0, 0, width, height));
+
+    let mut cast = String::new();
+    writeln!(
+        cast,
+        "{{\"version\": 2, \"width\": {width}, \"height\": {height}, \"title\": {}}}",
+        json_string(&format!("labyrintuine: {}", map.key))
+    )?;
+
+    let mut frame = 0;
+    let mut time = Duration::ZERO;
+    loop {
+        let _ =
+            terminal.try_draw(|frame| ui::draw(&mut app, frame).map_err(std::io::Error::other))?;
+        let buffer = terminal.backend().buffer().clone();
+
+        let mut output = String::new();
+        if frame == 0 {
+            // Hide the cursor and start from a blank screen
+            output.push_str("\u{1b}[?25l\u{1b}[2J");
+        }
+        output.push_str(&ansi_diff(&previous, &buffer)?);
+        writeln!(cast, "{}", event_line(time, &output))?;
+
+        previous = buffer;
+        if frame == app.animation_manager.steps.len() {
+            break;
+        }
+
+        frame += 1;
+        time += Duration::from_millis(ANIMATION_FRAME_DELAY_MS);
+        clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+        app.animation_manager.update();
+    }
+
+    // Hold the last frame and give the cursor back
+    writeln!(
+        cast,
+        "{}",
+        event_line(time + FINAL_FRAME_HOLD, "\u{1b}[?25h")
+    )?;
+
+    Ok(cast)
+}
+
+/// Renders the solver animation for a map and writes it as an asciicast v2 file.
+///
+/// # Errors
+///
+/// This function returns an error if the animation can't be rendered or the file can't be written.
+pub(crate) fn write(map: &Map, size: (u16, u16), path: &Path) -> Result<()> {
+    let cast = render(map, size)?;
+
+    fs::write(path, cast).wrap_err_with(|| format!("failed to write {}", path.display()))
+}
+
+/// Formats an output event of an asciicast file.
+fn event_line(time: Duration, output: &str) -> String {
+    format!(
+        "[{}.{:03}, \"o\", {}]",
+        time.as_secs(),
+        time.subsec_millis(),
+        json_string(output)
+    )
+}
+
+/// Returns the escape sequences drawing the cells that changed between two buffers.
+///
+/// # Errors
+///
+/// This function returns an error if the output can't be formatted.
+pub(crate) fn ansi_diff(previous: &Buffer, next: &Buffer) -> Result<String> {
+    let mut output = String::new();
+    let mut cursor = None;
+    let mut style = None;
+
+    for (column, row, cell) in previous.diff(next) {
+        if cursor != Some((column, row)) {
+            write!(output, "\u{1b}[{};{}H", row + 1, column + 1)?;
+        }
+
+        let cell_style = (cell.fg, cell.bg, cell.modifier);
+        if style != Some(cell_style) {
+            output.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+            style = Some(cell_style);
+        }
+
+        output.push_str(cell.symbol());
+        cursor = Some((column + 1, row));
+    }
+
+    if style.is_some() {
+        output.push_str("\u{1b}[0m");
+    }
+
+    Ok(output)
+}
+
+/// Returns the select graphic rendition sequence for a cell style.
+pub(crate) fn sgr(foreground: Color, background: Color, modifier: Modifier) -> String {
+    /// SGR parameters of each text modifier.
+    const MODIFIERS: [(Modifier, u8); 9] = [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::SLOW_BLINK, 5),
+        (Modifier::RAPID_BLINK, 6),
+        (Modifier::REVERSED, 7),
+        (Modifier::HIDDEN, 8),
+        (Modifier::CROSSED_OUT, 9),
+    ];
+
+    let mut parameters = vec![
+        "0".to_owned(),
+        color_parameters(foreground, false),
+        color_parameters(background, true),
+    ];
+    parameters.extend(
+        MODIFIERS
+            .iter()
+            .filter(|&&(flag, _)| modifier.contains(flag))
+            .map(|(_, parameter)| parameter.to_string()),
+    );
+
+    format!("\u{1b}[{}m", parameters.join(";"))
+}
+
+/// Returns the SGR parameters selecting a colour as the foreground or background colour.
+fn color_parameters(color: Color, background: bool) -> String {
+    let offset = if background { 10 } else { 0 };
+    let base = match color {
+        Color::Reset => 39,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Rgb(red, green, blue) => return format!("{};2;{red};{green};{blue}", 38 + offset),
+        Color::Indexed(index) => return format!("{};5;{index}", 38 + offset),
+    };
+
+    (base + offset).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_default_map() {
+        let map = Map::default();
+
+        let cast = render(&map, DEFAULT_SIZE).expect("the default map should render");
+        let lines = cast.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines.first().copied(),
+            Some(
+                "{\"version\": 2, \"width\": 80, \"height\": 24, \"title\": \"labyrintuine: \
+                 Default\"}"
+            )
+        );
+        assert!(
+            lines
+                .get(1)
+                .is_some_and(|line| line.starts_with("[0.000, \"o\", \"\\u001b[?25l")),
+            "the first frame should start at the beginning of the recording"
+        );
+        assert!(
+            lines
+                .get(2)
+                .is_some_and(|line| line.starts_with("[0.200, \"o\", ")),
+            "frames should be spaced by the animation delay"
+        );
+        assert!(
+            lines.len() > 3,
+            "there should be one frame per animation step"
+        );
+    }
+
+    #[test]
+    fn test_render_too_small() {
+        let map = Map::default();
+
+        assert!(render(&map, (10, 3)).is_err());
+    }
+
+    #[test]
+    fn test_ansi_diff_only_changed_cells() {
+        let area = Rect::new(0, 0, 4, 2);
+        let previous = Buffer::empty(area);
+        let mut next = Buffer::empty(area);
+        next.set_string(1, 1, "ab", ratatui::style::Style::default().fg(Color::Red));
+
+        let output = ansi_diff(&previous, &next).expect("the diff should be formatted");
+
+        assert_eq!(output, "\u{1b}[2;2H\u{1b}[0;31;49mab\u{1b}[0m");
+        assert_eq!(
+            ansi_diff(&next, &next).expect("the diff should be formatted"),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_sgr_modifiers_and_colors() {
+        assert_eq!(
+            sgr(
+                Color::White,
+                Color::Rgb(1, 2, 3),
+                Modifier::BOLD | Modifier::REVERSED
+            ),
+            "\u{1b}[0;97;48;2;1;2;3;1;7m"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! PNG images of mazes.
+//!
+//! This module rasterises a maze, and the solution path if any, into an RGB pixel buffer and
+//! encodes it as a PNG file. The image data is stored without compression, which keeps the encoder
+//! small at the cost of larger files, since maze images are made of few flat colours that any image
+//! tool can recompress.
+
+use color_eyre::eyre::{ensure, eyre, Result};
+
+use super::{maze_grid, ImageStyle};
+use crate::map::Map;
+
+/// Bytes every PNG file starts with.
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Largest amount of pixel data an image may hold, in bytes.
+const MAX_IMAGE_BYTES: usize = 1 << 28;
+
+/// Largest amount of data a stored deflate block may hold, in bytes.
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+/// RGB pixel buffer a maze is drawn onto.
+struct Raster {
+    /// Width of the image, in pixels.
+    width: usize,
+    /// Height of the image, in pixels.
+    height: usize,
+    /// Colour of every pixel, row by row, as consecutive red, green and blue bytes.
+    pixels: Vec<u8>,
+}
+
+impl Raster {
+    /// Creates a raster of the given size filled with a single colour.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the image would be too large.
+    fn new(width: usize, height: usize, color: [u8; 3]) -> Result<Self> {
+        let bytes = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .filter(|&bytes| bytes <= MAX_IMAGE_BYTES)
+            .ok_or_else(|| eyre!("a {width}x{height} image is too large to export"))?;
+        ensure!(bytes > 0, "an image must have at least one pixel");
+
+        Ok(Self {
+            width,
+            height,
+            pixels: color.repeat(width * height),
+        })
+    }
+
+    /// Fills a rectangle with a colour, clipping it to the bounds of the raster.
+    fn fill(&mut self, (x, y): (usize, usize), (width, height): (usize, usize), color: [u8; 3]) {
+        let right = x.saturating_add(width).min(self.width);
+        let bottom = y.saturating_add(height).min(self.height);
+        if x >= right {
+            return;
+        }
+
+        for row in y..bottom {
+            let start = (row * self.width + x) * 3;
+            let end = (row * self.width + right) * 3;
+
+            if let Some(pixels) = self.pixels.get_mut(start..end) {
+                for pixel in pixels.chunks_exact_mut(3) {
+                    pixel.copy_from_slice(&color);
+                }
+            }
+        }
+    }
+
+    /// Returns the image data as PNG scanlines, each preceded by its filter type.
+    fn scanlines(&self) -> Vec<u8> {
+        let mut scanlines = Vec::with_capacity(self.pixels.len() + self.height);
+
+        for row in self.pixels.chunks_exact(self.width * 3) {
+            // No filtering
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+
+        scanlines
+    }
+}
+
+/// Renders a maze, and optionally the path through it, as the contents of a PNG file.
+///
+/// # Errors
+///
+/// This function returns an error if the maze has no rows or the image would be too large.
+pub(crate) fn render(
+    map: &Map,
+    solution: Option<&[(usize, usize)]>,
+    style: &ImageStyle,
+) -> Result<Vec<u8>> {
+    let grid = maze_grid(map)?;
+    let topology = grid.topology();
+    let (columns, rows) = (grid.width(), grid.height());
+    let cell = usize::try_from(style.cell_size)?;
+    let too_large = || eyre!("{} is too large to export at this cell size", map.key);
+    // Shifted rows stick out of the maze by half a cell on the right
+    let overhang = (0..rows).map(|row| topology.shift(row)).max().unwrap_or(0);
+    // Left edge of the cells of a row, in pixels
+    let left_edge =
+        |(column, row): (usize, usize)| column * cell + usize::from(topology.shift(row)) * cell / 2;
+
+    let mut raster = Raster::new(
+        columns
+            .checked_mul(cell)
+            .and_then(|width| width.checked_add(usize::from(overhang) * cell / 2))
+            .ok_or_else(too_large)?,
+        rows.checked_mul(cell).ok_or_else(too_large)?,
+        style.background,
+    )?;
+
+    for (position, maze_cell) in grid.cells() {
+        if let Some(color) = style.cell_color(maze_cell) {
+            raster.fill(
+                (left_edge(position), position.1 * cell),
+                (cell, cell),
+                color,
+            );
+        }
+    }
+
+    if let Some(solution) = solution {
+        // Join the centres of consecutive cells with a line a third of a cell thick, leaving out
+        // portal jumps between cells that aren't next to each other. Moves between shifted rows
+        // of hexagonal mazes go down first and then across.
+        let thickness = (cell / 3).max(1);
+        let inset = (cell - thickness) / 2;
+
+        for (&from, &to) in solution
+            .iter()
+            .zip(solution.iter().skip(1).chain(solution.last()))
+            .filter(|&(&from, &to)| !grid.is_jump(from, to))
+        {
+            let (from_x, to_x) = (left_edge(from) + inset, left_edge(to) + inset);
+            let (top, bottom) = (from.1.min(to.1), from.1.max(to.1));
+
+            raster.fill(
+                (from_x, top * cell + inset),
+                (thickness, (bottom - top) * cell + thickness),
+                style.path,
+            );
+            raster.fill(
+                (from_x.min(to_x), to.1 * cell + inset),
+                (from_x.abs_diff(to_x) + thickness, thickness),
+                style.path,
+            );
+        }
+    }
+
+    encode(&raster)
+}
+
+/// Encodes a raster as a truecolour PNG file.
+///
+/// # Errors
+///
+/// This function returns an error if the image dimensions don't fit in a PNG header.
+fn encode(raster: &Raster) -> Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&u32::try_from(raster.width)?.to_be_bytes());
+    header.extend_from_slice(&u32::try_from(raster.height)?.to_be_bytes());
+    // Bit depth, truecolour, deflate compression, no filtering method and no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    push_chunk(&mut png, *b"IHDR", &header)?;
+    push_chunk(&mut png, *b"IDAT", &zlib_stored(&raster.scanlines()))?;
+    push_chunk(&mut png, *b"IEND", &[])?;
+
+    Ok(png)
+}
+
+/// Appends a chunk of the given type and data to a PNG file.
+///
+/// # Errors
+///
+/// This function returns an error if the data is too large for a single chunk.
+fn push_chunk(png: &mut Vec<u8>, kind: [u8; 4], data: &[u8]) -> Result<()> {
+    png.extend_from_slice(&u32::try_from(data.len())?.to_be_bytes());
+    png.extend_from_slice(&kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32(kind.iter().chain(data)).to_be_bytes());
+
+    Ok(())
+}
+
+/// Wraps data in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32 KiB window, no preset dictionary and the fastest compression level
+    let mut stream = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = u8::from(blocks.peek().is_none());
+        let length = u16::try_from(block.len()).unwrap_or(u16::MAX);
+
+        stream.push(last);
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// Computes the CRC-32 checksum PNG chunks end with.
+fn crc32<'bytes, I: IntoIterator<Item = &'bytes u8>>(bytes: I) -> u32 {
+    let mut crc = u32::MAX;
+
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+/// Computes the Adler-32 checksum zlib streams end with.
+fn adler32(data: &[u8]) -> u32 {
+    /// Largest prime smaller than 65536.
+    const MODULUS: u32 = 65_521;
+
+    let (mut low, mut high) = (1_u32, 0_u32);
+    for &byte in data {
+        low = (low + u32::from(byte)) % MODULUS;
+        high = (high + low) % MODULUS;
+    }
+
+    (high << 16) | low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Metadata;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_zlib_stored_splits_blocks() {
+        let data = vec![7; MAX_STORED_BLOCK + 1];
+
+        let stream = zlib_stored(&data);
+
+        assert_eq!(stream.len(), 2 + 5 + MAX_STORED_BLOCK + 5 + 1 + 4);
+        assert_eq!(stream.get(..2), Some([0x78, 0x01].as_slice()));
+        assert_eq!(stream.get(2), Some(&0));
+        assert_eq!(stream.get(2 + 5 + MAX_STORED_BLOCK), Some(&1));
+        assert_eq!(
+            u16::from_be_bytes([
+                *stream.first().expect("the stream should have a header"),
+                *stream.get(1).expect("the stream should have a header")
+            ]) % 31,
+            0,
+            "the zlib header check should pass"
+        );
+    }
+
+    #[test]
+    fn test_render_pixels() {
+        let map = Map {
+            key: "tiny".to_owned(),
+            data: vec!["222".to_owned(), "214".to_owned(), "222".to_owned()],
+            metadata: Metadata::default(),
+        };
+        let style = ImageStyle {
+            cell_size: 3,
+            ..ImageStyle::default()
+        };
+
+        let png = render(&map, Some(&[(1, 1), (2, 1)]), &style).expect("the maze should render");
+
+        assert_eq!(png.get(..8), Some(SIGNATURE.as_slice()));
+        assert_eq!(png.get(12..16), Some(b"IHDR".as_slice()));
+        assert_eq!(png.get(16..24), Some([0, 0, 0, 9, 0, 0, 0, 9].as_slice()));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+
+        // The image data follows the header chunk, as a single stored block after the zlib header
+        // holding 9 scanlines of a filter byte and 9 pixels each
+        assert_eq!(png.get(37..41), Some(b"IDAT".as_slice()));
+        assert_eq!(png.get(43..46), Some([1, 252, 0].as_slice()));
+        let scanlines = png
+            .get(48..48 + 9 * 28)
+            .expect("the image data should hold every scanline");
+        let pixel = |x: usize, y: usize| scanlines.get(y * 28 + 1 + x * 3..y * 28 + 4 + x * 3);
+
+        assert_eq!(pixel(0, 0), Some(style.wall.as_slice()));
+        assert_eq!(pixel(3, 3), Some(style.entry.as_slice()));
+        assert_eq!(pixel(4, 4), Some(style.path.as_slice()));
+        assert_eq!(pixel(8, 3), Some(style.exit.as_slice()));
+    }
+
+    #[test]
+    fn test_fill_clips_to_raster() {
+        let mut raster = Raster::new(2, 2, [0, 0, 0]).expect("the raster should be created");
+
+        raster.fill((1, 1), (5, 5), [1, 2, 3]);
+
+        assert_eq!(raster.pixels, vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3]);
+        assert_eq!(
+            raster.scanlines(),
+            vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3]
+        );
+    }
+}
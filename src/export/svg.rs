@@ -0,0 +1,195 @@
+//! SVG images of mazes.
+//!
+//! This module draws a maze as a scalable vector image, with every wall and the entry and exit
+//! points as a square, and the solution path, if any, as a polyline through the centre of its
+//! cells, broken into one polyline per stretch walked between portal jumps. The rows a hexagonal
+//! maze shifts are drawn half a cell to the right.
+
+use std::fmt::Write as _;
+
+use color_eyre::eyre::Result;
+
+use super::{hex_color, maze_grid, ImageStyle};
+use crate::map::Map;
+
+/// Renders a maze, and optionally the path through it, as an SVG document.
+///
+/// # Errors
+///
+/// This function returns an error if the maze has no rows or the document can't be formatted.
+pub(crate) fn render(
+    map: &Map,
+    solution: Option<&[(usize, usize)]>,
+    style: &ImageStyle,
+) -> Result<String> {
+    let grid = maze_grid(map)?;
+    let topology = grid.topology();
+    let (columns, rows) = (grid.width(), grid.height());
+    let cell = u64::from(style.cell_size);
+    // Shifted rows stick out of the maze by half a cell on the right
+    let overhang = (0..rows).map(|row| topology.shift(row)).max().unwrap_or(0);
+    let width = half_cells(columns as u64 * 2 + u64::from(overhang), cell);
+    let height = rows as u64 * cell;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">"
+    )?;
+    writeln!(
+        svg,
+        "  <title>{}</title>",
+        escape(&format!("labyrintuine: {}", map.key))
+    )?;
+    writeln!(
+        svg,
+        "  <rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>",
+        hex_color(style.background)
+    )?;
+
+    for ((column, row), maze_cell) in grid.cells() {
+        if let Some(color) = style.cell_color(maze_cell) {
+            writeln!(
+                svg,
+                "  <rect x=\"{}\" y=\"{}\" width=\"{cell}\" height=\"{cell}\" fill=\"{}\"/>",
+                half_cells(column as u64 * 2 + u64::from(topology.shift(row)), cell),
+                row as u64 * cell,
+                hex_color(color)
+            )?;
+        }
+    }
+
+    // Run the line through the centre of every cell, in units of half a cell to stay integral, and
+    // break it at portal jumps rather than draw a line between cells that aren't next to each other
+    for stretch in solution
+        .map(|path| grid.stretches(path))
+        .unwrap_or_default()
+    {
+        let points = stretch
+            .iter()
+            .map(|&(column, row)| {
+                format!(
+                    "{},{}",
+                    half_cells(column as u64 * 2 + 1 + u64::from(topology.shift(row)), cell),
+                    half_cells(row as u64 * 2 + 1, cell)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            svg,
+            "  <polyline points=\"{points}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+             stroke-linecap=\"round\" stroke-linejoin=\"round\"/>",
+            hex_color(style.path),
+            (cell / 3).max(1),
+        )?;
+    }
+
+    svg.push_str("</svg>\n");
+
+    Ok(svg)
+}
+
+/// Formats an amount of half cells as a decimal number of user units.
+fn half_cells(halves: u64, cell: u64) -> String {
+    let doubled = halves * cell;
+    if doubled.is_multiple_of(2) {
+        (doubled / 2).to_string()
+    } else {
+        format!("{}.5", doubled / 2)
+    }
+}
+
+/// Escapes the characters with a special meaning in XML text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Topology, map::Metadata};
+
+    #[test]
+    fn test_render_walls_and_solution() {
+        let map = Map {
+            key: "tiny".to_owned(),
+            data: vec!["222".to_owned(), "214".to_owned(), "222".to_owned()],
+            metadata: Metadata::default(),
+        };
+        let style = ImageStyle {
+            cell_size: 3,
+            ..ImageStyle::default()
+        };
+
+        let svg = render(&map, Some(&[(1, 1), (2, 1)]), &style).expect("the maze should render");
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             width=\"9\" height=\"9\" viewBox=\"0 0 9 9\">"
+        ));
+        assert_eq!(svg.matches("fill=\"#000000\"").count(), 7);
+        assert!(svg.contains("<rect x=\"3\" y=\"3\" width=\"3\" height=\"3\" fill=\"#30a030\"/>"));
+        assert!(svg.contains("<polyline points=\"4.5,4.5 7.5,4.5\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_render_shifts_hex_rows() {
+        let map = Map {
+            key: "hive".to_owned(),
+            data: vec!["222".to_owned(), "214".to_owned(), "222".to_owned()],
+            metadata: Metadata {
+                topology: Some(Topology::Hex),
+                ..Metadata::default()
+            },
+        };
+        let style = ImageStyle {
+            cell_size: 2,
+            ..ImageStyle::default()
+        };
+
+        let svg = render(&map, Some(&[(1, 1), (2, 1)]), &style).expect("the maze should render");
+
+        assert!(svg.contains("width=\"7\" height=\"6\""));
+        assert!(svg.contains("<rect x=\"3\" y=\"2\" width=\"2\" height=\"2\" fill=\"#30a030\"/>"));
+        assert!(svg.contains("<polyline points=\"4,3 6,3\""));
+    }
+
+    #[test]
+    fn test_render_without_solution() {
+        let map = Map::default();
+
+        let svg = render(&map, None, &ImageStyle::default()).expect("the maze should render");
+
+        assert!(!svg.contains("<polyline"));
+    }
+
+    #[test]
+    fn test_render_breaks_solution_at_portals() {
+        let map = Map {
+            key: "portal".to_owned(),
+            data: vec![
+                "222222".to_owned(),
+                "21p2p4".to_owned(),
+                "222222".to_owned(),
+            ],
+            metadata: Metadata::default(),
+        };
+        let style = ImageStyle {
+            cell_size: 2,
+            ..ImageStyle::default()
+        };
+
+        let svg = render(&map, Some(&[(1, 1), (2, 1), (4, 1), (5, 1)]), &style)
+            .expect("the maze should render");
+
+        assert!(svg.contains("<polyline points=\"3,3 5,3\""));
+        assert!(svg.contains("<polyline points=\"9,3 11,3\""));
+        assert!(svg.contains("fill=\"#00c8c8\""));
+    }
+}
@@ -1,129 +1,237 @@
 //! File loading and validation utilities for labyrinth map files.
 
-use std::fs;
+use std::{
+    fs::{self, DirEntry},
+    path::Path,
+};
 
-use color_eyre::eyre::{OptionExt as _, Result};
+use color_eyre::eyre::{ensure, eyre, OptionExt as _, Report, Result, WrapErr as _};
 
-use crate::map::Map;
+use crate::{
+    error::{Recoverable, RecoverableExt as _},
+    grid::{Cell, Grid, Topology},
+    map::{self, Map},
+    portal,
+    puzzle::{self, KeySet},
+};
 
 /// Scans the current directory for .labmap files and loads them.
 ///
 /// This function searches for files with the .labmap extension in the current working directory,
 /// validates their format, and adds them to the maps collection for user selection. It skips
-/// invalid files and continues processing valid ones.
-pub(crate) fn fetch_files(maps: &mut Vec<Map>) -> Result<()> {
-    for file in fs::read_dir(".")? {
-        match file {
-            Ok(file)
-                if !file.file_type()?.is_dir()
-                    && file
-                        .file_name()
-                        .to_str()
-                        .ok_or_eyre("failed to convert osstring to string slice")?
-                        .ends_with(".labmap") =>
-            {
-                let contents = fs::read_to_string(file.path())?;
-
-                if parse_file_contents(contents.trim()) {
-                    maps.push(Map::new(file.file_name(), &contents)?);
-                }
-            }
-            Err(err) => return Err(err.into()),
-            _ => {}
+/// invalid files and continues processing valid ones. Files that can't be read are skipped as well,
+/// and the reasons they were skipped are returned as recoverable errors.
+///
+/// # Errors
+///
+/// This function returns a recoverable error if the current directory can't be listed.
+pub(crate) fn fetch_files(maps: &mut Vec<Map>) -> Result<Vec<Report>> {
+    let mut skipped = Vec::new();
+
+    for file in fs::read_dir(".")
+        .wrap_err("failed to list the current directory")
+        .recoverable()?
+    {
+        let loaded = file
+            .wrap_err("failed to read directory entry")
+            .and_then(|file| load_file(&file));
+
+        match loaded {
+            Ok(Some(map)) => maps.push(map),
+            Ok(None) => {}
+            Err(err) => skipped.push(Recoverable(format!("{err:#}")).into()),
         }
     }
 
-    Ok(())
+    Ok(skipped)
+}
+
+/// Loads a single directory entry as a labyrinth map.
+///
+/// This function returns `None` for directories, files without the .labmap extension and files
+/// whose contents don't pass validation. The extension is checked before the file name is decoded,
+/// so that only map files with a non-UTF-8 name are reported.
+///
+/// # Errors
+///
+/// This function may return errors if:
+/// - The file name isn't valid UTF-8
+/// - The file type or contents can't be read
+fn load_file(file: &DirEntry) -> Result<Option<Map>> {
+    let file_name = file.file_name();
+    if !file_name.as_encoded_bytes().ends_with(b".labmap") {
+        return Ok(None);
+    }
+
+    let name = file_name
+        .to_str()
+        .ok_or_else(|| eyre!("skipped file with non-UTF-8 name {file_name:?}"))?;
+
+    if file
+        .file_type()
+        .wrap_err_with(|| format!("failed to read file type of {name}"))?
+        .is_dir()
+    {
+        return Ok(None);
+    }
+
+    let contents =
+        fs::read_to_string(file.path()).wrap_err_with(|| format!("failed to read {name}"))?;
+
+    if !parse_file_contents(contents.trim()) {
+        return Ok(None);
+    }
+
+    Map::new(file_name.clone(), &contents)
+        .wrap_err_with(|| format!("failed to load {name}"))
+        .map(Some)
+}
+
+/// Loads the labyrinth map at the given path.
+///
+/// Unlike the maps found by [`fetch_files`], a map given by path is expected to be valid, so a
+/// file whose contents don't pass validation is reported instead of skipped.
+///
+/// # Errors
+///
+/// This function may return errors if:
+/// - The file name isn't valid UTF-8 or lacks the .labmap extension
+/// - The file can't be read
+/// - The file contents don't pass validation
+pub(crate) fn load_map(path: &Path) -> Result<Map> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| eyre!("{} isn't a file", path.display()))?;
+    let name = file_name
+        .to_str()
+        .ok_or_eyre("map file names must be valid UTF-8")?;
+    ensure!(
+        name.ends_with(".labmap"),
+        "{name} lacks the .labmap extension"
+    );
+
+    let contents =
+        fs::read_to_string(path).wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    ensure!(
+        parse_file_contents(contents.trim()),
+        "{name} isn't a valid labyrinth map"
+    );
+
+    Map::new(file_name.to_owned(), &contents).wrap_err_with(|| format!("failed to load {name}"))
 }
 
 /// Validates the format and content of labyrinth map files.
 ///
 /// This function performs validation to ensure the maze format follows the specification:
-/// - Contains only valid characters (1-4)
+/// - Contains only valid characters (1-4, terrain from 5 to 9, keys from a to e, doors from A to
+///   E, portals from p to u and arrows ^, v, < and >)
 /// - Has consistent row lengths
-/// - Has exactly one entry point (1)
-/// - Is completely surrounded by walls (2s) except for exit points on the edges
+/// - Has at least one entry point (1)
+/// - Holds the key of every door it holds
+/// - Holds every portal it holds as a pair, each portal letter appearing exactly twice if at all
+/// - Only holds arrows and crossings (#) if its cells are square
+/// - Has floors of the same size, separated by `===` lines, if it has several of them
+/// - Holds stairs only where they land on stairs leading back, up on the floor above or down on
+///   the floor below
+/// - Has every floor completely surrounded by walls (2s) except for exit points on the edges
+///
+/// Both versions of the map format are accepted, the header of version 2 files being checked by
+/// [`map::parse_contents`] before the grid that follows it is validated with the topology it gives.
 pub(crate) fn parse_file_contents(input: &str) -> bool {
-    let lines: Vec<&str> = input.lines().collect();
-
-    // Must have at least 3x3 to form a proper walled maze
-    if lines.len() < 3 {
+    let Ok((metadata, lines)) = map::parse_contents(input) else {
         return false;
-    }
+    };
+    let topology = metadata.topology.unwrap_or_default();
 
-    let mut entry_point_counter = 0;
-    let Some(first_line) = lines.first() else {
+    // Check consistent row lengths and valid characters only
+    let Ok(grid) = Grid::new(&lines, topology) else {
         return false;
     };
-    let expected_width = first_line.len();
 
-    // Must have at least 3 columns to form proper walls
-    if expected_width < 3 {
+    // Must have at least 3x3 to form a proper walled maze
+    if grid.floor_width() < 3 || grid.height() < 3 {
         return false;
     }
 
-    // Validate each line
-    for line in &lines {
-        // Check consistent row lengths
-        if line.len() != expected_width {
-            return false;
+    // Gather the keys and doors
+    let mut keys = KeySet::default();
+    let mut doors = KeySet::default();
+    for (_, cell) in grid.cells() {
+        match cell {
+            Cell::Key(key) => keys = keys.with(key),
+            Cell::Door(key) => doors = doors.with(key),
+            _ => {}
         }
+    }
 
-        // Check valid characters only
-        if !line
-            .bytes()
-            .all(|byte| matches!(byte, b'1' | b'2' | b'3' | b'4'))
-        {
-            return false;
-        }
+    // Every door must be opened by a key lying somewhere in the maze
+    if (0..puzzle::KEY_COUNT).any(|key| doors.contains(key) && !keys.contains(key)) {
+        return false;
+    }
 
-        // Count entry points
-        for byte in line.bytes() {
-            if byte == b'1' {
-                entry_point_counter += 1;
-            }
-        }
+    // Portals must come in pairs, so that each of them leads somewhere
+    let unpaired = (0..portal::PAIR_COUNT).any(|pair| {
+        let count = grid
+            .cells()
+            .filter(|&(_, cell)| cell == Cell::Portal(pair))
+            .count();
+        count != 0 && count != 2
+    });
+    if unpaired {
+        return false;
+    }
 
-        // Too many entry points
-        if entry_point_counter > 1 {
-            return false;
-        }
+    // Must have at least one entry point
+    if grid.entries().is_empty() {
+        return false;
     }
 
-    // Must have exactly one entry point
-    if entry_point_counter != 1 {
+    // Arrows point north, south, east or west, which only square cells all have neighbours in, and
+    // crossings need a straight corridor across them both ways
+    let squared = grid
+        .cells()
+        .any(|(_, cell)| matches!(cell, Cell::Arrow(_) | Cell::Crossing));
+    if topology != Topology::Square && squared {
         return false;
     }
 
-    let last_row_idx = lines.len() - 1;
-    let last_col_idx = expected_width - 1;
-
-    // Check boundary walls and validate maze structure in a single pass
-    for (row_idx, line) in lines.iter().enumerate() {
-        for (col_idx, byte) in line.bytes().enumerate() {
-            let is_edge =
-                row_idx == 0 || row_idx == last_row_idx || col_idx == 0 || col_idx == last_col_idx;
-
-            if is_edge {
-                // On edges: only walls (2) or exit points (4) allowed
-                if !matches!(byte, b'2' | b'4') {
-                    return false;
-                }
-            } else {
-                // Interior: exit points (4) not allowed
-                if byte == b'4' {
-                    return false;
-                }
-            }
-        }
+    // Stairs must lead to another floor, where stairs lead back
+    let stranded = grid.cells().any(|(position, cell)| {
+        matches!(cell, Cell::Stairs(_))
+            && grid
+                .node(position)
+                .and_then(|node| grid.landing(node))
+                .is_none()
+    });
+    if stranded {
+        return false;
     }
 
-    true
+    let last_row_idx = grid.height() - 1;
+    let last_col_idx = grid.floor_width() - 1;
+
+    // Check the boundary walls of every floor and validate maze structure in a single pass
+    let walled = grid.cells().all(|(position, cell)| {
+        let (col_idx, row_idx) = grid.local(position);
+        let is_edge =
+            row_idx == 0 || row_idx == last_row_idx || col_idx == 0 || col_idx == last_col_idx;
+
+        if is_edge {
+            // On edges: only walls or exit points allowed
+            matches!(cell, Cell::Wall | Cell::Exit)
+        } else {
+            // Interior: exit points not allowed
+            cell != Cell::Exit
+        }
+    });
+
+    walled
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::TempDir;
 
     #[test]
     fn test_parse_file_contents_valid_maze() {
@@ -161,6 +269,75 @@ mod tests {
         assert!(!parse_file_contents(invalid_maze));
     }
 
+    #[test]
+    fn test_parse_file_contents_terrain() {
+        assert!(parse_file_contents("222222\n215794\n222222"));
+        assert!(!parse_file_contents("252222\n213334\n222222"));
+    }
+
+    #[test]
+    fn test_parse_file_contents_keys_and_doors() {
+        assert!(parse_file_contents("222222\n21Ab34\n23a2B2\n222222"));
+        assert!(!parse_file_contents("222222\n21A334\n232222\n222222"));
+        assert!(!parse_file_contents("222222\n21A334\n23f222\n222222"));
+    }
+
+    #[test]
+    fn test_parse_file_contents_arrows() {
+        assert!(parse_file_contents("222222\n21>v34\n23<^22\n222222"));
+        assert!(!parse_file_contents("2^2222\n213334\n222222"));
+    }
+
+    #[test]
+    fn test_parse_file_contents_crossings() {
+        assert!(parse_file_contents("22222\n22322\n21#34\n22322\n22222"));
+        assert!(!parse_file_contents("22#22\n21334\n22222"));
+        assert!(!parse_file_contents(
+            "labmap 2\ntopology: hex\n---\n22222\n21#34\n22222"
+        ));
+    }
+
+    #[test]
+    fn test_parse_file_contents_topology() {
+        assert!(parse_file_contents(
+            "labmap 2\ntopology: hex\n---\n2222\n2134\n2222"
+        ));
+        assert!(!parse_file_contents(
+            "labmap 2\ntopology: hex\n---\n22222\n21>34\n22222"
+        ));
+        assert!(!parse_file_contents(
+            "labmap 2\ntopology: round\n---\n2222\n2134\n2222"
+        ));
+    }
+
+    #[test]
+    fn test_parse_file_contents_floors() {
+        assert!(parse_file_contents(
+            "2222\n21+2\n2222\n===\n2222\n23-2\n2422"
+        ));
+        assert!(parse_file_contents(
+            "labmap 2\n---\n22222\n21+32\n22224\n===\n22222\n23-32\n22222"
+        ));
+        // Stairs must land on stairs leading back, and every floor must be walled in
+        assert!(!parse_file_contents(
+            "2222\n21+2\n2222\n===\n2222\n2332\n2422"
+        ));
+        assert!(!parse_file_contents("2222\n21-4\n2222"));
+        assert!(!parse_file_contents(
+            "2222\n2132\n2222\n===\n2222\n3342\n2222"
+        ));
+        assert!(!parse_file_contents("2222\n2134\n2222\n===\n2222\n2332"));
+    }
+
+    #[test]
+    fn test_parse_file_contents_portals() {
+        assert!(parse_file_contents("222222\n21p2p4\n222222"));
+        assert!(parse_file_contents("2222222\n21pq3q2\n2p22224\n2222222"));
+        assert!(!parse_file_contents("222222\n21p334\n222222"));
+        assert!(!parse_file_contents("222222\n21ppp4\n222222"));
+        assert!(!parse_file_contents("222222\n21z2z4\n222222"));
+    }
+
     #[test]
     fn test_parse_file_contents_no_entry_point() {
         let invalid_maze = "2222\n2334\n2222";
@@ -169,8 +346,8 @@ mod tests {
 
     #[test]
     fn test_parse_file_contents_multiple_entry_points() {
-        let invalid_maze = "2222\n2114\n2222";
-        assert!(!parse_file_contents(invalid_maze));
+        let valid_maze = "22222\n21314\n22222";
+        assert!(parse_file_contents(valid_maze));
     }
 
     #[test]
@@ -196,8 +373,61 @@ mod tests {
         assert!(!parse_file_contents(""));
     }
 
+    #[test]
+    fn test_parse_file_contents_with_header() {
+        assert!(parse_file_contents(
+            "labmap 2\n# comment\ntitle: Tiny\n---\n2222\n2134\n2222"
+        ));
+        assert!(!parse_file_contents(
+            "labmap 2\ntitle: Tiny\n2222\n2134\n2222"
+        ));
+        assert!(!parse_file_contents("labmap 9\n---\n2222\n2134\n2222"));
+    }
+
     #[test]
     fn test_parse_file_contents_single_line() {
         assert!(!parse_file_contents("222"));
     }
+
+    #[test]
+    fn test_load_file_skips_and_reports() {
+        let dir = TempDir::new("load");
+        fs::create_dir(dir.join("folder.labmap")).expect("failed to create test directory");
+        fs::write(dir.join("valid.labmap"), "2222\n2134\n2222").expect("failed to write map");
+        fs::write(dir.join("invalid.labmap"), "2222\n2224\n2222").expect("failed to write map");
+        fs::write(dir.join("notes.txt"), "2222\n2134\n2222").expect("failed to write file");
+
+        let mut loaded = fs::read_dir(dir.path())
+            .expect("failed to list test directory")
+            .map(|file| load_file(&file.expect("failed to read directory entry")))
+            .collect::<Result<Vec<_>>>()
+            .expect("loading files should succeed")
+            .into_iter()
+            .flatten()
+            .map(|map| map.key)
+            .collect::<Vec<_>>();
+        loaded.sort();
+
+        assert_eq!(loaded, vec!["valid"]);
+    }
+
+    #[test]
+    fn test_load_map_by_path() {
+        let dir = TempDir::new("path");
+        fs::write(dir.join("valid.labmap"), "2222\n2134\n2222").expect("failed to write map");
+        fs::write(dir.join("invalid.labmap"), "2222\n2224\n2222").expect("failed to write map");
+        fs::write(dir.join("valid.txt"), "2222\n2134\n2222").expect("failed to write file");
+
+        let valid = load_map(&dir.join("valid.labmap"));
+        let invalid = load_map(&dir.join("invalid.labmap"));
+        let wrong_extension = load_map(&dir.join("valid.txt"));
+        let missing = load_map(&dir.join("missing.labmap"));
+
+        let valid = valid.expect("a valid map should load");
+        assert_eq!(valid.key, "valid");
+        assert_eq!(valid.data, vec!["2222", "2134", "2222"]);
+        assert!(invalid.is_err());
+        assert!(wrong_extension.is_err());
+        assert!(missing.is_err());
+    }
 }
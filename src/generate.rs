@@ -0,0 +1,510 @@
+//! Generation of random mazes.
+//!
+//! This module carves mazes out of a grid of walls with a randomised depth-first search, the
+//! recursive backtracker. The search only walks the neighbours given by the [`Topology`] of the
+//! maze, so that square, hexagonal and triangular mazes all come out of the same algorithm. A cell
+//! is only carved when the cell it's carved from is the only open cell next to it, which keeps the
+//! open cells a tree: there is a single way between any two of them.
+//!
+//! Woven square mazes may also tunnel under a straight corridor, turning the corridor cell into a
+//! crossing, carving the cells on both sides of it and going on from the far one. The corridor and
+//! the tunnel stay apart in the maze graph, so that the open cells are still a tree.
+//!
+//! Dungeons, made of rooms joined by corridors, are laid out by the [`dungeon`] module instead.
+
+mod dungeon;
+
+use std::{
+    cmp::Reverse,
+    collections::VecDeque,
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{ensure, eyre, OptionExt as _, Result, WrapErr as _};
+
+pub use dungeon::DungeonSettings;
+
+use crate::{
+    file_loader,
+    grid::{Grid, NodeId, Topology},
+    map::Map,
+    time, weave,
+};
+
+/// Name of the algorithm mazes are generated with, as written in the header of their map.
+const GENERATOR: &str = "backtracker";
+
+/// Name of the algorithm woven mazes are generated with.
+const WEAVE_GENERATOR: &str = "weave backtracker";
+
+/// Name of the algorithm dungeons are laid out with.
+const DUNGEON_GENERATOR: &str = "dungeon";
+
+/// Settings of a maze generation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerateSettings {
+    /// Shape of the cells of the maze.
+    pub topology: Topology,
+    /// `(columns, rows)` size of the maze, its border of walls included.
+    pub size: (usize, usize),
+    /// Seed of the maze, or `None` to pick one from the clock.
+    pub seed: Option<u64>,
+    /// Whether corridors may pass under each other, which only square mazes allow.
+    pub weave: bool,
+    /// Rooms and corridors to lay out instead of carving a maze, if any, which only square mazes
+    /// allow.
+    pub dungeon: Option<DungeonSettings>,
+}
+
+impl Default for GenerateSettings {
+    fn default() -> Self {
+        Self {
+            topology: Topology::Square,
+            size: (31, 21),
+            seed: None,
+            weave: false,
+            dungeon: None,
+        }
+    }
+}
+
+/// Way the backtracker carves out of an open cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Passage {
+    /// Carving the neighbouring cell.
+    Step(NodeId),
+    /// Tunnelling under a straight corridor.
+    Under {
+        /// Cell carved between the open cell and the corridor.
+        near: NodeId,
+        /// Cell of the corridor that becomes a crossing.
+        crossing: NodeId,
+        /// Cell carved beyond the corridor, which the backtracker goes on from.
+        far: NodeId,
+    },
+}
+
+/// Pseudo-random number generator, so that a seed always yields the same maze.
+///
+/// This structure implements the `SplitMix64` generator, whose whole state is a single counter.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator starting from the given seed.
+    pub(crate) const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns the next pseudo-random number.
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut mixed = self.0;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        mixed ^ (mixed >> 31)
+    }
+
+    /// Returns a pseudo-random index below the given bound, or 0 if the bound is 0.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        u64::try_from(bound)
+            .ok()
+            .and_then(|bound| self.next().checked_rem(bound))
+            .and_then(|index| usize::try_from(index).ok())
+            .unwrap_or(0)
+    }
+
+    /// Shuffles the given items in place.
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+    }
+}
+
+/// Returns a seed picked from the clock, for generations that aren't given one.
+pub(crate) fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX)
+        })
+}
+
+/// Generates a maze and writes it to the output file as a labyrinth map.
+///
+/// The map header records the generator, the seed and the topology of the maze, so that the same
+/// maze can be generated again.
+///
+/// # Errors
+///
+/// This function returns an error if the maze is smaller than 3x3 cells, if a maze that isn't
+/// square is to be woven or laid out as a dungeon, if a dungeon is to be woven, if the dungeon
+/// settings are invalid, or if the output isn't a .labmap file or can't be written.
+pub(crate) fn generate(output: &Path, settings: &GenerateSettings) -> Result<Map> {
+    let file_name = output
+        .file_name()
+        .ok_or_else(|| eyre!("{} isn't a file", output.display()))?;
+    ensure!(
+        file_name.as_encoded_bytes().ends_with(b".labmap"),
+        "{} lacks the .labmap extension",
+        output.display()
+    );
+
+    let seed = settings.seed.unwrap_or_else(clock_seed);
+    let (rows, generator) = match &settings.dungeon {
+        Some(dungeon) => {
+            ensure!(
+                settings.topology == Topology::Square && !settings.weave,
+                "dungeons can only be square, without crossings"
+            );
+            (
+                dungeon::carve(dungeon, settings.size, seed)?,
+                DUNGEON_GENERATOR,
+            )
+        }
+        None if settings.weave => (
+            carve(settings.topology, settings.size, seed, true)?,
+            WEAVE_GENERATOR,
+        ),
+        None => (
+            carve(settings.topology, settings.size, seed, false)?,
+            GENERATOR,
+        ),
+    };
+
+    let mut map = Map::new(file_name.to_owned(), &rows.join("\n"))?;
+    map.metadata.generator = Some(generator.to_owned());
+    map.metadata.seed = Some(seed);
+    map.metadata.topology = Some(settings.topology);
+    map.metadata.created = Some(time::today());
+
+    let contents = map.to_labmap();
+    ensure!(
+        file_loader::parse_file_contents(&contents),
+        "the generated maze isn't a valid labyrinth map"
+    );
+    fs::write(output, contents)
+        .wrap_err_with(|| format!("failed to write {}", output.display()))?;
+
+    Ok(map)
+}
+
+/// Carves a maze of the given topology and `(columns, rows)` size, and returns the rows of its map.
+///
+/// The maze is carved from the entry point in the top left corner, and the exit is opened on the
+/// border next to the open cell farthest from it. Woven mazes may also tunnel under corridors.
+///
+/// # Errors
+///
+/// This function returns an error if the maze is smaller than 3x3 cells, or if it's woven without
+/// being square.
+fn carve(
+    topology: Topology,
+    (columns, rows): (usize, usize),
+    seed: u64,
+    weave: bool,
+) -> Result<Vec<String>> {
+    ensure!(
+        columns >= 3 && rows >= 3,
+        "a maze must be at least 3x3 cells, found {columns}x{rows}"
+    );
+    ensure!(
+        !weave || topology == Topology::Square,
+        "only square mazes can be woven, found a {} one",
+        topology.name()
+    );
+
+    let grid = Grid::new(&vec!["2".repeat(columns); rows], topology)?;
+    let entry = grid
+        .node((1, 1))
+        .ok_or_eyre("the maze has no cell inside its border")?;
+
+    let mut rng = Rng::new(seed);
+    let mut open = vec![false; columns * rows];
+    if let Some(cell) = open.get_mut(entry) {
+        *cell = true;
+    }
+    let mut crossings = Vec::new();
+
+    let mut stack = vec![entry];
+    while let Some(&node) = stack.last() {
+        let steps = grid
+            .neighbours(node)
+            .filter(|&next| is_free(&grid, &open, next, &[node]))
+            .map(Passage::Step);
+        let tunnels = grid
+            .topology()
+            .steps(grid.position(node))
+            .iter()
+            .filter(|_| weave)
+            .filter_map(|&step| tunnel(&grid, &open, &crossings, node, step));
+        let candidates = steps.chain(tunnels).collect::<Vec<_>>();
+
+        match candidates.get(rng.below(candidates.len())) {
+            Some(&Passage::Step(next)) => {
+                if let Some(cell) = open.get_mut(next) {
+                    *cell = true;
+                }
+                stack.push(next);
+            }
+            Some(&Passage::Under {
+                near,
+                crossing,
+                far,
+            }) => {
+                for carved in [near, far] {
+                    if let Some(cell) = open.get_mut(carved) {
+                        *cell = true;
+                    }
+                }
+                crossings.push(crossing);
+                stack.push(far);
+            }
+            None => {
+                let _ = stack.pop();
+            }
+        }
+    }
+
+    let mut carved = (0..rows)
+        .map(|row| {
+            (0..columns)
+                .map(|column| match grid.node((column, row)) {
+                    Some(node) if node == entry => '1',
+                    Some(node) if crossings.contains(&node) => char::from(weave::CROSSING),
+                    Some(node) if is_open(&open, node) => '3',
+                    _ => '2',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+
+    // Open the exit on the border, next to the farthest open cell that touches it
+    let distances = distances(&Grid::new(&carved, topology)?, entry);
+    let exit = (0..columns * rows)
+        .filter(|&node| !is_interior(&grid, node))
+        .filter_map(|node| {
+            grid.neighbours(node)
+                .filter_map(|next| distances.get(next).copied().flatten())
+                .max()
+                .map(|distance| (distance, node))
+        })
+        .max_by_key(|&(distance, node)| (distance, Reverse(node)))
+        .map(|(_, node)| node)
+        .ok_or_eyre("no border cell touches the carved maze")?;
+
+    let (column, row) = grid.position(exit);
+    if let Some(line) = carved.get_mut(row) {
+        line.replace_range(column..=column, "4");
+    }
+    Ok(carved)
+}
+
+/// Returns whether the given node lies inside the border of the maze.
+fn is_interior(grid: &Grid, node: NodeId) -> bool {
+    let (column, row) = grid.position(node);
+    (1..grid.width().saturating_sub(1)).contains(&column)
+        && (1..grid.height().saturating_sub(1)).contains(&row)
+}
+
+/// Returns whether the given node has been carved open.
+fn is_open(open: &[bool], node: NodeId) -> bool {
+    open.get(node).copied().unwrap_or(false)
+}
+
+/// Returns whether the given closed node can be carved, which it can if no open cell but the given
+/// ones lies next to it.
+fn is_free(grid: &Grid, open: &[bool], node: NodeId, from: &[NodeId]) -> bool {
+    is_interior(grid, node)
+        && !is_open(open, node)
+        && grid
+            .neighbours(node)
+            .all(|other| from.contains(&other) || !is_open(open, other))
+}
+
+/// Returns the tunnel that can be dug from the given node with the given step, if any.
+///
+/// The corridor tunnelled under must run straight across the way, two steps away, and the cells on
+/// both sides of it must be free to carve.
+fn tunnel(
+    grid: &Grid,
+    open: &[bool],
+    crossings: &[NodeId],
+    node: NodeId,
+    (dx, dy): (isize, isize),
+) -> Option<Passage> {
+    let near = grid.neighbour(node, (dx, dy))?;
+    let crossing = grid.neighbour(near, (dx, dy))?;
+    let far = grid.neighbour(crossing, (dx, dy))?;
+    let across = [(dy, dx), (-dy, -dx)].iter().all(|&step| {
+        grid.neighbour(crossing, step)
+            .is_some_and(|side| is_open(open, side))
+    });
+
+    (across
+        && !crossings.contains(&crossing)
+        && is_free(grid, open, near, &[node, crossing])
+        && is_free(grid, open, far, &[crossing]))
+    .then_some(Passage::Under {
+        near,
+        crossing,
+        far,
+    })
+}
+
+/// Returns the number of moves from the entry point to every node of the carved maze, if it can be
+/// reached, over and under crossings.
+fn distances(maze: &Grid, entry: NodeId) -> Vec<Option<usize>> {
+    let mut distances = vec![None; maze.node_count()];
+    if let Some(distance) = distances.get_mut(entry) {
+        *distance = Some(0);
+    }
+    let mut queue = VecDeque::from([entry]);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances.get(node).copied().flatten().unwrap_or(0);
+        for next in maze.moves(node) {
+            if let Some(slot @ None) = distances.get_mut(next) {
+                *slot = Some(distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pathfinding::{self, AnimationManager},
+        testing::TempDir,
+    };
+
+    #[test]
+    fn test_carve_every_topology() {
+        for topology in Topology::ALL {
+            let rows = carve(topology, (15, 9), 42, false).expect("the maze should be carved");
+            let mut map = Map::new("maze.labmap".into(), &rows.join("\n"))
+                .expect("the rows should form a map");
+            map.metadata.topology = Some(topology);
+
+            assert!(
+                file_loader::parse_file_contents(&map.to_labmap()),
+                "{} mazes should be valid",
+                topology.name()
+            );
+            assert_eq!(
+                rows,
+                carve(topology, (15, 9), 42, false).expect("the maze should be carved")
+            );
+
+            let mut manager = AnimationManager::new();
+            manager
+                .record(Grid::of(&map).expect("the map should form a grid"))
+                .expect("the maze has an entry point");
+            assert!(
+                manager.found.is_some(),
+                "{} mazes should have a way out",
+                topology.name()
+            );
+        }
+
+        assert_ne!(
+            carve(Topology::Square, (15, 9), 1, false).expect("the maze should be carved"),
+            carve(Topology::Square, (15, 9), 2, false).expect("the maze should be carved")
+        );
+        assert!(carve(Topology::Hex, (2, 9), 42, false).is_err());
+    }
+
+    #[test]
+    fn test_carve_weave() {
+        let woven = (0..20)
+            .map(|seed| carve(Topology::Square, (21, 15), seed, true))
+            .collect::<Result<Vec<_>>>()
+            .expect("the mazes should be carved");
+
+        for rows in &woven {
+            let map = Map::new("maze.labmap".into(), &rows.join("\n"))
+                .expect("the rows should form a map");
+            assert!(file_loader::parse_file_contents(&map.to_labmap()));
+
+            // Every open cell but the exit is reached once, crossings once over and once under
+            let grid = Grid::of(&map).expect("the map should form a grid");
+            let open = rows
+                .iter()
+                .flat_map(|row| row.bytes())
+                .map(|cell| match cell {
+                    weave::CROSSING => 2,
+                    b'2' | b'4' => 0,
+                    _ => 1,
+                })
+                .sum::<usize>();
+            assert_eq!(
+                pathfinding::reachable(&grid, &grid.entries()).len(),
+                open + 1
+            );
+        }
+        assert!(woven
+            .iter()
+            .flatten()
+            .any(|row| row.contains(char::from(weave::CROSSING))));
+        assert!(carve(Topology::Hex, (21, 15), 42, true).is_err());
+    }
+
+    #[test]
+    fn test_generate_writes_valid_map() {
+        let dir = TempDir::new("generate");
+
+        let settings = GenerateSettings {
+            topology: Topology::Hex,
+            size: (11, 7),
+            seed: Some(7),
+            weave: false,
+            dungeon: None,
+        };
+        let map = generate(&dir.join("hive.labmap"), &settings);
+        let written = file_loader::load_map(&dir.join("hive.labmap"));
+        let wrong_extension = generate(&dir.join("hive.txt"), &settings);
+
+        let map = map.expect("the maze should be generated");
+        assert_eq!(map.key, "hive");
+        assert_eq!(written.expect("the generated map should load"), map);
+        assert_eq!(map.metadata.generator.as_deref(), Some(GENERATOR));
+        assert_eq!(map.metadata.seed, Some(7));
+        assert_eq!(map.metadata.topology, Some(Topology::Hex));
+        assert!(wrong_extension.is_err());
+    }
+
+    #[test]
+    fn test_generate_dungeon() {
+        let dir = TempDir::new("dungeon");
+
+        let settings = GenerateSettings {
+            seed: Some(3),
+            dungeon: Some(DungeonSettings::default()),
+            ..GenerateSettings::default()
+        };
+        let map = generate(&dir.join("dungeon.labmap"), &settings);
+        let hex = generate(
+            &dir.join("hex.labmap"),
+            &GenerateSettings {
+                topology: Topology::Hex,
+                ..settings
+            },
+        );
+        let woven = generate(
+            &dir.join("woven.labmap"),
+            &GenerateSettings {
+                weave: true,
+                ..settings
+            },
+        );
+
+        let map = map.expect("the dungeon should be generated");
+        assert_eq!(map.metadata.generator.as_deref(), Some(DUNGEON_GENERATOR));
+        assert!(hex.is_err());
+        assert!(woven.is_err());
+    }
+}
@@ -0,0 +1,360 @@
+//! Generation of dungeons.
+//!
+//! This module lays out mazes the way roguelike levels are: rectangular rooms scattered over the
+//! map with walls between them, joined by corridors that turn once. The corridors follow a minimum
+//! spanning tree of the rooms, so that every room can be reached, along with some of the shortest
+//! remaining connections, which make loops. The entry point lies in the middle of the first room
+//! placed, and the exits open on the border next to the rooms farthest from it.
+
+use std::cmp::Reverse;
+
+use color_eyre::eyre::{ensure, eyre, Result};
+
+use super::{distances, Rng};
+use crate::grid::{Grid, Topology};
+
+/// Number of attempts at placing each room before the dungeon makes do with fewer rooms.
+const ATTEMPTS: usize = 50;
+
+/// Settings of a dungeon generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DungeonSettings {
+    /// Number of rooms, fewer being placed if they don't fit.
+    pub rooms: usize,
+    /// Smallest `(columns, rows)` size of a room.
+    pub min_room: (usize, usize),
+    /// Largest `(columns, rows)` size of a room.
+    pub max_room: (usize, usize),
+    /// Number of extra corridors making loops, per hundred corridors needed to join the rooms.
+    pub loop_percent: usize,
+    /// Number of exits, each next to a different room.
+    pub exits: usize,
+}
+
+impl Default for DungeonSettings {
+    fn default() -> Self {
+        Self {
+            rooms: 6,
+            min_room: (3, 3),
+            max_room: (7, 5),
+            loop_percent: 25,
+            exits: 1,
+        }
+    }
+}
+
+/// Rectangular room of a dungeon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Room {
+    /// Column of the leftmost cells of the room.
+    x: usize,
+    /// Row of the topmost cells of the room.
+    y: usize,
+    /// Number of columns of the room.
+    width: usize,
+    /// Number of rows of the room.
+    height: usize,
+}
+
+impl Room {
+    /// Returns the `(column, row)` coordinates of the cell in the middle of the room.
+    const fn centre(self) -> (usize, usize) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    /// Returns whether the room overlaps the given one, or touches it without a wall between them.
+    const fn touches(self, other: Self) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    /// Returns the number of moves between the middle of the room and the middle of the given one.
+    const fn distance(self, other: Self) -> usize {
+        let ((x, y), (other_x, other_y)) = (self.centre(), other.centre());
+        x.abs_diff(other_x) + y.abs_diff(other_y)
+    }
+}
+
+/// Lays out a dungeon of the given `(columns, rows)` size, and returns the rows of its map.
+///
+/// # Errors
+///
+/// This function returns an error if the settings ask for no room or no exit, if the smallest rooms
+/// are larger than the largest ones or don't fit inside the border, or if no room could be placed.
+pub(super) fn carve(
+    settings: &DungeonSettings,
+    (columns, rows): (usize, usize),
+    seed: u64,
+) -> Result<Vec<String>> {
+    let (min_room, max_room) = (settings.min_room, settings.max_room);
+    ensure!(
+        settings.rooms > 0 && settings.exits > 0,
+        "a dungeon needs at least one room and one exit"
+    );
+    ensure!(
+        min_room.0 > 0 && min_room.1 > 0 && min_room.0 <= max_room.0 && min_room.1 <= max_room.1,
+        "rooms must be at least 1x1 cells and at most as large as allowed, found {}x{} to {}x{}",
+        min_room.0,
+        min_room.1,
+        max_room.0,
+        max_room.1
+    );
+    ensure!(
+        min_room.0 + 2 <= columns && min_room.1 + 2 <= rows,
+        "rooms of {}x{} cells don't fit in a {columns}x{rows} dungeon",
+        min_room.0,
+        min_room.1
+    );
+
+    let mut rng = Rng::new(seed);
+    let mut between = |low: usize, high: usize| low + rng.below(high - low + 1);
+    let mut placed: Vec<Room> = Vec::new();
+    for _ in 0..settings.rooms * ATTEMPTS {
+        if placed.len() == settings.rooms {
+            break;
+        }
+        let width = between(min_room.0, max_room.0.min(columns - 2));
+        let height = between(min_room.1, max_room.1.min(rows - 2));
+        let room = Room {
+            x: between(1, columns - 1 - width),
+            y: between(1, rows - 1 - height),
+            width,
+            height,
+        };
+        if placed.iter().all(|&other| !room.touches(other)) {
+            placed.push(room);
+        }
+    }
+    let first = *placed
+        .first()
+        .ok_or_else(|| eyre!("no room fits in the dungeon"))?;
+
+    let mut cells = vec![vec![b'2'; columns]; rows];
+    for room in &placed {
+        for y in room.y..room.y + room.height {
+            for x in room.x..room.x + room.width {
+                set(&mut cells, (x, y), b'3');
+            }
+        }
+    }
+    for (from, to) in corridors(&placed, settings.loop_percent) {
+        let ((from_x, from_y), (to_x, to_y)) = (from.centre(), to.centre());
+        // Corridors turn at either end, picked at random
+        let corner = if rng.below(2) == 0 {
+            (to_x, from_y)
+        } else {
+            (from_x, to_y)
+        };
+        for cell in line(from.centre(), corner).chain(line(corner, to.centre())) {
+            set(&mut cells, cell, b'3');
+        }
+    }
+    set(&mut cells, first.centre(), b'1');
+
+    // Open the exits next to the rooms farthest from the entry point
+    let maze = Grid::new(&text(&cells), Topology::Square)?;
+    let entry = maze.node(first.centre());
+    let distances = entry
+        .map(|entry| distances(&maze, entry))
+        .unwrap_or_default();
+    let mut farthest = placed
+        .iter()
+        .filter_map(|&room| {
+            let distance = distances
+                .get(maze.node(room.centre())?)
+                .copied()
+                .flatten()?;
+            Some((distance, room))
+        })
+        .collect::<Vec<_>>();
+    farthest.sort_by_key(|&(distance, _)| Reverse(distance));
+    for &(_, room) in farthest.iter().take(settings.exits) {
+        let border = exit(room, (columns, rows));
+        for cell in line(room.centre(), border) {
+            if cells.get(cell.1).and_then(|row| row.get(cell.0)) == Some(&b'2') {
+                set(&mut cells, cell, b'3');
+            }
+        }
+        set(&mut cells, border, b'4');
+    }
+
+    Ok(text(&cells))
+}
+
+/// Returns the pairs of rooms joined by corridors.
+///
+/// The corridors of a minimum spanning tree, grown from the first room, join every room, and the
+/// shortest of the other connections are added on top of them, `loop_percent` per hundred.
+fn corridors(rooms: &[Room], loop_percent: usize) -> Vec<(Room, Room)> {
+    let mut joined = rooms.iter().take(1).copied().collect::<Vec<_>>();
+    let mut corridors = Vec::new();
+    while let Some((from, to)) = rooms
+        .iter()
+        .filter(|room| !joined.contains(room))
+        .flat_map(|&to| joined.iter().map(move |&from| (from, to)))
+        .min_by_key(|&(from, to)| from.distance(to))
+    {
+        corridors.push((from, to));
+        joined.push(to);
+    }
+
+    let extra = (corridors.len() * loop_percent).div_ceil(100);
+    let mut others = rooms
+        .iter()
+        .enumerate()
+        .flat_map(|(index, &from)| rooms.iter().skip(index + 1).map(move |&to| (from, to)))
+        .filter(|&(from, to)| !corridors.contains(&(from, to)) && !corridors.contains(&(to, from)))
+        .collect::<Vec<_>>();
+    others.sort_by_key(|&(from, to)| from.distance(to));
+    corridors.extend(others.into_iter().take(extra));
+    corridors
+}
+
+/// Returns the cell of the border closest to the given room, straight out of its middle.
+const fn exit(room: Room, (columns, rows): (usize, usize)) -> (usize, usize) {
+    let (x, y) = room.centre();
+    let west = room.x;
+    let east = columns - room.x - room.width;
+    let north = room.y;
+    let south = rows - room.y - room.height;
+
+    if west <= east && west <= north && west <= south {
+        (0, y)
+    } else if east <= north && east <= south {
+        (columns - 1, y)
+    } else if north <= south {
+        (x, 0)
+    } else {
+        (x, rows - 1)
+    }
+}
+
+/// Returns the cells of the straight line between the given `(column, row)` coordinates, both
+/// included, which must share a column or a row.
+fn line(from: (usize, usize), to: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    (from.0.min(to.0)..=from.0.max(to.0))
+        .flat_map(move |x| (from.1.min(to.1)..=from.1.max(to.1)).map(move |y| (x, y)))
+}
+
+/// Sets the cell at the given `(column, row)` coordinates of a map being laid out.
+fn set(cells: &mut [Vec<u8>], (x, y): (usize, usize), cell: u8) {
+    if let Some(slot) = cells.get_mut(y).and_then(|row| row.get_mut(x)) {
+        *slot = cell;
+    }
+}
+
+/// Returns the rows of a map being laid out.
+fn text(cells: &[Vec<u8>]) -> Vec<String> {
+    cells
+        .iter()
+        .map(|row| row.iter().copied().map(char::from).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file_loader, pathfinding};
+
+    #[test]
+    fn test_rooms() {
+        let room = Room {
+            x: 1,
+            y: 1,
+            width: 3,
+            height: 2,
+        };
+        let beside = Room { x: 4, ..room };
+        let apart = Room { x: 5, ..room };
+
+        assert_eq!(room.centre(), (2, 2));
+        assert!(room.touches(room));
+        assert!(room.touches(beside));
+        assert!(!room.touches(apart));
+        assert_eq!(room.distance(apart), 4);
+        assert_eq!(exit(apart, (12, 9)), (6, 0));
+        assert_eq!(exit(room, (12, 9)), (0, 2));
+    }
+
+    #[test]
+    fn test_carve_dungeon() {
+        let settings = DungeonSettings {
+            rooms: 8,
+            loop_percent: 50,
+            exits: 2,
+            ..DungeonSettings::default()
+        };
+
+        for seed in 0..20 {
+            let rows = carve(&settings, (41, 25), seed).expect("the dungeon should be laid out");
+            assert!(file_loader::parse_file_contents(&rows.join("\n")));
+            assert_eq!(
+                rows,
+                carve(&settings, (41, 25), seed).expect("the dungeon should be laid out")
+            );
+
+            // Every room and corridor can be reached from the entry point, and so can both exits
+            let grid = Grid::new(&rows, Topology::Square).expect("the rows should form a grid");
+            let open = grid.cells().filter(|&(_, cell)| cell.cost().is_some());
+            assert_eq!(
+                pathfinding::reachable(&grid, &grid.entries()).len(),
+                open.count()
+            );
+            assert_eq!(grid.exits().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_corridors() {
+        let room = Room {
+            x: 1,
+            y: 1,
+            width: 2,
+            height: 2,
+        };
+        let rooms = [room, Room { x: 5, ..room }, Room { y: 5, ..room }];
+
+        assert_eq!(
+            corridors(&rooms, 0),
+            vec![(room, Room { x: 5, ..room }), (room, Room { y: 5, ..room })]
+        );
+        assert_eq!(corridors(&rooms, 25).len(), 3);
+        assert!(corridors(&rooms[..1], 100).is_empty());
+    }
+
+    #[test]
+    fn test_carve_dungeon_errors() {
+        let settings = DungeonSettings::default();
+
+        assert!(carve(
+            &DungeonSettings {
+                rooms: 0,
+                ..settings
+            },
+            (31, 21),
+            1
+        )
+        .is_err());
+        assert!(carve(
+            &DungeonSettings {
+                exits: 0,
+                ..settings
+            },
+            (31, 21),
+            1
+        )
+        .is_err());
+        assert!(carve(&settings, (4, 21), 1).is_err());
+        assert!(carve(
+            &DungeonSettings {
+                min_room: (8, 3),
+                ..settings
+            },
+            (31, 21),
+            1
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,778 @@
+//! Maze topology.
+//!
+//! This module turns the rows of characters of a map into a [`Grid`] of typed [`Cell`]s, built once
+//! per maze. Every cell is a node identified by its index in reading order, and the moves out of a
+//! node, arrows and portals included, are answered in constant time, so that solvers, validators
+//! and renderers don't need to index into the rows of the map themselves.
+//!
+//! The rows are read according to the [`Topology`] of the maze. Square cells have four neighbours,
+//! while hexagonal cells have six, every odd row being shifted right by half a cell, and triangular
+//! cells three, pointing up and down in turn.
+//!
+//! Crossings of weave mazes stand for two nodes: their bridge keeps the identifier of the cell,
+//! while the tunnel beneath it comes after every cell, at the identifier of the cell plus the
+//! number of cells.
+//!
+//! Multi-level mazes have their floors laid side by side, from the ground floor on the left, so
+//! that every cell keeps a `(column, row)` pair of coordinates. Moves never cross the edge of a
+//! floor, and the only way to another floor is through its stairs.
+
+use color_eyre::eyre::{ensure, eyre, Result};
+
+use crate::{
+    map::Map,
+    oneway, portal, puzzle, stairs,
+    terrain::{self, Terrain},
+    weave,
+};
+
+/// Identifier of a node of the maze graph, the index of its cell in reading order, or that index
+/// plus the number of cells for the tunnel beneath a crossing.
+pub(crate) type NodeId = usize;
+
+/// Moves to the four neighbouring cells (north, south, east and west), in the order solvers explore
+/// them in.
+pub(crate) const DIRECTIONS: [(isize, isize); 4] = [(0, -1), (0, 1), (1, 0), (-1, 0)];
+
+/// Moves to the six neighbouring cells of a hexagonal cell on an even row (north-west, north-east,
+/// south-west, south-east, east and west).
+const EVEN_HEX_STEPS: [(isize, isize); 6] = [(-1, -1), (0, -1), (-1, 1), (0, 1), (1, 0), (-1, 0)];
+
+/// Moves to the six neighbouring cells of a hexagonal cell on an odd row, which is shifted right by
+/// half a cell.
+const ODD_HEX_STEPS: [(isize, isize); 6] = [(0, -1), (1, -1), (0, 1), (1, 1), (1, 0), (-1, 0)];
+
+/// Moves to the three neighbouring cells of a triangle pointing up (south, east and west).
+const UP_TRIANGLE_STEPS: [(isize, isize); 3] = [(0, 1), (1, 0), (-1, 0)];
+
+/// Moves to the three neighbouring cells of a triangle pointing down (north, east and west).
+const DOWN_TRIANGLE_STEPS: [(isize, isize); 3] = [(0, -1), (1, 0), (-1, 0)];
+
+/// Shape of the cells of a maze, which decides the neighbours of every cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd)]
+pub enum Topology {
+    /// Square cells with four neighbours.
+    #[default]
+    Square,
+    /// Hexagonal cells with six neighbours, every odd row being shifted right by half a cell.
+    Hex,
+    /// Triangular cells with three neighbours, the cells whose coordinates add up to an even number
+    /// pointing up and the other ones pointing down.
+    Triangle,
+}
+
+impl Topology {
+    /// Every topology, in the order they're listed in.
+    pub(crate) const ALL: [Self; 3] = [Self::Square, Self::Hex, Self::Triangle];
+
+    /// Returns the name of this topology in the map format.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Square => "square",
+            Self::Hex => "hex",
+            Self::Triangle => "triangle",
+        }
+    }
+
+    /// Returns the topology with the given name in the map format, if any.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|topology| topology.name() == name)
+    }
+
+    /// Returns the moves from the cell at the given `(column, row)` coordinates to its neighbours,
+    /// in the order solvers explore them in.
+    pub(crate) const fn steps(self, (x, y): (usize, usize)) -> &'static [(isize, isize)] {
+        match self {
+            Self::Square => &DIRECTIONS,
+            Self::Hex if y % 2 == 0 => &EVEN_HEX_STEPS,
+            Self::Hex => &ODD_HEX_STEPS,
+            Self::Triangle if (x + y) % 2 == 0 => &UP_TRIANGLE_STEPS,
+            Self::Triangle => &DOWN_TRIANGLE_STEPS,
+        }
+    }
+
+    /// Returns the number of moves between the given cells if no wall stood in the way, which never
+    /// exceeds the number of moves actually needed.
+    pub(crate) fn distance(self, from: (usize, usize), to: (usize, usize)) -> usize {
+        let rows = from.1.abs_diff(to.1);
+        match self {
+            // Every triangle move changes a single coordinate by one, like square moves do
+            Self::Square | Self::Triangle => from.0.abs_diff(to.0).saturating_add(rows),
+            Self::Hex => {
+                // Moving along a row only takes the columns not already covered diagonally, from
+                // the half-cell positions of both cells
+                let double = |(x, y): (usize, usize)| x.saturating_mul(2).saturating_add(y % 2);
+                let columns = double(from).abs_diff(double(to));
+                rows.saturating_add(columns.saturating_sub(rows) / 2)
+            }
+        }
+    }
+
+    /// Returns the number of horizontal canvas dots a cell spans, so that the cells of shifted rows
+    /// still land on dots.
+    pub(crate) const fn dots_per_column(self) -> usize {
+        match self {
+            Self::Hex => 2,
+            Self::Square | Self::Triangle => 1,
+        }
+    }
+
+    /// Returns how far right the cells of the given row are shifted, in half cells.
+    pub(crate) const fn shift(self, row: usize) -> u8 {
+        match self {
+            Self::Hex if row % 2 == 1 => 1,
+            Self::Square | Self::Hex | Self::Triangle => 0,
+        }
+    }
+}
+
+/// Content of a maze cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Cell {
+    /// Entry point, marked with '1'.
+    Entry,
+    /// Wall, marked with '2'.
+    Wall,
+    /// Open path, marked with '3'.
+    Open,
+    /// Exit, marked with '4'.
+    Exit,
+    /// Terrain costlier to cross than an open path, marked with '5' to '9'.
+    Terrain(&'static Terrain),
+    /// Key with the given index, marked with 'a' to 'e'.
+    Key(u8),
+    /// Door opened by the key with the given index, marked with 'A' to 'E'.
+    Door(u8),
+    /// Portal of the pair with the given index, marked with 'p' to 'u'.
+    Portal(u8),
+    /// One-way cell only left with the given move, marked with '^', 'v', '<' or '>'.
+    Arrow((isize, isize)),
+    /// Stairs climbing the given number of floors, marked with '+' going up and '-' going down.
+    Stairs(isize),
+    /// Crossing of a corridor running east to west over one running north to south, marked with
+    /// '#'.
+    Crossing,
+}
+
+impl Cell {
+    /// Returns the cell marked by the given character of the map format, if any.
+    pub(crate) fn parse(byte: u8) -> Option<Self> {
+        match byte {
+            b'1' => Some(Self::Entry),
+            b'2' => Some(Self::Wall),
+            b'3' => Some(Self::Open),
+            b'4' => Some(Self::Exit),
+            weave::CROSSING => Some(Self::Crossing),
+            _ => terrain::terrain(byte)
+                .map(Self::Terrain)
+                .or_else(|| puzzle::key(byte).map(Self::Key))
+                .or_else(|| puzzle::door(byte).map(Self::Door))
+                .or_else(|| portal::portal(byte).map(Self::Portal))
+                .or_else(|| oneway::direction(byte).map(Self::Arrow))
+                .or_else(|| stairs::levels(byte).map(Self::Stairs)),
+        }
+    }
+
+    /// Returns the cost of stepping onto this cell, or `None` if it can't be walked through.
+    ///
+    /// Entry points, open paths, exits, keys, portals, arrows, stairs and crossings cost a single
+    /// unit, so that the cost of a route through a maze without terrain is its number of moves.
+    /// Doors can't be walked through here, since opening them depends on the keys held, which the
+    /// searches over keys and positions check on their own.
+    pub(crate) const fn cost(self) -> Option<u32> {
+        match self {
+            Self::Wall | Self::Door(_) => None,
+            Self::Terrain(terrain) => Some(terrain.cost),
+            Self::Entry
+            | Self::Open
+            | Self::Exit
+            | Self::Key(_)
+            | Self::Portal(_)
+            | Self::Arrow(_)
+            | Self::Stairs(_)
+            | Self::Crossing => Some(1),
+        }
+    }
+}
+
+/// Maze as a graph of cells.
+///
+/// This structure holds the cells of a maze in reading order, its floors laid side by side, along
+/// with the other end of every portal, so that moving from a cell to any of the cells it leads to
+/// takes constant time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Grid {
+    /// Number of columns of the maze, every floor included.
+    width: usize,
+    /// Number of rows of the maze.
+    height: usize,
+    /// Shape of the cells of the maze.
+    topology: Topology,
+    /// Number of floors of the maze.
+    floors: usize,
+    /// Cells of the maze, row by row.
+    cells: Vec<Cell>,
+    /// Other end of the portal on every cell, if it holds one whose pair is complete.
+    twins: Vec<Option<NodeId>>,
+}
+
+impl Grid {
+    /// Builds the grid of a map, with the topology given in its header.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the rows of the map don't form a grid, as with
+    /// [`Grid::new`].
+    pub(crate) fn of(map: &Map) -> Result<Self> {
+        Self::new(&map.data, map.metadata.topology.unwrap_or_default())
+    }
+
+    /// Builds the grid of a maze with the given topology from the rows of its map.
+    ///
+    /// The floors of a multi-level maze are separated by [`stairs::FLOOR_DELIMITER`] lines.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the floors aren't all as high as the first one, if the
+    /// rows aren't all as long as the first one, or if any of them holds a character that isn't
+    /// part of the map format.
+    pub(crate) fn new<Row: AsRef<str>>(rows: &[Row], topology: Topology) -> Result<Self> {
+        let floors = stairs::floors(rows).collect::<Vec<_>>();
+        let height = floors.first().map_or(0, |floor| floor.len());
+        for (index, floor) in floors.iter().enumerate() {
+            ensure!(
+                floor.len() == height,
+                "floor {index} is {} rows high instead of {height}",
+                floor.len()
+            );
+        }
+
+        let floor_width = rows.first().map_or(0, |row| row.as_ref().len());
+        let width = floor_width.saturating_mul(floors.len());
+        let mut cells = Vec::with_capacity(width.saturating_mul(height));
+        for row_index in 0..height {
+            for (floor_index, floor) in floors.iter().enumerate() {
+                // Count the rows of the floors below and their delimiters, as laid out in the map
+                let index = floor_index * (height + 1) + row_index;
+                let row = floor.get(row_index).map_or("", AsRef::as_ref);
+                ensure!(
+                    row.len() == floor_width,
+                    "row {index} is {} cells long instead of {floor_width}",
+                    row.len()
+                );
+                for byte in row.bytes() {
+                    cells.push(Cell::parse(byte).ok_or_else(|| {
+                        eyre!(
+                            "row {index} holds {:?}, which isn't a maze cell",
+                            char::from(byte)
+                        )
+                    })?);
+                }
+            }
+        }
+
+        let mut twins = vec![None; cells.len()];
+        for (node, cell) in cells.iter().enumerate() {
+            if let Cell::Portal(_) = cell {
+                let twin = cells
+                    .iter()
+                    .enumerate()
+                    .position(|(other, candidate)| other != node && candidate == cell);
+                if let Some(slot) = twins.get_mut(node) {
+                    *slot = twin;
+                }
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            topology,
+            floors: floors.len(),
+            cells,
+            twins,
+        })
+    }
+
+    /// Builds the grid of a single floor of the maze, to draw it on its own.
+    ///
+    /// The stairs of the floor lead nowhere in it, and neither do its portals.
+    pub(crate) fn floor(&self, floor: usize) -> Self {
+        let cells = self
+            .cells()
+            .filter(|&(position, _)| self.floor_of(position) == floor)
+            .map(|(_, cell)| cell)
+            .collect::<Vec<_>>();
+
+        Self {
+            width: self.floor_width(),
+            height: self.height,
+            topology: self.topology,
+            floors: 1,
+            twins: vec![None; cells.len()],
+            cells,
+        }
+    }
+
+    /// Returns the number of columns of the maze, every floor included.
+    pub(crate) const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of floors of the maze.
+    pub(crate) const fn floors(&self) -> usize {
+        self.floors
+    }
+
+    /// Returns the number of columns of every floor of the maze.
+    pub(crate) const fn floor_width(&self) -> usize {
+        match self.width.checked_div(self.floors) {
+            Some(width) => width,
+            None => self.width,
+        }
+    }
+
+    /// Returns the floor the given `(column, row)` coordinates lie on, the ground floor being 0.
+    pub(crate) const fn floor_of(&self, (x, _): (usize, usize)) -> usize {
+        match x.checked_div(self.floor_width()) {
+            Some(floor) => floor,
+            None => 0,
+        }
+    }
+
+    /// Returns the `(column, row)` coordinates of the given ones within their floor.
+    pub(crate) const fn local(&self, (x, y): (usize, usize)) -> (usize, usize) {
+        match x.checked_rem(self.floor_width()) {
+            Some(column) => (column, y),
+            None => (x, y),
+        }
+    }
+
+    /// Returns the coordinates of the cell at the given `(column, row)` coordinates within the
+    /// given floor, as the reverse of [`Grid::local`].
+    pub(crate) const fn on_floor(&self, floor: usize, (x, y): (usize, usize)) -> (usize, usize) {
+        (floor * self.floor_width() + x, y)
+    }
+
+    /// Returns where the given coordinates lie in the grid built by [`Grid::floor`] for the given
+    /// floor, if they lie on it, or the coordinates themselves if no floor is given.
+    pub(crate) fn project(
+        &self,
+        floor: Option<usize>,
+        position: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        match floor {
+            Some(floor) => (self.floor_of(position) == floor).then(|| self.local(position)),
+            None => Some(position),
+        }
+    }
+
+    /// Returns the number of moves between the given `(column, row)` coordinates if no wall stood
+    /// in the way, as counted by [`Topology::distance`] within a floor, plus one move per floor
+    /// climbed, which never exceeds the number of moves actually needed.
+    pub(crate) fn distance(&self, from: (usize, usize), to: (usize, usize)) -> usize {
+        self.topology
+            .distance(self.local(from), self.local(to))
+            .saturating_add(self.floor_of(from).abs_diff(self.floor_of(to)))
+    }
+
+    /// Returns the number of rows of the maze.
+    pub(crate) const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the shape of the cells of the maze.
+    pub(crate) const fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns the node at the given `(column, row)` coordinates, if they lie within the maze.
+    pub(crate) const fn node(&self, (x, y): (usize, usize)) -> Option<NodeId> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the index of the cell of the given node, which differs from the node for tunnels.
+    const fn index(&self, node: NodeId) -> usize {
+        match node.checked_sub(self.width * self.height) {
+            Some(index) => index,
+            None => node,
+        }
+    }
+
+    /// Returns whether the given node is the tunnel beneath a crossing.
+    const fn is_tunnel(&self, node: NodeId) -> bool {
+        node >= self.width * self.height
+    }
+
+    /// Returns the number of nodes of the maze graph, tunnels included, which are all below it.
+    pub(crate) const fn node_count(&self) -> usize {
+        self.width * self.height * 2
+    }
+
+    /// Returns the tunnel beneath the crossing on the given node, if it holds one.
+    pub(crate) fn tunnel(&self, node: NodeId) -> Option<NodeId> {
+        (node < self.cells.len() && self.cell(node) == Some(Cell::Crossing))
+            .then(|| node + self.cells.len())
+    }
+
+    /// Returns the `(column, row)` coordinates of the given node.
+    pub(crate) const fn position(&self, node: NodeId) -> (usize, usize) {
+        let node = self.index(node);
+        match (node.checked_rem(self.width), node.checked_div(self.width)) {
+            (Some(x), Some(y)) => (x, y),
+            _ => (0, 0),
+        }
+    }
+
+    /// Returns the content of the given node, if it lies within the maze.
+    pub(crate) fn cell(&self, node: NodeId) -> Option<Cell> {
+        self.cells.get(self.index(node)).copied()
+    }
+
+    /// Returns the content of the cell at the given `(column, row)` coordinates, if any.
+    pub(crate) fn at(&self, position: (usize, usize)) -> Option<Cell> {
+        self.cell(self.node(position)?)
+    }
+
+    /// Iterates over the cells of the maze in reading order, along with their coordinates.
+    pub(crate) fn cells(&self) -> impl Iterator<Item = ((usize, usize), Cell)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(node, &cell)| (self.position(node), cell))
+    }
+
+    /// Returns the nodes holding the given kind of cell, in reading order.
+    fn nodes_where(&self, wanted: fn(Cell) -> bool) -> Vec<NodeId> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|&(_, &cell)| wanted(cell))
+            .map(|(node, _)| node)
+            .collect()
+    }
+
+    /// Returns every entry point of the maze, in reading order.
+    pub(crate) fn entries(&self) -> Vec<NodeId> {
+        self.nodes_where(|cell| cell == Cell::Entry)
+    }
+
+    /// Returns every exit of the maze, in reading order.
+    pub(crate) fn exits(&self) -> Vec<NodeId> {
+        self.nodes_where(|cell| cell == Cell::Exit)
+    }
+
+    /// Returns every portal of the maze, in reading order.
+    pub(crate) fn portals(&self) -> Vec<NodeId> {
+        self.nodes_where(|cell| matches!(cell, Cell::Portal(_)))
+    }
+
+    /// Returns whether the maze holds any door, and thus needs keys to be kept track of.
+    pub(crate) fn has_doors(&self) -> bool {
+        self.cells.iter().any(|cell| matches!(cell, Cell::Door(_)))
+    }
+
+    /// Returns the node at the other end of the portal on the given node, if it holds one.
+    pub(crate) fn twin(&self, node: NodeId) -> Option<NodeId> {
+        self.twins.get(node).copied().flatten()
+    }
+
+    /// Returns the node the stairs on the given node lead to, if it holds stairs landing on stairs
+    /// leading back.
+    pub(crate) fn landing(&self, node: NodeId) -> Option<NodeId> {
+        let Some(Cell::Stairs(levels)) = self.cell(node) else {
+            return None;
+        };
+        let position = self.position(node);
+        let floor = self
+            .floor_of(position)
+            .checked_add_signed(levels)
+            .filter(|&floor| floor < self.floors)?;
+        let landing = self.node(self.on_floor(floor, self.local(position)))?;
+
+        (self.cell(landing) == Some(Cell::Stairs(-levels))).then_some(landing)
+    }
+
+    /// Returns the node next to the given one with the given move, if it lies on the same floor.
+    pub(crate) fn neighbour(&self, node: NodeId, (dx, dy): (isize, isize)) -> Option<NodeId> {
+        let position = self.position(node);
+        let (x, y) = self.local(position);
+        let x = x
+            .checked_add_signed(dx)
+            .filter(|&x| x < self.floor_width())?;
+        self.node(self.on_floor(self.floor_of(position), (x, y.checked_add_signed(dy)?)))
+    }
+
+    /// Returns the nodes next to the given one on its floor, whatever they hold, in the order of
+    /// [`Topology::steps`].
+    pub(crate) fn neighbours(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.topology
+            .steps(self.local(self.position(node)))
+            .iter()
+            .filter_map(move |&step| self.neighbour(node, step))
+    }
+
+    /// Returns the nodes that can be moved to from the given one, regardless of keys.
+    ///
+    /// The neighbours that aren't walls come first, in the order of [`Topology::steps`], an arrow
+    /// only leading to the cell it points to and a crossing only straight through, sideways over
+    /// its bridge and up or down through its tunnel, the way it was entered. The other end of the
+    /// portal on the given node, if any, comes next, and the landing of its stairs last. Doors are
+    /// moved to like any other cell, the searches checking on their own whether they can be opened.
+    pub(crate) fn moves(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let here = self.cell(node);
+        self.topology
+            .steps(self.local(self.position(node)))
+            .iter()
+            .copied()
+            .filter(move |&step| match here {
+                Some(Cell::Arrow(direction)) => direction == step,
+                Some(Cell::Crossing) => weave::goes_under(step) == self.is_tunnel(node),
+                _ => true,
+            })
+            .filter_map(move |step| {
+                let next = self.neighbour(node, step)?;
+                match self.tunnel(next) {
+                    Some(tunnel) if weave::goes_under(step) => Some(tunnel),
+                    _ => Some(next),
+                }
+            })
+            .filter(|&next| self.cell(next).is_some_and(|cell| cell != Cell::Wall))
+            .chain(self.twin(node))
+            .chain(self.landing(node))
+    }
+
+    /// Returns whether moving between the given `(column, row)` coordinates is a jump through a
+    /// portal or up or down stairs rather than a step to a neighbouring cell or staying put.
+    pub(crate) fn is_jump(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        let (local_from, local_to) = (self.local(from), self.local(to));
+        from != to
+            && (self.floor_of(from) != self.floor_of(to)
+                || !self.topology.steps(local_from).iter().any(|&(dx, dy)| {
+                    local_from.0.checked_add_signed(dx) == Some(local_to.0)
+                        && local_from.1.checked_add_signed(dy) == Some(local_to.1)
+                }))
+    }
+
+    /// Splits a path into the stretches walked between jumps through portals and stairs.
+    pub(crate) fn stretches<'path>(
+        &self,
+        path: &'path [(usize, usize)],
+    ) -> Vec<&'path [(usize, usize)]> {
+        path.chunk_by(|&from, &to| !self.is_jump(from, to))
+            .collect()
+    }
+
+    /// Returns the total cost of following the given path of `(column, row)` coordinates.
+    ///
+    /// The first cell of the path is where the walk starts from, so only the cells stepped onto
+    /// after it are paid for. Doors are assumed to have been opened and cost a single unit, while
+    /// other cells that can't be walked through make the path invalid and yield `None`.
+    pub(crate) fn path_cost(&self, path: &[(usize, usize)]) -> Option<u32> {
+        path.iter().skip(1).try_fold(0_u32, |total, &position| {
+            let step = match self.at(position)? {
+                Cell::Door(_) => 1,
+                cell => cell.cost()?,
+            };
+            total.checked_add(step)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cells() {
+        assert_eq!(Cell::parse(b'1'), Some(Cell::Entry));
+        assert_eq!(Cell::parse(b'c'), Some(Cell::Key(2)));
+        assert_eq!(Cell::parse(b'C'), Some(Cell::Door(2)));
+        assert_eq!(Cell::parse(b'q'), Some(Cell::Portal(1)));
+        assert_eq!(Cell::parse(b'v'), Some(Cell::Arrow((0, 1))));
+        assert_eq!(Cell::parse(b'x'), None);
+        assert_eq!(Cell::parse(b'7').and_then(Cell::cost), Some(5));
+        assert_eq!(Cell::Door(0).cost(), None);
+        assert_eq!(Cell::Wall.cost(), None);
+    }
+
+    #[test]
+    fn test_grid_nodes() {
+        let grid =
+            Grid::new(&["2222", "2134", "2222"], Topology::Square).expect("the rows form a grid");
+
+        assert_eq!((grid.width(), grid.height()), (4, 3));
+        assert_eq!(grid.node((1, 1)), Some(5));
+        assert_eq!(grid.node((4, 1)), None);
+        assert_eq!(grid.position(7), (3, 1));
+        assert_eq!(grid.at((3, 1)), Some(Cell::Exit));
+        assert_eq!(grid.entries(), vec![5]);
+        assert_eq!(grid.exits(), vec![7]);
+        assert!(Grid::new(&["2222", "213"], Topology::Square).is_err());
+        assert!(Grid::new(&["2x22"], Topology::Square).is_err());
+        assert_eq!(
+            Grid::new::<&str>(&[], Topology::Square)
+                .map(|grid| grid.width())
+                .ok(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_grid_moves() {
+        let grid = Grid::new(&["22222", "21p32", "2>2p4", "22222"], Topology::Square)
+            .expect("the rows form a grid");
+        let node = |position| grid.node(position).expect("the cell lies in the maze");
+
+        assert_eq!(
+            grid.moves(node((1, 1))).collect::<Vec<_>>(),
+            vec![node((1, 2)), node((2, 1))]
+        );
+        assert_eq!(
+            grid.moves(node((2, 1))).collect::<Vec<_>>(),
+            vec![node((3, 1)), node((1, 1)), node((3, 2))]
+        );
+        assert_eq!(grid.moves(node((1, 2))).collect::<Vec<_>>(), vec![]);
+        assert_eq!(grid.twin(node((3, 2))), Some(node((2, 1))));
+    }
+
+    #[test]
+    fn test_topology_steps() {
+        let hex = Grid::new(&["3333"; 4], Topology::Hex).expect("the rows form a grid");
+        let triangle = Grid::new(&["3333"; 3], Topology::Triangle).expect("the rows form a grid");
+        let neighbours = |grid: &Grid, position| {
+            grid.neighbours(grid.node(position).expect("the cell lies in the maze"))
+                .map(|node| grid.position(node))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            neighbours(&hex, (2, 2)),
+            vec![(1, 1), (2, 1), (1, 3), (2, 3), (3, 2), (1, 2)]
+        );
+        assert_eq!(
+            neighbours(&hex, (0, 1)),
+            vec![(0, 0), (1, 0), (0, 2), (1, 2), (1, 1)]
+        );
+        assert_eq!(neighbours(&triangle, (1, 1)), vec![(1, 2), (2, 1), (0, 1)]);
+        assert_eq!(neighbours(&triangle, (2, 1)), vec![(2, 0), (3, 1), (1, 1)]);
+        assert_eq!(Topology::from_name("hex"), Some(Topology::Hex));
+        assert_eq!(Topology::from_name("round"), None);
+    }
+
+    #[test]
+    fn test_topology_distance() {
+        assert_eq!(Topology::Square.distance((0, 0), (2, 2)), 4);
+        assert_eq!(Topology::Hex.distance((0, 0), (2, 2)), 3);
+        assert_eq!(Topology::Hex.distance((0, 0), (0, 2)), 2);
+        assert_eq!(Topology::Hex.distance((3, 1), (0, 1)), 3);
+        assert_eq!(Topology::Triangle.distance((1, 1), (2, 3)), 3);
+    }
+
+    #[test]
+    fn test_stretches() {
+        let square = Grid::new(&["3333"; 6], Topology::Square).expect("the rows form a grid");
+        let hex = Grid::new(&["3333"; 6], Topology::Hex).expect("the rows form a grid");
+        let path = [(1, 1), (2, 1), (3, 4), (3, 5)];
+
+        assert!(square.is_jump((2, 1), (3, 4)));
+        assert!(!square.is_jump((3, 4), (3, 5)));
+        assert!(!square.is_jump((3, 5), (3, 5)));
+        assert_eq!(
+            square.stretches(&path),
+            vec![[(1, 1), (2, 1)].as_slice(), [(3, 4), (3, 5)].as_slice()]
+        );
+        // Diagonal moves between hexagonal cells are steps, not jumps
+        assert!(square.is_jump((2, 2), (1, 1)));
+        assert!(!hex.is_jump((2, 2), (1, 1)));
+    }
+
+    #[test]
+    fn test_grid_floors() {
+        let grid = Grid::new(
+            &["2222", "21+2", "2222", "===", "2222", "23-2", "2422"],
+            Topology::Square,
+        )
+        .expect("the floors form a grid");
+        let node = |position| grid.node(position).expect("the cell lies in the maze");
+
+        assert_eq!((grid.width(), grid.height()), (8, 3));
+        assert_eq!((grid.floors(), grid.floor_width()), (2, 4));
+        assert_eq!(grid.at((6, 1)), Some(Cell::Stairs(-1)));
+        assert_eq!(grid.floor_of((6, 1)), 1);
+        assert_eq!(grid.local((6, 1)), (2, 1));
+        assert_eq!(grid.on_floor(1, (2, 1)), (6, 1));
+
+        // Stairs lead to the floor above and back, and moves never cross the edge of a floor
+        assert_eq!(
+            grid.moves(node((2, 1))).collect::<Vec<_>>(),
+            vec![node((1, 1)), node((6, 1))]
+        );
+        assert_eq!(
+            grid.moves(node((6, 1))).collect::<Vec<_>>(),
+            vec![node((5, 1)), node((2, 1))]
+        );
+        assert_eq!(grid.landing(node((1, 1))), None);
+        assert_eq!(grid.neighbour(node((3, 1)), (1, 0)), None);
+        assert!(grid.is_jump((2, 1), (6, 1)));
+        assert!(grid.is_jump((3, 1), (4, 1)));
+        assert!(!grid.is_jump((5, 1), (6, 1)));
+        assert_eq!(grid.distance((1, 1), (5, 2)), 2);
+
+        let upstairs = grid.floor(1);
+        assert_eq!((upstairs.width(), upstairs.floors()), (4, 1));
+        assert_eq!(upstairs.at((1, 2)), Some(Cell::Exit));
+        assert_eq!(grid.project(Some(1), (5, 2)), Some((1, 2)));
+        assert_eq!(grid.project(Some(0), (5, 2)), None);
+        assert_eq!(grid.project(None, (5, 2)), Some((5, 2)));
+
+        assert!(Grid::new(&["222", "===", "222", "222"], Topology::Square).is_err());
+        assert!(Grid::new(&["222", "===", "2222"], Topology::Square).is_err());
+    }
+
+    #[test]
+    fn test_grid_crossings() {
+        let grid = Grid::new(
+            &["22222", "22322", "21#34", "22322", "22222"],
+            Topology::Square,
+        )
+        .expect("the rows form a grid");
+        let node = |position| grid.node(position).expect("the cell lies in the maze");
+        let bridge = node((2, 2));
+        let tunnel = grid.tunnel(bridge).expect("the cell holds a crossing");
+
+        assert_eq!(grid.node_count(), 50);
+        assert_eq!(tunnel, 37);
+        assert_eq!(grid.tunnel(node((2, 1))), None);
+        assert_eq!(grid.position(tunnel), (2, 2));
+        assert_eq!(grid.cell(tunnel), Some(Cell::Crossing));
+
+        // Corridors run straight through crossings, sideways over the bridge and up or down below
+        assert_eq!(grid.moves(node((1, 2))).collect::<Vec<_>>(), vec![bridge]);
+        assert_eq!(grid.moves(node((2, 1))).collect::<Vec<_>>(), vec![tunnel]);
+        assert_eq!(
+            grid.moves(bridge).collect::<Vec<_>>(),
+            vec![node((3, 2)), node((1, 2))]
+        );
+        assert_eq!(
+            grid.moves(tunnel).collect::<Vec<_>>(),
+            vec![node((2, 1)), node((2, 3))]
+        );
+    }
+
+    #[test]
+    fn test_path_cost() {
+        let grid =
+            Grid::new(&["2222", "2174", "2222"], Topology::Square).expect("the rows form a grid");
+
+        assert_eq!(grid.path_cost(&[(1, 1), (2, 1), (3, 1)]), Some(6));
+        assert_eq!(grid.path_cost(&[(1, 1)]), Some(0));
+        assert_eq!(grid.path_cost(&[(1, 1), (1, 0)]), None);
+    }
+}
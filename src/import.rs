@@ -0,0 +1,274 @@
+//! Importing of mazes drawn in other formats.
+//!
+//! This module turns bitmaps and ASCII-art grids into labyrinth maps. Each importer reads its
+//! format into a grid of wall and path cells, on which the entry point and the exit are then
+//! placed, either where the user asked or on the openings found along the border of the maze.
+
+mod ascii;
+mod netpbm;
+
+use std::{ffi::OsStr, fs, path::Path};
+
+use color_eyre::eyre::{ensure, eyre, OptionExt as _, Result, WrapErr as _};
+
+use crate::{file_loader, map::Map, time};
+
+/// Settings of a maze import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSettings {
+    /// `(column, row)` cell of the entry point, or `None` to place it inside a border opening.
+    pub entry: Option<(usize, usize)>,
+    /// `(column, row)` cell of the exit, or `None` to place it on the last border opening.
+    pub exit: Option<(usize, usize)>,
+    /// Brightness, in percent of the maximum, below which image pixels are walls.
+    pub threshold: u8,
+    /// Side length of a maze cell in image pixels, or `None` to detect it.
+    pub scale: Option<usize>,
+    /// Characters standing for walls in ASCII art.
+    pub walls: String,
+    /// Characters standing for paths in ASCII art.
+    pub paths: String,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            entry: None,
+            exit: None,
+            threshold: 50,
+            scale: None,
+            walls: "#+-|".to_owned(),
+            paths: " .".to_owned(),
+        }
+    }
+}
+
+/// Grid of cells read by an importer, row by row, where `true` marks a wall.
+type Grid = Vec<Vec<bool>>;
+
+/// Imports the maze in the input file and writes it to the output file as a labyrinth map.
+///
+/// Files with the .pbm, .pgm or .pnm extension are read as images, and any other file as ASCII art.
+///
+/// # Errors
+///
+/// This function returns an error if the input can't be read or parsed, if the entry point or the
+/// exit can't be placed, or if the output isn't a .labmap file or can't be written.
+pub(crate) fn import(input: &Path, output: &Path, settings: &ImportSettings) -> Result<Map> {
+    let file_name = output
+        .file_name()
+        .ok_or_else(|| eyre!("{} isn't a file", output.display()))?;
+    ensure!(
+        file_name.as_encoded_bytes().ends_with(b".labmap"),
+        "{} lacks the .labmap extension",
+        output.display()
+    );
+
+    let is_image = matches!(
+        input.extension().and_then(OsStr::to_str),
+        Some("pbm" | "pgm" | "pnm")
+    );
+    let grid = if is_image {
+        let image =
+            fs::read(input).wrap_err_with(|| format!("failed to read {}", input.display()))?;
+        netpbm::read(&image, settings)
+    } else {
+        let text = fs::read_to_string(input)
+            .wrap_err_with(|| format!("failed to read {}", input.display()))?;
+        ascii::read(&text, settings)
+    }
+    .wrap_err_with(|| format!("failed to import {}", input.display()))?;
+
+    let grid = place_markers(&grid, settings.entry, settings.exit)?.join("\n");
+    ensure!(
+        file_loader::parse_file_contents(&grid),
+        "the imported maze isn't a valid labyrinth map"
+    );
+
+    let mut map = Map::new(file_name.to_owned(), &grid)?;
+    map.metadata.description = input
+        .file_name()
+        .map(|name| format!("Imported from {}", name.to_string_lossy()));
+    map.metadata.created = Some(time::today());
+
+    fs::write(output, map.to_labmap())
+        .wrap_err_with(|| format!("failed to write {}", output.display()))?;
+
+    Ok(map)
+}
+
+/// Converts a grid to the rows of a labyrinth map, placing the entry point and the exit on it.
+///
+/// Without a given exit, the exit is placed on the last opening found going clockwise around the
+/// border from the top left corner, corners excluded. Without a given entry point, the entry point
+/// is placed on the cell just inside the first other opening. Every opening left on the border is
+/// then walled up, as the map format only allows exits there.
+///
+/// # Errors
+///
+/// This function returns an error if the grid is smaller than 3x3 cells, if a given cell is a wall
+/// or isn't where the map format expects it, or if there aren't enough openings to place the
+/// missing markers.
+fn place_markers(
+    grid: &Grid,
+    entry: Option<(usize, usize)>,
+    exit: Option<(usize, usize)>,
+) -> Result<Vec<String>> {
+    let rows = grid.len();
+    let columns = grid.first().map_or(0, Vec::len);
+    ensure!(
+        rows >= 3 && columns >= 3,
+        "the maze must be at least 3x3 cells, found {columns}x{rows}"
+    );
+
+    let is_open = |(column, row): (usize, usize)| {
+        grid.get(row)
+            .and_then(|line| line.get(column))
+            .is_some_and(|&wall| !wall)
+    };
+    let is_edge = |(column, row): (usize, usize)| {
+        column == 0 || row == 0 || column == columns - 1 || row == rows - 1
+    };
+    // Corners only touch other border cells, so they can't lead into the maze
+    let is_corner = |(column, row): (usize, usize)| {
+        (column == 0 || column == columns - 1) && (row == 0 || row == rows - 1)
+    };
+    let openings = border(columns, rows)
+        .into_iter()
+        .filter(|&cell| is_open(cell) && !is_corner(cell))
+        .collect::<Vec<_>>();
+
+    let exit = match exit {
+        Some(cell) => {
+            ensure!(
+                is_edge(cell) && cell.0 < columns && cell.1 < rows,
+                "the exit must be on the border of the maze, found {cell:?}"
+            );
+            ensure!(is_open(cell), "the exit at {cell:?} is a wall");
+            cell
+        }
+        None => *openings
+            .last()
+            .ok_or_eyre("the maze has no opening on its border to place the exit on")?,
+    };
+
+    let entry = if let Some(cell) = entry {
+        ensure!(
+            !is_edge(cell) && cell.0 < columns && cell.1 < rows,
+            "the entry point must be inside the border of the maze, found {cell:?}"
+        );
+        ensure!(is_open(cell), "the entry point at {cell:?} is a wall");
+        cell
+    } else {
+        let &(column, row) = openings
+            .iter()
+            .find(|&&opening| opening != exit)
+            .ok_or_eyre("the maze needs a second opening on its border for the entry point")?;
+        let inside = (column.clamp(1, columns - 2), row.clamp(1, rows - 2));
+        ensure!(
+            is_open(inside),
+            "the cell {inside:?} inside the opening at {:?} is a wall",
+            (column, row)
+        );
+        inside
+    };
+
+    Ok(grid
+        .iter()
+        .enumerate()
+        .map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .map(|(column, &wall)| match (column, row) {
+                    cell if cell == entry => '1',
+                    cell if cell == exit => '4',
+                    cell if wall || is_edge(cell) => '2',
+                    _ => '3',
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Returns the cells on the border of a grid, going clockwise from the top left corner.
+fn border(columns: usize, rows: usize) -> Vec<(usize, usize)> {
+    let top = (0..columns).map(|column| (column, 0));
+    let right = (1..rows).map(|row| (columns - 1, row));
+    let bottom = (0..columns - 1).rev().map(|column| (column, rows - 1));
+    let left = (1..rows - 1).rev().map(|row| (0, row));
+
+    top.chain(right).chain(bottom).chain(left).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    /// Builds a grid from rows of '#' walls and ' ' paths.
+    fn grid(rows: &[&str]) -> Grid {
+        rows.iter()
+            .map(|row| row.chars().map(|cell| cell == '#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_place_markers_on_border_openings() {
+        let grid = grid(&["# ###", "#   #", "### #", "#    ", "#####"]);
+
+        let rows = place_markers(&grid, None, None).expect("the markers should be placed");
+
+        assert_eq!(rows, vec!["22222", "21332", "22232", "23334", "22222"]);
+        assert!(file_loader::parse_file_contents(&rows.join("\n")));
+    }
+
+    #[test]
+    fn test_place_markers_from_arguments() {
+        let grid = grid(&["#####", "#   #", "#   #", "#    ", "#####"]);
+
+        let rows =
+            place_markers(&grid, Some((2, 2)), Some((4, 3))).expect("the markers should be placed");
+
+        assert_eq!(rows, vec!["22222", "23332", "23132", "23334", "22222"]);
+    }
+
+    #[test]
+    fn test_place_markers_errors() {
+        let closed = grid(&["###", "# #", "###"]);
+        let single = grid(&["# #", "# #", "## "]);
+
+        assert!(place_markers(&closed, None, None).is_err());
+        assert!(place_markers(&single, None, None).is_err());
+        assert!(place_markers(&single, Some((1, 1)), Some((0, 0))).is_err());
+        assert!(place_markers(&single, Some((1, 0)), Some((1, 0))).is_err());
+        assert!(place_markers(&grid(&["# ", "  "]), None, None).is_err());
+    }
+
+    #[test]
+    fn test_import_writes_valid_map() {
+        let dir = TempDir::new("import");
+        fs::write(dir.join("maze.txt"), "# ###\n#   #\n### #\n#    \n#####\n")
+            .expect("failed to write maze");
+
+        let map = import(
+            &dir.join("maze.txt"),
+            &dir.join("maze.labmap"),
+            &ImportSettings::default(),
+        );
+        let written = file_loader::load_map(&dir.join("maze.labmap"));
+        let wrong_extension = import(
+            &dir.join("maze.txt"),
+            &dir.join("maze.map"),
+            &ImportSettings::default(),
+        );
+
+        let map = map.expect("the maze should be imported");
+        assert_eq!(map.key, "maze");
+        assert_eq!(written.expect("the imported map should load"), map);
+        assert_eq!(
+            map.metadata.description.as_deref(),
+            Some("Imported from maze.txt")
+        );
+        assert!(wrong_extension.is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! ASCII-art mazes.
+//!
+//! This module reads mazes drawn with text characters, such as `#` for walls and spaces for paths,
+//! where every character stands for a single cell.
+
+use color_eyre::eyre::{bail, ensure, Result};
+
+use super::{Grid, ImportSettings};
+
+/// Reads an ASCII-art maze into a grid.
+///
+/// Blank lines before and after the maze are ignored, and lines shorter than the longest one are
+/// padded with paths, since trailing spaces are often stripped by text editors.
+///
+/// # Errors
+///
+/// This function returns an error if the maze is empty or contains a character that is neither a
+/// wall nor a path character.
+pub(super) fn read(text: &str, settings: &ImportSettings) -> Result<Grid> {
+    let lines = text
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |last| last + 1);
+    let lines = lines.get(..end).unwrap_or_default();
+    ensure!(!lines.is_empty(), "the maze is empty");
+
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut grid = Vec::with_capacity(lines.len());
+    for (row, line) in lines.iter().enumerate() {
+        let mut cells = Vec::with_capacity(width);
+
+        for (column, char) in line.chars().enumerate() {
+            if settings.walls.contains(char) {
+                cells.push(true);
+            } else if settings.paths.contains(char) {
+                cells.push(false);
+            } else {
+                bail!(
+                    "unknown character {char:?} at ({column}, {row}), expected one of the wall \
+                     characters {:?} or the path characters {:?}",
+                    settings.walls,
+                    settings.paths
+                );
+            }
+        }
+
+        cells.resize(width, false);
+        grid.push(cells);
+    }
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_pads_and_trims() {
+        let grid = read("\n+-+\n| .\n+-+\n\n", &ImportSettings::default())
+            .expect("the maze should be read");
+
+        assert_eq!(
+            grid,
+            vec![
+                vec![true, true, true],
+                vec![true, false, false],
+                vec![true, true, true]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_custom_characters() {
+        let settings = ImportSettings {
+            walls: "X".to_owned(),
+            paths: "o".to_owned(),
+            ..ImportSettings::default()
+        };
+
+        let grid = read("XoX\nXXX", &settings).expect("the maze should be read");
+
+        assert_eq!(grid, vec![vec![true, false, true], vec![true, true, true]]);
+        assert!(read("X#X", &settings).is_err());
+        assert!(read("\n\n", &settings).is_err());
+    }
+}
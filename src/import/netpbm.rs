@@ -0,0 +1,347 @@
+//! Netpbm bitmaps of mazes.
+//!
+//! This module reads PBM and PGM images, in both their plain and raw variants, thresholds them into
+//! dark and light pixels and samples one pixel per maze cell, dark pixels being walls.
+
+use color_eyre::eyre::{bail, ensure, eyre, OptionExt as _, Result};
+
+use super::{Grid, ImportSettings};
+
+/// Largest amount of pixels an image may hold.
+const MAX_PIXELS: usize = 1 << 26;
+
+/// Reads a PBM or PGM image of a maze into a grid.
+///
+/// Every maze cell is a square of [`ImportSettings::scale`] pixels, sampled at its centre. Without
+/// a given scale, the largest one that every run of same-coloured pixels is a multiple of is used,
+/// which matches the cells of images drawn on a grid.
+///
+/// # Errors
+///
+/// This function returns an error if the image isn't a valid PBM or PGM image, or if it's smaller
+/// than a single cell.
+pub(super) fn read(image: &[u8], settings: &ImportSettings) -> Result<Grid> {
+    let mut reader = Reader { image, position: 0 };
+
+    let magic = reader.token()?;
+    let width = reader.number("width")?;
+    let height = reader.number("height")?;
+    ensure!(
+        width > 0 && height > 0,
+        "the image must have at least one pixel"
+    );
+    let pixels = width
+        .checked_mul(height)
+        .filter(|&pixels| pixels <= MAX_PIXELS)
+        .ok_or_else(|| eyre!("a {width}x{height} image is too large to import"))?;
+
+    let dark = match magic {
+        b"P1" => (0..pixels)
+            .map(|_| reader.bit())
+            .collect::<Result<Vec<_>>>()?,
+        b"P4" => {
+            reader.raster_start()?;
+            let stride = width.div_ceil(8);
+            let raster = reader.take(stride * height)?;
+
+            raster
+                .chunks_exact(stride)
+                .flat_map(|row| {
+                    (0..width).map(move |column| {
+                        row.get(column / 8)
+                            .is_some_and(|byte| byte & (0x80 >> (column % 8)) != 0)
+                    })
+                })
+                .collect()
+        }
+        b"P2" | b"P5" => {
+            let max = reader.number("maximum value")?;
+            ensure!(
+                (1..=0xffff).contains(&max),
+                "the maximum value must be between 1 and 65535, found {max}"
+            );
+            let threshold = max * usize::from(settings.threshold);
+            let is_dark = |value: usize| value * 100 < threshold;
+
+            if magic == b"P2" {
+                (0..pixels)
+                    .map(|_| {
+                        let value = reader.number("pixel value")?;
+                        ensure!(
+                            value <= max,
+                            "pixel values must not exceed the maximum value {max}, found {value}"
+                        );
+                        Ok(is_dark(value))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            } else {
+                reader.raster_start()?;
+                let sample = if max > 0xff { 2 } else { 1 };
+                let raster = reader.take(pixels * sample)?;
+
+                raster
+                    .chunks_exact(sample)
+                    .map(|bytes| {
+                        is_dark(
+                            bytes
+                                .iter()
+                                .fold(0, |value, &byte| value << 8 | usize::from(byte)),
+                        )
+                    })
+                    .collect()
+            }
+        }
+        _ => bail!(
+            "unsupported image format {:?}, expected a PBM or PGM image",
+            String::from_utf8_lossy(magic)
+        ),
+    };
+
+    let rows = dark.chunks_exact(width).collect::<Vec<_>>();
+    let scale = match settings.scale {
+        Some(scale) => scale,
+        None => detect_scale(&rows, width),
+    };
+    ensure!(
+        scale > 0 && scale <= width && scale <= height,
+        "a {width}x{height} image can't hold cells of {scale} pixels"
+    );
+
+    Ok((0..height / scale)
+        .map(|row| {
+            (0..width / scale)
+                .map(|column| {
+                    rows.get(row * scale + scale / 2)
+                        .and_then(|pixels| pixels.get(column * scale + scale / 2))
+                        .is_some_and(|&dark| dark)
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Returns the greatest common divisor of the lengths of every horizontal and vertical run of
+/// same-coloured pixels.
+fn detect_scale(rows: &[&[bool]], width: usize) -> usize {
+    let mut scale = 0;
+    let mut add_runs = |pixels: &mut dyn Iterator<Item = bool>| {
+        let mut previous = None;
+        let mut run = 0;
+
+        for pixel in pixels {
+            if previous.is_some_and(|previous| previous != pixel) {
+                scale = gcd(scale, run);
+                run = 0;
+            }
+            previous = Some(pixel);
+            run += 1;
+        }
+        scale = gcd(scale, run);
+    };
+
+    for row in rows {
+        add_runs(&mut row.iter().copied());
+    }
+    for column in 0..width {
+        add_runs(&mut rows.iter().filter_map(|row| row.get(column).copied()));
+    }
+
+    scale.max(1)
+}
+
+/// Returns the greatest common divisor of two numbers, where zero divides nothing.
+const fn gcd(mut first: usize, mut second: usize) -> usize {
+    while second != 0 {
+        (first, second) = (second, first % second);
+    }
+
+    first
+}
+
+/// Cursor over the bytes of a Netpbm image.
+struct Reader<'image> {
+    /// Contents of the image file.
+    image: &'image [u8],
+    /// Index of the next byte to read.
+    position: usize,
+}
+
+impl<'image> Reader<'image> {
+    /// Skips whitespace and comments, which run from a '#' to the end of the line.
+    fn skip_blanks(&mut self) {
+        while let Some(&byte) = self.image.get(self.position) {
+            if byte == b'#' {
+                while self
+                    .image
+                    .get(self.position)
+                    .is_some_and(|&byte| byte != b'\n')
+                {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Reads the next whitespace-separated token of the header or of a plain raster.
+    fn token(&mut self) -> Result<&'image [u8]> {
+        self.skip_blanks();
+
+        let start = self.position;
+        while self
+            .image
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'#')
+        {
+            self.position += 1;
+        }
+
+        self.image
+            .get(start..self.position)
+            .filter(|token| !token.is_empty())
+            .ok_or_eyre("the image ends unexpectedly")
+    }
+
+    /// Reads the next token as a decimal number.
+    fn number(&mut self, name: &str) -> Result<usize> {
+        let token = self.token()?;
+
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| {
+                eyre!(
+                    "the {name} must be a number, found {:?}",
+                    String::from_utf8_lossy(token)
+                )
+            })
+    }
+
+    /// Reads the next pixel of a plain PBM raster, where pixels need not be separated.
+    fn bit(&mut self) -> Result<bool> {
+        self.skip_blanks();
+
+        let bit = match self.image.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            Some(&byte) => bail!("expected a 0 or 1 pixel, found {:?}", char::from(byte)),
+            None => bail!("the image ends unexpectedly"),
+        };
+        self.position += 1;
+
+        Ok(bit)
+    }
+
+    /// Skips the single whitespace byte separating the header from a raw raster.
+    fn raster_start(&mut self) -> Result<()> {
+        ensure!(
+            self.image
+                .get(self.position)
+                .is_some_and(u8::is_ascii_whitespace),
+            "expected whitespace before the raster"
+        );
+        self.position += 1;
+
+        Ok(())
+    }
+
+    /// Reads the given amount of raw raster bytes.
+    fn take(&mut self, length: usize) -> Result<&'image [u8]> {
+        let bytes = self
+            .image
+            .get(self.position..self.position + length)
+            .ok_or_eyre("the image ends unexpectedly")?;
+        self.position += length;
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expected grid of the 3x3 test images, a wall with a hole in the middle.
+    fn ring() -> Grid {
+        vec![
+            vec![true, true, true],
+            vec![true, false, true],
+            vec![true, true, true],
+        ]
+    }
+
+    #[test]
+    fn test_read_plain_and_raw_bitmaps() {
+        let settings = ImportSettings::default();
+
+        let plain = read(b"P1\n# a comment\n3 3\n111\n1 0 1\n111\n", &settings);
+        let raw = read(b"P4 3 3\n\xe0\xa0\xe0", &settings);
+
+        assert_eq!(plain.expect("the plain bitmap should be read"), ring());
+        assert_eq!(raw.expect("the raw bitmap should be read"), ring());
+    }
+
+    #[test]
+    fn test_read_graymaps_with_threshold() {
+        let settings = ImportSettings::default();
+
+        let plain = read(b"P2 3 3 10\n0 1 2\n3 9 4\n4 4 4\n", &settings);
+        let raw = read(
+            b"P5 3 3 255\n\x00\x00\x00\x00\xff\x00\x00\x00\x00",
+            &settings,
+        );
+        let strict = read(
+            b"P2 3 3 10\n0 1 2\n3 9 4\n4 4 4\n",
+            &ImportSettings {
+                threshold: 95,
+                scale: Some(1),
+                ..ImportSettings::default()
+            },
+        );
+
+        assert_eq!(plain.expect("the plain graymap should be read"), ring());
+        assert_eq!(raw.expect("the raw graymap should be read"), ring());
+        assert_eq!(
+            strict.expect("the plain graymap should be read"),
+            vec![vec![true; 3]; 3]
+        );
+    }
+
+    #[test]
+    fn test_read_detects_scale() {
+        // The ring drawn with cells of 2x2 pixels
+        let image = b"P1 6 6\n111111\n111111\n110011\n110011\n111111\n111111\n";
+
+        let detected = read(image, &ImportSettings::default());
+        let given = read(
+            image,
+            &ImportSettings {
+                scale: Some(3),
+                ..ImportSettings::default()
+            },
+        );
+
+        assert_eq!(detected.expect("the bitmap should be read"), ring());
+        assert_eq!(
+            given.expect("the bitmap should be read"),
+            vec![vec![true, true], vec![true, true]]
+        );
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let settings = ImportSettings::default();
+
+        assert!(read(b"P3 1 1 255\n0 0 0", &settings).is_err());
+        assert!(read(b"P1 3 3\n111\n1", &settings).is_err());
+        assert!(read(b"P4 3 3\n\xe0", &settings).is_err());
+        assert!(read(b"P1 0 3\n", &settings).is_err());
+        assert!(read(b"P2 1 1 0\n0", &settings).is_err());
+        assert!(read(b"P2 1 1 10\n11", &settings).is_err());
+        assert!(read(b"P2 1 1 10\n18446744073709551615", &settings).is_err());
+        assert!(read(b"P1 1 1\n2", &settings).is_err());
+    }
+}
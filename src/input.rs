@@ -0,0 +1,86 @@
+//! Sources of input events for the event handlers.
+//!
+//! This module contains the trait through which the main loop waits for input events, along with
+//! the implementation reading them from the terminal and an in-memory queue used to script input in
+//! tests.
+
+use std::{collections::VecDeque, time::Duration};
+
+use color_eyre::eyre::Result;
+use ratatui::crossterm::event::{self, Event};
+
+/// Source of input events.
+pub(crate) trait EventSource {
+    /// Waits up to the given amount of time for the next input event.
+    ///
+    /// This function returns `None` if no event arrived before the timeout.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the events can't be read.
+    fn next_event(&mut self, timeout: Duration) -> Result<Option<Event>>;
+}
+
+/// Source reading input events from the terminal through Crossterm.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next_event(&mut self, timeout: Duration) -> Result<Option<Event>> {
+        if event::poll(timeout)? {
+            Ok(Some(event::read()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Source returning input events from an in-memory queue.
+///
+/// This structure returns its events in order without ever waiting, and returns `None` once it has
+/// run out of them.
+#[derive(Debug, Default)]
+pub(crate) struct EventQueue {
+    /// Events pending to be returned, in order.
+    events: VecDeque<Event>,
+}
+
+impl EventQueue {
+    /// Creates a queue returning the given events in order.
+    pub(crate) fn new<I: IntoIterator<Item = Event>>(events: I) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+}
+
+impl EventSource for EventQueue {
+    fn next_event(&mut self, _timeout: Duration) -> Result<Option<Event>> {
+        Ok(self.events.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyCode;
+
+    use super::*;
+
+    #[test]
+    fn test_event_queue_returns_events_in_order() {
+        let mut queue = EventQueue::new([Event::Resize(80, 24), Event::Key(KeyCode::Enter.into())]);
+
+        assert_eq!(
+            queue.next_event(Duration::ZERO).expect("queues never fail"),
+            Some(Event::Resize(80, 24))
+        );
+        assert_eq!(
+            queue.next_event(Duration::ZERO).expect("queues never fail"),
+            Some(Event::Key(KeyCode::Enter.into()))
+        );
+        assert_eq!(
+            queue.next_event(Duration::ZERO).expect("queues never fail"),
+            None
+        );
+    }
+}
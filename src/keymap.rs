@@ -0,0 +1,577 @@
+//! Key bindings mapping terminal key presses to application actions.
+//!
+//! This module contains the actions the user can trigger from the keyboard, the presets shipped
+//! with the game and the parser for the configuration file through which users can override them.
+
+use std::{env, fs, path::PathBuf};
+
+use color_eyre::eyre::{bail, eyre, Result, WrapErr as _};
+use ratatui::crossterm::event::KeyCode;
+
+/// Enumeration of the actions that can be bound to a key.
+///
+/// This enumeration holds every action the event handlers know how to perform. Key presses are
+/// translated into actions through the active [`Keymap`], so handlers never match on raw keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Action {
+    /// Move the cursor up in menus and lists.
+    Up,
+    /// Move the cursor down in menus and lists.
+    Down,
+    /// Select the item under the cursor.
+    Select,
+    /// Return to the previous screen.
+    Back,
+    /// Quit the application.
+    Quit,
+    /// Pan the in-game camera to the left.
+    PanLeft,
+    /// Pan the in-game camera down.
+    PanDown,
+    /// Pan the in-game camera up.
+    PanUp,
+    /// Pan the in-game camera to the right.
+    PanRight,
+    /// Zoom the in-game camera in.
+    ZoomIn,
+    /// Zoom the in-game camera out.
+    ZoomOut,
+    /// Toggle whether the in-game camera follows the solver.
+    ToggleFollow,
+    /// Export the solver animation of the current map as an asciicast.
+    ExportCast,
+    /// Export images of the current map with its solution drawn in.
+    ExportImage,
+    /// Switch the in-game solver to the next search algorithm.
+    CycleSolver,
+    /// Toggle the in-game statistics panel.
+    ToggleStats,
+    /// Show the next floor of a multi-level maze in-game.
+    CycleFloor,
+    /// Open the panel knocking walls out of the in-game maze.
+    Braid,
+    /// Show the help overlay for the current screen.
+    Help,
+}
+
+impl Action {
+    /// Every action, in the order they're listed to the user.
+    pub(crate) const ALL: [Self; 19] = [
+        Self::Up,
+        Self::Down,
+        Self::Select,
+        Self::Back,
+        Self::Quit,
+        Self::PanLeft,
+        Self::PanDown,
+        Self::PanUp,
+        Self::PanRight,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ToggleFollow,
+        Self::ExportCast,
+        Self::ExportImage,
+        Self::CycleSolver,
+        Self::ToggleStats,
+        Self::CycleFloor,
+        Self::Braid,
+        Self::Help,
+    ];
+
+    /// Returns the name of the action as written in the configuration file.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Select => "select",
+            Self::Back => "back",
+            Self::Quit => "quit",
+            Self::PanLeft => "pan_left",
+            Self::PanDown => "pan_down",
+            Self::PanUp => "pan_up",
+            Self::PanRight => "pan_right",
+            Self::ZoomIn => "zoom_in",
+            Self::ZoomOut => "zoom_out",
+            Self::ToggleFollow => "toggle_follow",
+            Self::ExportCast => "export_cast",
+            Self::ExportImage => "export_image",
+            Self::CycleSolver => "cycle_solver",
+            Self::ToggleStats => "toggle_stats",
+            Self::CycleFloor => "cycle_floor",
+            Self::Braid => "braid",
+            Self::Help => "help",
+        }
+    }
+
+    /// Returns the action with the given configuration file name, if any.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// Enumeration of the key binding presets shipped with the game.
+///
+/// Every preset binds the movement actions to a different set of keys. The remaining actions are
+/// bound to the same keys in all presets, along with Enter to select and Esc to go back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Preset {
+    /// Vim-style movement with 'h', 'j', 'k' and 'l'.
+    Vim,
+    /// Movement with the arrow keys.
+    Arrows,
+    /// Movement with 'w', 'a', 's' and 'd'.
+    Wasd,
+}
+
+impl Preset {
+    /// Every preset, in the order their bindings are listed to the user.
+    pub(crate) const ALL: [Self; 3] = [Self::Vim, Self::Arrows, Self::Wasd];
+
+    /// Returns the preset with the given configuration file name, if any.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vim" => Some(Self::Vim),
+            "arrows" => Some(Self::Arrows),
+            "wasd" => Some(Self::Wasd),
+            _ => None,
+        }
+    }
+
+    /// Returns the movement bindings of the preset.
+    const fn movement(self) -> [(KeyCode, Action); 4] {
+        match self {
+            Self::Vim => [
+                (KeyCode::Char('j'), Action::Down),
+                (KeyCode::Char('k'), Action::Up),
+                (KeyCode::Char('l'), Action::Select),
+                (KeyCode::Char('h'), Action::Back),
+            ],
+            Self::Arrows => [
+                (KeyCode::Down, Action::Down),
+                (KeyCode::Up, Action::Up),
+                (KeyCode::Right, Action::Select),
+                (KeyCode::Left, Action::Back),
+            ],
+            Self::Wasd => [
+                (KeyCode::Char('s'), Action::Down),
+                (KeyCode::Char('w'), Action::Up),
+                (KeyCode::Char('d'), Action::Select),
+                (KeyCode::Char('a'), Action::Back),
+            ],
+        }
+    }
+}
+
+/// Bindings shared by every preset.
+const COMMON_BINDINGS: [(KeyCode, Action); 17] = [
+    (KeyCode::Enter, Action::Select),
+    (KeyCode::Esc, Action::Back),
+    (KeyCode::Char('q'), Action::Quit),
+    (KeyCode::Char('H'), Action::PanLeft),
+    (KeyCode::Char('J'), Action::PanDown),
+    (KeyCode::Char('K'), Action::PanUp),
+    (KeyCode::Char('L'), Action::PanRight),
+    (KeyCode::Char('+'), Action::ZoomIn),
+    (KeyCode::Char('-'), Action::ZoomOut),
+    (KeyCode::Char('f'), Action::ToggleFollow),
+    (KeyCode::Char('c'), Action::ExportCast),
+    (KeyCode::Char('p'), Action::ExportImage),
+    (KeyCode::Char('v'), Action::CycleSolver),
+    (KeyCode::Char('t'), Action::ToggleStats),
+    (KeyCode::Char('n'), Action::CycleFloor),
+    (KeyCode::Char('b'), Action::Braid),
+    (KeyCode::Char('?'), Action::Help),
+];
+
+/// Mapping from key presses to actions.
+///
+/// This structure holds the active key bindings as an ordered list, so that the first key bound to
+/// an action is the one shown in the navigation hints. A key is bound to at most one action, but an
+/// action may be bound to any amount of keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Keymap {
+    /// Key bindings in the order they were added.
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_presets(&Preset::ALL)
+    }
+}
+
+impl Keymap {
+    /// Creates a keymap with the bindings of all the given presets.
+    ///
+    /// The movement bindings of the presets come first, in the given order, followed by the
+    /// bindings shared by all of them.
+    pub(crate) fn from_presets(presets: &[Preset]) -> Self {
+        let bindings = presets
+            .iter()
+            .flat_map(|preset| preset.movement())
+            .chain(COMMON_BINDINGS)
+            .collect();
+
+        Self { bindings }
+    }
+
+    /// Returns the action bound to the given key, if any.
+    pub(crate) fn action(&self, key: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find_map(|&(bound, action)| (bound == key).then_some(action))
+    }
+
+    /// Returns the keys bound to the given action, in binding order.
+    pub(crate) fn keys(&self, action: Action) -> impl Iterator<Item = KeyCode> + '_ {
+        self.bindings
+            .iter()
+            .filter_map(move |&(key, bound)| (bound == action).then_some(key))
+    }
+
+    /// Replaces the keys bound to an action.
+    ///
+    /// The given keys are unbound from any other action they were bound to before.
+    pub(crate) fn bind(&mut self, action: Action, keys: &[KeyCode]) {
+        self.bindings
+            .retain(|(key, bound)| *bound != action && !keys.contains(key));
+        self.bindings.extend(keys.iter().map(|&key| (key, action)));
+    }
+
+    /// Formats every key bound to an action, separated by commas.
+    ///
+    /// This function returns `None` if the action isn't bound to any key.
+    pub(crate) fn key_names(&self, action: Action) -> Option<String> {
+        let keys = self.keys(action).map(key_name).collect::<Vec<_>>();
+
+        (!keys.is_empty()).then(|| keys.join(", "))
+    }
+
+    /// Formats a navigation hint for a group of actions.
+    ///
+    /// This function returns the first key bound to each action, separated by spaces and wrapped
+    /// in parentheses, followed by the given label. Actions without any key bound are skipped, and
+    /// no hint is returned if none of the actions are bound.
+    pub(crate) fn hint(&self, actions: &[Action], label: &str) -> Option<String> {
+        let keys = actions
+            .iter()
+            .filter_map(|&action| self.keys(action).next())
+            .map(key_name)
+            .collect::<Vec<_>>();
+
+        (!keys.is_empty()).then(|| format!("({}) {label}", keys.join(" ")))
+    }
+
+    /// Formats the navigation hints for several groups of actions, separated by slashes.
+    pub(crate) fn hints(&self, groups: &[(&[Action], &str)]) -> String {
+        groups
+            .iter()
+            .filter_map(|&(actions, label)| self.hint(actions, label))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Parses a keymap from the contents of a configuration file.
+    ///
+    /// The file is made of `name = value` lines, where blank lines and lines starting with '#' are
+    /// ignored. A `preset` line replaces every binding with the comma-separated list of presets
+    /// given as its value, while a line named after an action replaces the keys bound to it with
+    /// the comma-separated list of keys given as its value. Lines are applied in order on top of
+    /// the default keymap.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error pointing at the first line that can't be parsed.
+    pub(crate) fn parse(contents: &str) -> Result<Self> {
+        let mut keymap = Self::default();
+
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            keymap
+                .apply(line)
+                .wrap_err_with(|| format!("invalid key binding on line {}", idx + 1))?;
+        }
+
+        Ok(keymap)
+    }
+
+    /// Applies a single non-empty configuration line to the keymap.
+    fn apply(&mut self, line: &str) -> Result<()> {
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| eyre!("expected `name = value`, found `{line}`"))?;
+        let name = name.trim();
+        let values = value
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+
+        if name == "preset" {
+            let presets = values
+                .map(|value| {
+                    Preset::from_name(value).ok_or_else(|| eyre!("unknown preset `{value}`"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if presets.is_empty() {
+                bail!("no preset given");
+            }
+
+            *self = Self::from_presets(&presets);
+        } else {
+            let action = Action::from_name(name).ok_or_else(|| eyre!("unknown action `{name}`"))?;
+            let keys = values
+                .map(|value| parse_key(value).ok_or_else(|| eyre!("unknown key `{value}`")))
+                .collect::<Result<Vec<_>>>()?;
+
+            self.bind(action, &keys);
+        }
+
+        Ok(())
+    }
+
+    /// Loads the keymap from the user's configuration file.
+    ///
+    /// This function returns the default keymap if there's no configuration file.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the configuration file exists but can't be read or parsed.
+    pub(crate) fn load() -> Result<Self> {
+        let Some(path) = config_path().filter(|path| path.is_file()) else {
+            return Ok(Self::default());
+        };
+
+        let contents = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("failed to read key bindings from {}", path.display()))?;
+
+        Self::parse(&contents)
+            .wrap_err_with(|| format!("failed to load key bindings from {}", path.display()))
+    }
+}
+
+/// Returns the path of the key bindings configuration file.
+///
+/// The file is looked up as `labyrintuine/keymap.conf` within `$XDG_CONFIG_HOME`, falling back to
+/// `$HOME/.config` if the former isn't set.
+pub(crate) fn config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("labyrintuine").join("keymap.conf"))
+}
+
+/// Parses a key from its name in the configuration file.
+///
+/// Single characters stand for themselves, while special keys are named in lowercase, such as
+/// `up`, `enter` or `esc`.
+pub(crate) fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(char));
+    }
+
+    match name.to_lowercase().as_str() {
+        "up" => Some(KeyCode::Up),
+        "down" => Some(KeyCode::Down),
+        "left" => Some(KeyCode::Left),
+        "right" => Some(KeyCode::Right),
+        "enter" => Some(KeyCode::Enter),
+        "esc" => Some(KeyCode::Esc),
+        "space" => Some(KeyCode::Char(' ')),
+        "tab" => Some(KeyCode::Tab),
+        "backspace" => Some(KeyCode::Backspace),
+        "home" => Some(KeyCode::Home),
+        "end" => Some(KeyCode::End),
+        "pageup" => Some(KeyCode::PageUp),
+        "pagedown" => Some(KeyCode::PageDown),
+        _ => None,
+    }
+}
+
+/// Returns the name of a key as accepted by [`parse_key`], if it can be bound.
+pub(crate) fn key_token(key: KeyCode) -> Option<String> {
+    let token = match key {
+        KeyCode::Char(' ') => "space",
+        KeyCode::Char(char) => return Some(char.to_string()),
+        KeyCode::Up => "up",
+        KeyCode::Down => "down",
+        KeyCode::Left => "left",
+        KeyCode::Right => "right",
+        KeyCode::Enter => "enter",
+        KeyCode::Esc => "esc",
+        KeyCode::Tab => "tab",
+        KeyCode::Backspace => "backspace",
+        KeyCode::Home => "home",
+        KeyCode::End => "end",
+        KeyCode::PageUp => "pageup",
+        KeyCode::PageDown => "pagedown",
+        _ => return None,
+    };
+
+    Some(token.to_owned())
+}
+
+/// Returns the name under which a key is shown in the navigation hints.
+pub(crate) fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "space".to_owned(),
+        KeyCode::Char(char) => char.to_string(),
+        KeyCode::Up => "\u{2191}".to_owned(),
+        KeyCode::Down => "\u{2193}".to_owned(),
+        KeyCode::Left => "\u{2190}".to_owned(),
+        KeyCode::Right => "\u{2192}".to_owned(),
+        KeyCode::Enter => "enter".to_owned(),
+        KeyCode::Esc => "esc".to_owned(),
+        KeyCode::Tab => "tab".to_owned(),
+        KeyCode::Backspace => "backspace".to_owned(),
+        KeyCode::Home => "home".to_owned(),
+        KeyCode::End => "end".to_owned(),
+        KeyCode::PageUp => "pageup".to_owned(),
+        KeyCode::PageDown => "pagedown".to_owned(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_keymap_binds_all_presets() {
+        let keymap = Keymap::default();
+
+        assert_eq!(keymap.action(KeyCode::Char('j')), Some(Action::Down));
+        assert_eq!(keymap.action(KeyCode::Down), Some(Action::Down));
+        assert_eq!(keymap.action(KeyCode::Char('s')), Some(Action::Down));
+        assert_eq!(keymap.action(KeyCode::Enter), Some(Action::Select));
+        assert_eq!(keymap.action(KeyCode::Esc), Some(Action::Back));
+        assert_eq!(keymap.action(KeyCode::Char('x')), None);
+    }
+
+    #[test]
+    fn test_default_keymap_binds_every_action() {
+        let keymap = Keymap::default();
+
+        for action in Action::ALL {
+            assert!(
+                keymap.keys(action).next().is_some(),
+                "{} should be bound by default",
+                action.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_hints_use_first_bound_key() {
+        let keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.hints(&[(&[Action::Down], "down"), (&[Action::Up], "up")]),
+            "(j) down / (k) up"
+        );
+        assert_eq!(
+            keymap.hint(
+                &[
+                    Action::PanLeft,
+                    Action::PanDown,
+                    Action::PanUp,
+                    Action::PanRight
+                ],
+                "pan"
+            ),
+            Some("(H J K L) pan".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_key_names_lists_every_binding() {
+        let mut keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.key_names(Action::Up),
+            Some("k, \u{2191}, w".to_owned())
+        );
+
+        keymap.bind(Action::Help, &[]);
+        assert_eq!(keymap.key_names(Action::Help), None);
+    }
+
+    #[test]
+    fn test_bind_moves_key_between_actions() {
+        let mut keymap = Keymap::default();
+
+        keymap.bind(Action::Quit, &[KeyCode::Esc]);
+
+        assert_eq!(keymap.action(KeyCode::Esc), Some(Action::Quit));
+        assert_eq!(keymap.action(KeyCode::Char('q')), None);
+        assert_eq!(keymap.keys(Action::Back).count(), 3);
+    }
+
+    #[test]
+    fn test_parse_preset_and_overrides() {
+        let contents = "# arrows only\npreset = arrows\n\ndown = Down, n\n";
+
+        let keymap = Keymap::parse(contents).expect("valid configuration should parse");
+
+        assert_eq!(keymap.action(KeyCode::Char('j')), None);
+        assert_eq!(keymap.action(KeyCode::Char('n')), Some(Action::Down));
+        assert_eq!(keymap.action(KeyCode::Up), Some(Action::Up));
+        assert_eq!(
+            keymap.hint(&[Action::Up], "up"),
+            Some("(\u{2191}) up".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_unbinding_action() {
+        let keymap = Keymap::parse("toggle_follow =").expect("empty bindings should parse");
+
+        assert_eq!(keymap.hint(&[Action::ToggleFollow], "follow"), None);
+        assert_eq!(keymap.action(KeyCode::Char('f')), None);
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_line() {
+        let err = Keymap::parse("up = k\njump = space").expect_err("unknown actions should fail");
+
+        assert_eq!(
+            format!("{err:#}"),
+            "invalid key binding on line 2: unknown action `jump`"
+        );
+
+        assert!(Keymap::parse("up k").is_err());
+        assert!(Keymap::parse("preset = emacs").is_err());
+        assert!(Keymap::parse("up = shift").is_err());
+    }
+
+    #[test]
+    fn test_key_tokens_round_trip() {
+        for key in [
+            KeyCode::Char('j'),
+            KeyCode::Char(' '),
+            KeyCode::Up,
+            KeyCode::Enter,
+            KeyCode::PageDown,
+        ] {
+            let token = key_token(key).expect("the key should have a token");
+            assert_eq!(parse_key(&token), Some(key), "`{token}` should round-trip");
+        }
+
+        assert_eq!(key_token(KeyCode::F(1)), None);
+    }
+
+    #[test]
+    fn test_parse_key_names() {
+        assert_eq!(parse_key("q"), Some(KeyCode::Char('q')));
+        assert_eq!(parse_key("Q"), Some(KeyCode::Char('Q')));
+        assert_eq!(parse_key("Enter"), Some(KeyCode::Enter));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("f13"), None);
+    }
+}
@@ -4,12 +4,41 @@
 //! game features a modular architecture with separate concerns for UI rendering, event handling,
 //! file operations, and core application logic.
 
+mod analysis;
 mod app;
+mod braid;
+mod camera;
+mod cli;
+mod error;
 mod events;
+mod export;
 mod file_loader;
+mod generate;
+mod grid;
+mod import;
+mod input;
+mod keymap;
 mod map;
+mod oneway;
 mod pathfinding;
+mod portal;
+mod puzzle;
+mod replay;
+mod session;
+mod stairs;
+mod terrain;
+#[cfg(test)]
+mod testing;
+mod time;
 mod types;
 mod ui;
+mod weave;
 
 pub use app::App;
+pub use braid::BraidSettings;
+pub use cli::{ImageExport, MapBraid, MapGeneration, MapImport, Options, USAGE};
+pub use export::ImageStyle;
+pub use generate::{DungeonSettings, GenerateSettings};
+pub use grid::Topology;
+pub use import::ImportSettings;
+pub use session::{init_terminal, install_hooks, restore_terminal, TerminalGuard};
@@ -5,15 +5,59 @@
     reason = "The dependencies are used in the library crate."
 )]
 
-use color_eyre::{eyre::Result, install};
-use labyrintuine::App;
+use std::env;
+
+use color_eyre::eyre::Result;
+use labyrintuine::{App, Options, USAGE};
 
 fn main() -> Result<()> {
-    install()?;
+    labyrintuine::install_hooks()?;
+
+    let options = Options::parse(env::args_os().skip(1))?;
+    if options.help {
+        print!("{USAGE}");
+        return Ok(());
+    }
+
+    let mut app = App::default();
+    if let Some(path) = &options.map {
+        app.load_map(path)?;
+    }
+    if let Some(import) = &options.import {
+        return app.import_map(import);
+    }
+    if let Some(generation) = &options.generate {
+        return app.generate_map(generation);
+    }
+    if let Some(braid) = &options.braid {
+        println!("{}", app.braid_map(braid)?);
+        return Ok(());
+    }
+    if options.analyze {
+        println!("{}", app.analyze_map()?);
+        return Ok(());
+    }
+    if let Some(export) = &options.image {
+        return app.export_image(export);
+    }
+    if let Some(path) = &options.export_cast {
+        return app.export_cast(path, options.size);
+    }
+
+    if let Some(path) = &options.replay {
+        app.replay(path)?;
+    }
+
+    // Headless replays use the default key bindings so that they behave the same on every machine
+    if options.headless {
+        return app.run_headless(&options.expectations);
+    }
 
-    let mut terminal = ratatui::init();
-    App::default().run(&mut terminal)?;
-    ratatui::restore();
+    app.load_keymap();
+    if let Some(path) = &options.record {
+        app.record(path)?;
+    }
 
-    Ok(())
+    // The guard restores the terminal when dropped, before any error is returned and reported
+    app.run_guarded(labyrintuine::init_terminal()?)
 }
@@ -2,10 +2,34 @@
 //!
 //! This module contains the `Map` struct and related functionality for handling labyrinth map data,
 //! including loading, validation, and the default map.
+//!
+//! Map files come in two versions. Version 1 files are bare grids of digits, while version 2 files
+//! start with a `labmap 2` line followed by an optional header of `field: value` lines, closed by a
+//! `---` line before the grid. Lines starting with '#' are comments in both versions. The optional
+//! `topology` field tells how the grid is read, square cells being assumed without it.
+//!
+//! ```text
+//! labmap 2
+//! # Drawn for the first worksheet
+//! title: Spiral
+//! difficulty: easy
+//! ---
+//! 2222
+//! 2134
+//! 2222
+//! ```
 
 use std::{ffi::OsString, sync::LazyLock};
 
-use color_eyre::eyre::{OptionExt as _, Result};
+use color_eyre::eyre::{bail, ensure, eyre, OptionExt as _, Result};
+
+use crate::grid::Topology;
+
+/// Version of the map format written by the crate.
+pub(crate) const FORMAT_VERSION: u32 = 2;
+
+/// Line closing the header of a version 2 map file.
+const HEADER_END: &str = "---";
 
 /// Labyrinth map data container.
 ///
@@ -23,6 +47,130 @@ pub(crate) struct Map {
     /// This field represents the actual map stored as a vector of strings, each string representing
     /// a row in the map.
     pub data: Vec<String>,
+    /// Details about the map given in the header of its file.
+    pub metadata: Metadata,
+}
+
+/// Details about a labyrinth map.
+///
+/// This structure holds the optional fields of the header of version 2 map files, which are shown
+/// to the user in the map menu and the in-game tooltip.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd)]
+pub(crate) struct Metadata {
+    /// Title of the map.
+    pub title: Option<String>,
+    /// Author of the map.
+    pub author: Option<String>,
+    /// Description of the map.
+    pub description: Option<String>,
+    /// Difficulty of the map, in the author's words.
+    pub difficulty: Option<String>,
+    /// Name of the algorithm the map was generated with.
+    pub generator: Option<String>,
+    /// Seed the map was generated from.
+    pub seed: Option<u64>,
+    /// Date the map was created on.
+    pub created: Option<String>,
+    /// Shape of the cells of the maze, square if unset.
+    pub topology: Option<Topology>,
+}
+
+impl Metadata {
+    /// Name of every header field, in the order they're written and shown.
+    pub(crate) const FIELDS: [&'static str; 8] = [
+        "title",
+        "author",
+        "description",
+        "difficulty",
+        "generator",
+        "seed",
+        "created",
+        "topology",
+    ];
+
+    /// Sets a header field from its value in a map file.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the field is unknown or was already set, or if the value
+    /// isn't valid for the field.
+    pub(crate) fn set(&mut self, field: &str, value: &str) -> Result<()> {
+        let slot = match field {
+            "title" => &mut self.title,
+            "author" => &mut self.author,
+            "description" => &mut self.description,
+            "difficulty" => &mut self.difficulty,
+            "generator" => &mut self.generator,
+            "created" => &mut self.created,
+            "seed" => {
+                ensure!(self.seed.is_none(), "the seed field is set twice");
+                self.seed = Some(
+                    value
+                        .parse()
+                        .map_err(|err| eyre!("invalid seed {value:?}: {err}"))?,
+                );
+                return Ok(());
+            }
+            "topology" => {
+                ensure!(self.topology.is_none(), "the topology field is set twice");
+                self.topology = Some(Topology::from_name(value).ok_or_else(|| {
+                    eyre!(
+                        "unknown topology {value:?}, expected one of {}",
+                        Topology::ALL.map(Topology::name).join(", ")
+                    )
+                })?);
+                return Ok(());
+            }
+            _ => bail!(
+                "unknown header field {field:?}, expected one of {}",
+                Self::FIELDS.join(", ")
+            ),
+        };
+
+        ensure!(slot.is_none(), "the {field} field is set twice");
+        *slot = Some(value.to_owned());
+
+        Ok(())
+    }
+
+    /// Returns the `(field, value)` pairs of the fields that are set, in the order of
+    /// [`Metadata::FIELDS`].
+    pub(crate) fn fields(&self) -> Vec<(&'static str, String)> {
+        let values = [
+            self.title.clone(),
+            self.author.clone(),
+            self.description.clone(),
+            self.difficulty.clone(),
+            self.generator.clone(),
+            self.seed.map(|seed| seed.to_string()),
+            self.created.clone(),
+            self.topology.map(|topology| topology.name().to_owned()),
+        ];
+
+        Self::FIELDS
+            .into_iter()
+            .zip(values)
+            .filter_map(|(field, value)| Some((field, value?)))
+            .collect()
+    }
+
+    /// Returns a one-line summary of the map, made of its title, or the given fallback name, along
+    /// with its author and difficulty.
+    pub(crate) fn summary(&self, name: &str) -> String {
+        let title = self.title.as_deref().unwrap_or(name);
+        let author = self
+            .author
+            .as_ref()
+            .map(|author| format!(" by {author}"))
+            .unwrap_or_default();
+        let difficulty = self
+            .difficulty
+            .as_ref()
+            .map(|difficulty| format!(" ({difficulty})"))
+            .unwrap_or_default();
+
+        format!("{title}{author}{difficulty}")
+    }
 }
 
 impl Default for Map {
@@ -44,10 +192,7 @@ impl Map {
     /// - The `OsString` cannot be converted to a string slice
     /// - The filename doesn't contain the expected ".labmap" extension
     pub(crate) fn new(key: OsString, data: &str) -> Result<Self> {
-        let mut vec = Vec::new();
-        for line in data.lines() {
-            vec.push(line.to_owned());
-        }
+        let (metadata, rows) = parse_contents(data)?;
 
         let mut file_name = key
             .to_str()
@@ -61,9 +206,82 @@ impl Map {
 
         Ok(Self {
             key: file_name,
-            data: vec,
+            data: rows.into_iter().map(str::to_owned).collect(),
+            metadata,
         })
     }
+
+    /// Formats the map as the contents of a map file in the current version of the format.
+    pub(crate) fn to_labmap(&self) -> String {
+        let mut lines = vec![format!("labmap {FORMAT_VERSION}")];
+        lines.extend(
+            self.metadata
+                .fields()
+                .into_iter()
+                // Header values can't span several lines
+                .map(|(field, value)| {
+                    format!("{field}: {}", value.lines().collect::<Vec<_>>().join(" "))
+                }),
+        );
+        lines.push(HEADER_END.to_owned());
+        lines.extend(self.data.iter().cloned());
+
+        let mut contents = lines.join("\n");
+        contents.push('\n');
+        contents
+    }
+}
+
+/// Splits the contents of a map file into the metadata of its header and the rows of its grid.
+///
+/// Files without a version line are read as version 1 files, holding only a grid. Comment lines are
+/// dropped, as are blank lines before and after the grid. The grid itself isn't validated.
+///
+/// # Errors
+///
+/// This function returns an error if the version isn't supported, if the header isn't closed or if
+/// any of its lines isn't a valid `field: value` pair.
+pub(crate) fn parse_contents(contents: &str) -> Result<(Metadata, Vec<&str>)> {
+    let mut lines = contents
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .skip_while(|line| line.trim().is_empty())
+        .peekable();
+    let mut metadata = Metadata::default();
+
+    if let Some(version_line) = lines.next_if(|line| line.starts_with("labmap")) {
+        let version = version_line.trim_start_matches("labmap").trim();
+        ensure!(
+            version == FORMAT_VERSION.to_string(),
+            "unsupported map format version {version:?}, expected {FORMAT_VERSION}"
+        );
+
+        loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| eyre!("the map header isn't closed with a {HEADER_END} line"))?;
+            if line.trim() == HEADER_END {
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').ok_or_else(|| {
+                eyre!("expected a header line of the form FIELD: VALUE, found {line:?}")
+            })?;
+            metadata.set(field.trim(), value.trim())?;
+        }
+    }
+
+    let mut rows = lines
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>();
+    while rows.last().is_some_and(|row| row.trim().is_empty()) {
+        let _ = rows.pop();
+    }
+
+    Ok((metadata, rows))
 }
 
 /// Default labyrinth map used as fallback.
@@ -98,6 +316,64 @@ static DEFAULT_MAP: LazyLock<&str> = LazyLock::new(|| {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_map_new_with_header() {
+        let contents = "\
+# A map with every header field
+labmap 2
+title: Spiral
+author: Ada
+description: A short walk
+difficulty: easy
+generator: backtracker
+seed: 42
+created: 2026-10-18
+topology: hex
+---
+# The grid
+2222
+2134
+2222
+";
+
+        let map = Map::new("spiral.labmap".into(), contents).expect("the map should parse");
+
+        assert_eq!(map.key, "spiral");
+        assert_eq!(map.data, vec!["2222", "2134", "2222"]);
+        assert_eq!(map.metadata.seed, Some(42));
+        assert_eq!(map.metadata.topology, Some(Topology::Hex));
+        assert_eq!(map.metadata.summary(&map.key), "Spiral by Ada (easy)");
+        assert_eq!(
+            map.to_labmap().lines().collect::<Vec<_>>(),
+            contents
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_map_new_version_1() {
+        let map =
+            Map::new("bare.labmap".into(), "2222\n2134\n2222\n").expect("the map should parse");
+
+        assert_eq!(map.data, vec!["2222", "2134", "2222"]);
+        assert_eq!(map.metadata, Metadata::default());
+        assert_eq!(map.metadata.summary(&map.key), "bare");
+        assert_eq!(map.to_labmap(), "labmap 2\n---\n2222\n2134\n2222\n");
+    }
+
+    #[test]
+    fn test_parse_contents_errors() {
+        assert!(parse_contents("labmap 3\n---\n2222").is_err());
+        assert!(parse_contents("labmap 2\ntitle: Spiral\n2222").is_err());
+        assert!(parse_contents("labmap 2\ncolour: red\n---\n2222").is_err());
+        assert!(parse_contents("labmap 2\ntitle: a\ntitle: b\n---\n2222").is_err());
+        assert!(parse_contents("labmap 2\nseed: many\n---\n2222").is_err());
+        assert!(parse_contents("labmap 2\ntopology: round\n---\n2222").is_err());
+        assert!(parse_contents("labmap 2\njust words\n---\n2222").is_err());
+    }
+
     #[test]
     fn test_map_default() {
         let map = Map::default();
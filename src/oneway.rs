@@ -0,0 +1,46 @@
+//! One-way cells.
+//!
+//! This module describes the arrow cells of a maze ('^', 'v', '<' and '>'), which can be stepped
+//! onto from any side but only left in the direction they point to. Mazes holding them are
+//! directed: a cell reachable from another one may not lead back to it.
+
+/// Colour arrows are drawn in, as RGB components.
+pub(crate) const ARROW_COLOUR: [u8; 3] = [0xff, 0x80, 0x40];
+
+/// Every arrow of the map format, along with the move it allows and the glyph it's drawn with.
+const ARROWS: [(u8, (isize, isize), char); 4] = [
+    (b'^', (0, -1), '\u{2191}'),
+    (b'v', (0, 1), '\u{2193}'),
+    (b'<', (-1, 0), '\u{2190}'),
+    (b'>', (1, 0), '\u{2192}'),
+];
+
+/// Returns the only move allowed out of the arrow on the given cell, if it holds one.
+pub(crate) fn direction(cell: u8) -> Option<(isize, isize)> {
+    ARROWS
+        .iter()
+        .find(|&&(byte, ..)| byte == cell)
+        .map(|&(_, step, _)| step)
+}
+
+/// Returns the glyph of the arrow pointing in the given direction, if any.
+pub(crate) fn glyph(direction: (isize, isize)) -> Option<char> {
+    ARROWS
+        .iter()
+        .find(|&&(_, step, _)| step == direction)
+        .map(|&(.., glyph)| glyph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrows() {
+        assert_eq!(direction(b'^'), Some((0, -1)));
+        assert_eq!(direction(b'>'), Some((1, 0)));
+        assert_eq!(direction(b'3'), None);
+        assert_eq!(glyph((-1, 0)), Some('\u{2190}'));
+        assert_eq!(glyph((1, 1)), None);
+    }
+}
@@ -3,9 +3,20 @@
 //! This module contains the pathfinding algorithm implementation, animation system, and coordinate
 //! transformation utilities for maze solving visualization.
 
-use std::time::{Duration, Instant};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use color_eyre::eyre::{OptionExt as _, Result};
+use color_eyre::eyre::{bail, ensure, Result};
+
+use crate::{
+    grid::{Cell, Grid, NodeId},
+    puzzle::KeySet,
+    time::{Clock, SystemClock},
+};
 
 /// Animation frame delay in milliseconds.
 ///
@@ -19,7 +30,7 @@ pub(crate) const ANIMATION_FRAME_DELAY_MS: u64 = 200;
 /// This enumeration represents the different types of steps that can occur during the animated
 /// pathfinding visualization, allowing for proper rendering of both forward exploration and
 /// backtracking behavior.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum AnimationStep {
     /// Add a coordinate to the current path visualization.
     ///
@@ -31,6 +42,176 @@ pub(crate) enum AnimationStep {
     /// This variant represents backtracking in the pathfinding algorithm by removing a coordinate
     /// from the currently displayed path.
     Remove(usize, usize),
+    /// Mark a coordinate as reached from the entry point with the given index.
+    ///
+    /// This variant represents the flood of a multi-source search spreading to a new cell, which is
+    /// claimed by the entry point it was reached from first.
+    Visit(usize, usize, usize),
+    /// Mark a coordinate as part of the path from the entry point with the given index to an exit.
+    ///
+    /// This variant represents the shortest paths found by a multi-source search, traced once the
+    /// flood reaches an exit.
+    Trace(usize, usize, usize),
+    /// Mark a coordinate as reached while holding the given keys.
+    ///
+    /// This variant represents the search over keys and positions settling a new state, a cell
+    /// being reached again whenever it is reached with different keys.
+    Reach(usize, usize, KeySet),
+    /// Mark the key with the given index, lying at a coordinate, as picked up.
+    ///
+    /// This variant represents the path out of a maze collecting a key, right after the cell it
+    /// lies on is traced.
+    Collect(usize, usize, u8),
+    /// Flash both ends of a portal jump from the first coordinate to the second one.
+    ///
+    /// This variant represents a walker going through a portal, which is shown as a brief flash at
+    /// both of its ends rather than as a line between two cells that aren't next to each other.
+    Jump((usize, usize), (usize, usize)),
+}
+
+/// Search algorithm used to solve mazes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Solver {
+    /// Depth-first search, or breadth-first search from several entry points, both blind to the
+    /// cost of terrain.
+    #[default]
+    DepthFirst,
+    /// Dijkstra's algorithm, expanding the cheapest cells to reach first.
+    Dijkstra,
+    /// A* search, expanding first the cells that look cheapest to get out from.
+    AStar,
+}
+
+impl Solver {
+    /// Returns the name of the search algorithm, as shown on the in-game screen.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::DepthFirst => "depth-first",
+            Self::Dijkstra => "Dijkstra",
+            Self::AStar => "A*",
+        }
+    }
+
+    /// Returns the search algorithm cycled to after this one.
+    pub(crate) const fn next(self) -> Self {
+        match self {
+            Self::DepthFirst => Self::Dijkstra,
+            Self::Dijkstra => Self::AStar,
+            Self::AStar => Self::DepthFirst,
+        }
+    }
+}
+
+/// Number of moves and total cost of a way out of a maze.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Route {
+    /// Number of moves from the entry point to the exit.
+    pub steps: usize,
+    /// Sum of the costs of the cells stepped onto.
+    pub cost: u32,
+}
+
+impl Route {
+    /// Measures the given path through a maze, or returns `None` if it crosses walls.
+    pub(crate) fn of(grid: &Grid, path: &[(usize, usize)]) -> Option<Self> {
+        Some(Self {
+            steps: path.len().saturating_sub(1),
+            cost: grid.path_cost(path)?,
+        })
+    }
+}
+
+/// Statistics about the ways out of a maze.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct RouteStats {
+    /// Way out found by the solver, if any.
+    pub found: Option<Route>,
+    /// Way out with the fewest moves from any entry point, regardless of terrain.
+    pub shortest: Option<Route>,
+    /// Way out with the lowest cost from any entry point.
+    pub cheapest: Option<Route>,
+}
+
+impl RouteStats {
+    /// Computes the statistics of a maze, given the path the solver found through it.
+    pub(crate) fn compute(
+        grid: &Grid,
+        entries: &[NodeId],
+        found: Option<&[(usize, usize)]>,
+    ) -> Self {
+        let routes = |search: fn(&Grid, NodeId) -> Option<Path>| {
+            entries
+                .iter()
+                .filter_map(|&entry| search(grid, entry))
+                .filter_map(|path| Route::of(grid, &path))
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            found: found.and_then(|path| Route::of(grid, path)),
+            shortest: routes(shortest_path)
+                .into_iter()
+                .min_by_key(|route| (route.steps, route.cost)),
+            cheapest: routes(cheapest_path)
+                .into_iter()
+                .min_by_key(|route| (route.cost, route.steps)),
+        }
+    }
+}
+
+/// Sequence of `(column, row)` coordinates leading from one maze cell to another.
+pub(crate) type Path = Vec<(usize, usize)>;
+
+/// Outcome of a search from several entry points at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SearchReport {
+    /// Every entry point along with the shortest path from it to an exit, if there's any.
+    pub paths: Vec<((usize, usize), Option<Path>)>,
+    /// Index of the entry point from which an exit was reached first, if any.
+    pub first: Option<usize>,
+}
+
+impl SearchReport {
+    /// Returns the path out from the entry point that reached an exit first, if any.
+    pub(crate) fn found(&self) -> Option<&Path> {
+        let (_, path) = self.paths.get(self.first?)?;
+        path.as_ref()
+    }
+
+    /// Returns a one-line account of which entry point reaches an exit first.
+    pub(crate) fn summary(&self) -> String {
+        let winner = self
+            .first
+            .and_then(|first| Some((first, self.paths.get(first)?)));
+
+        match winner {
+            Some((first, ((column, row), Some(path)))) => format!(
+                "entry {} at ({column}, {row}) is out first, in {} moves",
+                first + 1,
+                path.len().saturating_sub(1)
+            ),
+            _ => "no entry point reaches an exit".to_owned(),
+        }
+    }
+
+    /// Describes the path out from the entry point at the given cell, if there's one there.
+    pub(crate) fn describe_entry(&self, cell: (usize, usize)) -> Option<String> {
+        let (index, (_, path)) = self
+            .paths
+            .iter()
+            .enumerate()
+            .find(|(_, (entry, _))| *entry == cell)?;
+        let (column, row) = cell;
+
+        Some(match path {
+            Some(path) => format!(
+                "entry {} at ({column}, {row}): out in {} moves",
+                index + 1,
+                path.len().saturating_sub(1)
+            ),
+            None => format!("entry {} at ({column}, {row}): no way out", index + 1),
+        })
+    }
 }
 
 /// Animation state manager for pathfinding visualization.
@@ -59,6 +240,36 @@ pub(crate) struct AnimationManager {
     /// This field maintains the currently visible path coordinates during animation, allowing for
     /// proper backtracking visualization by removing coordinates when needed.
     pub current_path: Vec<(usize, usize)>,
+    /// Cells reached so far by a multi-source search, along with the index of their entry point.
+    pub visited: Vec<((usize, usize), usize)>,
+    /// Cells traced so far on the paths out of a multi-source search, along with the index of
+    /// their entry point.
+    pub traced: Vec<((usize, usize), usize)>,
+    /// Cells reached so far by a search over keys and positions, along with the keys held there.
+    pub reached: Vec<((usize, usize), KeySet)>,
+    /// Keys picked up so far on the paths out, along with the cell they lie on.
+    pub collected: Vec<((usize, usize), u8)>,
+    /// Outcome of the search, if the maze has several entry points.
+    pub report: Option<SearchReport>,
+    /// Way out found by the solver, if any.
+    pub found: Option<Path>,
+    /// Cells of the shortest way out that the solver's way out doesn't go through, if the maze has
+    /// a single entry point, showing where a depth-first search strays from the optimal path.
+    pub shortcut: Path,
+    /// Both ends of the portal jump made by the last animation step, if it was one.
+    pub flash: Option<((usize, usize), (usize, usize))>,
+    /// Statistics about the ways out of the maze being solved.
+    pub stats: RouteStats,
+    /// Search algorithm the maze is solved with.
+    pub solver: Solver,
+    /// Maze being solved, built once whenever the animation is recorded.
+    pub grid: Grid,
+    /// Clock driving the animation.
+    ///
+    /// This field holds the source of time used to decide when the next animation frame is due,
+    /// which is the system clock unless a different one is injected with
+    /// [`with_clock`](AnimationManager::with_clock).
+    pub clock: Rc<dyn Clock>,
 }
 
 impl Default for AnimationManager {
@@ -70,11 +281,28 @@ impl Default for AnimationManager {
 impl AnimationManager {
     /// Creates a new animation manager with default values.
     pub(crate) fn new() -> Self {
+        Self::with_clock(Rc::new(SystemClock))
+    }
+
+    /// Creates a new animation manager driven by the given clock.
+    pub(crate) fn with_clock(clock: Rc<dyn Clock>) -> Self {
         Self {
             steps: Vec::new(),
             current_index: 0,
-            last_update_time: Instant::now(),
+            last_update_time: clock.now(),
             current_path: Vec::new(),
+            visited: Vec::new(),
+            traced: Vec::new(),
+            reached: Vec::new(),
+            collected: Vec::new(),
+            report: None,
+            found: None,
+            shortcut: Vec::new(),
+            flash: None,
+            stats: RouteStats::default(),
+            solver: Solver::default(),
+            grid: Grid::default(),
+            clock,
         }
     }
 
@@ -82,15 +310,89 @@ impl AnimationManager {
     pub(crate) fn reset(&mut self) {
         self.current_index = 0;
         self.current_path.clear();
-        self.last_update_time = Instant::now();
+        self.visited.clear();
+        self.traced.clear();
+        self.reached.clear();
+        self.collected.clear();
+        self.flash = None;
+        self.last_update_time = self.clock.now();
     }
 
     /// Clears all animation data and resets state.
     pub(crate) fn clear(&mut self) {
         self.steps.clear();
+        self.report = None;
+        self.found = None;
+        self.shortcut.clear();
+        self.stats = RouteStats::default();
         self.reset();
     }
 
+    /// Records the animation steps solving the given maze and restarts the animation.
+    ///
+    /// With the depth-first solver, mazes with a single entry point are explored with a depth-first
+    /// search, while mazes with several are flooded from every entry point at once with a
+    /// breadth-first search. Mazes with doors are searched over keys and positions instead, with a
+    /// breadth-first search for the depth-first solver. The cost-aware solvers search from every
+    /// entry point at once. The outcome of searches from several entry points is kept in
+    /// [`report`](AnimationManager::report). The cells of the shortest way out off the solver's way
+    /// out are kept in [`shortcut`](AnimationManager::shortcut).
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the maze has no entry point.
+    pub(crate) fn record(&mut self, grid: Grid) -> Result<()> {
+        self.steps.clear();
+        self.grid = grid;
+
+        let grid = &self.grid;
+        let entries = grid.entries();
+        let doors = grid.has_doors();
+        let (report, found) = match (self.solver, entries.as_slice()) {
+            (_, []) => bail!("failed to retrieve entry point in map"),
+            (Solver::DepthFirst, &[entry]) if !doors => {
+                let found =
+                    record_animation_steps(grid, entry, &mut HashSet::new(), &mut self.steps)
+                        .then(|| solution_path(grid, &self.steps))
+                        .flatten();
+                (None, found)
+            }
+            (solver, _) => {
+                let report = if solver == Solver::DepthFirst && !doors {
+                    record_multi_source_steps(grid, &entries, &mut self.steps)
+                } else {
+                    record_state_steps(grid, &entries, solver, &mut self.steps)
+                };
+                let found = report.found().cloned();
+                ((entries.len() > 1).then_some(report), found)
+            }
+        };
+        self.report = report;
+        self.stats = RouteStats::compute(grid, &entries, found.as_deref());
+        self.shortcut = match entries.as_slice() {
+            &[entry] => shortest_path(grid, entry)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|cell| !found.as_ref().is_some_and(|path| path.contains(cell)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.found = found;
+
+        self.reset();
+        Ok(())
+    }
+
+    /// Returns the cell the animation last reached, for the camera to follow.
+    pub(crate) fn head(&self) -> Option<(usize, usize)> {
+        self.current_path
+            .last()
+            .or_else(|| self.traced.last().map(|(cell, _)| cell))
+            .or_else(|| self.visited.last().map(|(cell, _)| cell))
+            .or_else(|| self.reached.last().map(|(cell, _)| cell))
+            .copied()
+    }
+
     /// Updates the animation state based on timing and current progress.
     ///
     /// This method advances the animation by processing the next step in the animation sequence
@@ -98,10 +400,16 @@ impl AnimationManager {
     /// current animation path to show the pathfinding exploration and backtracking.
     pub(crate) fn update(&mut self) {
         // Check if enough time has passed for the next animation frame
-        if self.last_update_time.elapsed() >= Duration::from_millis(ANIMATION_FRAME_DELAY_MS) {
-            self.last_update_time = Instant::now();
+        let now = self.clock.now();
+        if now.saturating_duration_since(self.last_update_time)
+            >= Duration::from_millis(ANIMATION_FRAME_DELAY_MS)
+        {
+            self.last_update_time = now;
 
             if self.current_index < self.steps.len() {
+                // Jumps only flash until the next step
+                self.flash = None;
+
                 // Process the next animation step
                 if let Some(step) = self.steps.get(self.current_index) {
                     match step {
@@ -109,15 +417,31 @@ impl AnimationManager {
                             self.current_path.push((*x, *y));
                         }
                         AnimationStep::Remove(x, y) => {
-                            // Remove the coordinate from current path (backtracking)
+                            // Remove the coordinate from current path (backtracking), which may
+                            // go through a crossing twice, over and under it
                             if let Some(pos) = self
                                 .current_path
                                 .iter()
-                                .position(|&coord| coord == (*x, *y))
+                                .rposition(|&coord| coord == (*x, *y))
                             {
                                 let _ = self.current_path.remove(pos);
                             }
                         }
+                        AnimationStep::Visit(x, y, source) => {
+                            self.visited.push(((*x, *y), *source));
+                        }
+                        AnimationStep::Trace(x, y, source) => {
+                            self.traced.push(((*x, *y), *source));
+                        }
+                        AnimationStep::Reach(x, y, keys) => {
+                            self.reached.push(((*x, *y), *keys));
+                        }
+                        AnimationStep::Collect(x, y, key) => {
+                            self.collected.push(((*x, *y), *key));
+                        }
+                        AnimationStep::Jump(from, to) => {
+                            self.flash = Some((*from, *to));
+                        }
                     }
                 }
 
@@ -135,62 +459,445 @@ impl AnimationManager {
 /// This function performs depth-first search to explore the maze and records each step of the
 /// algorithm (forward moves and backtracking) for animated playback. It captures the exact
 /// sequence of the pathfinding algorithm's exploration from the entry point through the maze.
+/// Every node is entered at most once, the visited nodes being shared by every branch of the
+/// search, so that mazes with loops and open rooms are explored in linear time. The search stops at
+/// the first exit it reaches, which is added and removed at once, leaving the path leading to it
+/// drawn, and returns whether it reached one.
 pub(crate) fn record_animation_steps(
-    map_data: &[String],
-    start: (usize, usize),
-    current_path: &mut Vec<(usize, usize)>,
+    grid: &Grid,
+    start: NodeId,
+    visited: &mut HashSet<NodeId>,
     animation_steps: &mut Vec<AnimationStep>,
-) {
+) -> bool {
     // Record adding current position to path
-    current_path.push(start);
-    animation_steps.push(AnimationStep::Add(start.0, start.1));
-
-    // Check if we've reached an exit point ('4')
-    if let Some(row) = map_data.get(start.1) {
-        if let Some(cell) = row.as_bytes().get(start.0) {
-            if *cell == b'4' {
-                // Found exit - record removing position during backtrack
-                let _ = current_path.pop();
-                animation_steps.push(AnimationStep::Remove(start.0, start.1));
-                return;
-            }
+    let (x, y) = grid.position(start);
+    let _ = visited.insert(start);
+    animation_steps.push(AnimationStep::Add(x, y));
+
+    // Check if we've reached an exit point, where the search ends
+    if grid.cell(start) == Some(Cell::Exit) {
+        // Found exit - record removing position during backtrack
+        animation_steps.push(AnimationStep::Remove(x, y));
+        return true;
+    }
+
+    // Explore the neighbours of the cell (north, south, east, west on square cells), unless an
+    // arrow only allows one, then the other end of the portal on this cell, if any
+    for next in grid.moves(start) {
+        // Skip if already visited by any branch of the search
+        if visited.contains(&next) {
+            continue;
+        }
+
+        // Only explore floor cells and exits, leaving out entry points and doors
+        if matches!(grid.cell(next), Some(Cell::Entry | Cell::Door(_))) {
+            continue;
+        }
+
+        let to = grid.position(next);
+        if grid.is_jump((x, y), to) {
+            animation_steps.push(AnimationStep::Jump((x, y), to));
+        }
+
+        // Recursively explore from this position
+        if record_animation_steps(grid, next, visited, animation_steps) {
+            return true;
         }
     }
 
-    // Explore all four directions (north, south, east, west)
-    let directions = [(0_i32, -1_i32), (0, 1), (1, 0), (-1, 0)];
+    // Record removing position during backtrack
+    animation_steps.push(AnimationStep::Remove(x, y));
+    false
+}
+
+/// Returns the nodes next to the given one that a breadth-first search can move to.
+///
+/// Neighbors are returned in the same order the depth-first search explores them in, and every cell
+/// that isn't a wall or a door is walkable, including other entry points and terrain.
+fn open_neighbors(grid: &Grid, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    grid.moves(node)
+        .filter(|&next| grid.cell(next).and_then(Cell::cost).is_some())
+}
+
+/// Finds the shortest path from a cell to the nearest exit with a breadth-first search.
+///
+/// This function returns the path from the given cell to the exit inclusive, picking up the keys
+/// needed to open the doors on the way, or `None` if no exit can be reached.
+pub(crate) fn shortest_path(grid: &Grid, start: NodeId) -> Option<Path> {
+    state_search(grid, &[start], Solver::DepthFirst).path(grid)
+}
+
+/// Records animation steps for a breadth-first search from several entry points at once.
+///
+/// This function floods the maze from every entry point in lockstep, each cell being claimed by the
+/// entry point that reaches it first, until an exit is reached. The shortest path from every entry
+/// point to an exit is then traced, starting with the entry point that got out first.
+pub(crate) fn record_multi_source_steps(
+    grid: &Grid,
+    entries: &[NodeId],
+    animation_steps: &mut Vec<AnimationStep>,
+) -> SearchReport {
+    let mut owners = vec![None; grid.node_count()];
+    let mut queue = VecDeque::new();
+    for (source, &entry) in entries.iter().enumerate() {
+        if let Some(owner) = owners.get_mut(entry) {
+            *owner = Some(source);
+        }
+        queue.push_back(entry);
+        let (x, y) = grid.position(entry);
+        animation_steps.push(AnimationStep::Visit(x, y, source));
+    }
 
-    for (dx, dy) in directions {
-        // Calculate neighbor coordinates with proper bounds checking
-        let Some(new_x) = start.0.checked_add_signed(dx as isize) else {
+    let mut first = None;
+    'flood: while let Some(node) = queue.pop_front() {
+        let Some(&Some(source)) = owners.get(node) else {
             continue;
         };
-        let Some(new_y) = start.1.checked_add_signed(dy as isize) else {
+
+        for next in open_neighbors(grid, node) {
+            let Some(owner @ None) = owners.get_mut(next) else {
+                continue;
+            };
+
+            *owner = Some(source);
+            let (from, to) = (grid.position(node), grid.position(next));
+            if grid.is_jump(from, to) {
+                animation_steps.push(AnimationStep::Jump(from, to));
+            }
+            animation_steps.push(AnimationStep::Visit(to.0, to.1, source));
+            if grid.cell(next) == Some(Cell::Exit) {
+                first = Some(source);
+                break 'flood;
+            }
+            queue.push_back(next);
+        }
+    }
+
+    let paths = entries
+        .iter()
+        .map(|&entry| (grid.position(entry), shortest_path(grid, entry)))
+        .collect::<Vec<_>>();
+    record_traces(grid, &paths, first, animation_steps);
+
+    SearchReport { paths, first }
+}
+
+/// Records the tracing of the path out from every entry point, starting with the given one.
+///
+/// Keys met for the first time along a path are recorded as picked up right after the cell they lie
+/// on is traced.
+fn record_traces(
+    grid: &Grid,
+    paths: &[((usize, usize), Option<Path>)],
+    first: Option<usize>,
+    animation_steps: &mut Vec<AnimationStep>,
+) {
+    let others = (0..paths.len()).filter(|&source| Some(source) != first);
+    for source in first.into_iter().chain(others) {
+        let Some((_, Some(path))) = paths.get(source) else {
             continue;
         };
 
-        let new_pos = (new_x, new_y);
+        let mut keys = KeySet::default();
+        let mut previous = None;
+        for &(x, y) in path {
+            if let Some(from) = previous.filter(|&from| grid.is_jump(from, (x, y))) {
+                animation_steps.push(AnimationStep::Jump(from, (x, y)));
+            }
+            previous = Some((x, y));
+            animation_steps.push(AnimationStep::Trace(x, y, source));
+
+            if let Some(Cell::Key(key)) = grid.at((x, y)) {
+                if !keys.contains(key) {
+                    keys = keys.with(key);
+                    animation_steps.push(AnimationStep::Collect(x, y, key));
+                }
+            }
+        }
+    }
+}
+
+/// State of a search over keys and positions: the node stood on and the keys held there.
+type State = (NodeId, KeySet);
+
+/// Outcome of a search over keys and positions through a maze.
+struct StateSearch {
+    /// States in the order they were settled, along with the index of their entry point.
+    settled: Vec<(State, usize)>,
+    /// Exit state reached, along with the index of the entry point it was reached from, if any.
+    exit: Option<(State, usize)>,
+    /// State each reached state was last reached from, entry points having none.
+    parents: HashMap<State, State>,
+}
 
-        // Skip if already visited in current path
-        if current_path.contains(&new_pos) {
+impl StateSearch {
+    /// Returns the path leading from an entry point to the exit that was reached, if any.
+    fn path(&self, grid: &Grid) -> Option<Path> {
+        let (mut state, _) = self.exit?;
+        let mut path = vec![grid.position(state.0)];
+        while let Some(&parent) = self.parents.get(&state) {
+            path.push(grid.position(parent.0));
+            state = parent;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+/// Returns the states that can be moved to from the given one, along with the cost of the move.
+///
+/// Doors can only be passed while holding their key, stepping onto a key picks it up, arrows can
+/// only be left in the direction they point to, and jumping to the other end of a portal costs a
+/// single move. When `track_keys` is unset, keys are walked over without being picked up, which
+/// keeps the search from telling apart states that only differ by keys no door needs.
+fn next_states(grid: &Grid, (node, keys): State, track_keys: bool) -> Vec<(State, u32)> {
+    grid.moves(node)
+        .filter_map(|next| {
+            let cell = grid.cell(next)?;
+            let cost = match cell {
+                Cell::Door(key) => keys.contains(key).then_some(1)?,
+                _ => cell.cost()?,
+            };
+            let keys = match cell {
+                Cell::Key(key) if track_keys => keys.with(key),
+                _ => keys,
+            };
+
+            Some(((next, keys), cost))
+        })
+        .collect()
+}
+
+/// Searches a way out of a maze over keys and positions, from several cells at once.
+///
+/// This function runs Dijkstra's algorithm from every source at once, settling states in order of
+/// cost until an exit is settled. The depth-first solver ignores the cost of terrain, every move
+/// costing one, which turns the search into a breadth-first one. With A*, states are settled in
+/// order of their cost plus the number of moves left to the nearest exit instead, or to the nearest
+/// portal and on from the portal closest to an exit when that is fewer, as counted by
+/// [`Grid::distance`] for the shape of the cells and the floors in between. Since every move costs
+/// at least one, that estimate never exceeds the actual cost and the way out found is still the
+/// cheapest.
+fn state_search(grid: &Grid, sources: &[NodeId], solver: Solver) -> StateSearch {
+    let track_keys = grid.has_doors();
+    let positions = |nodes: Vec<NodeId>| -> Vec<(usize, usize)> {
+        if solver == Solver::AStar {
+            nodes.into_iter().map(|node| grid.position(node)).collect()
+        } else {
+            Vec::new()
+        }
+    };
+    let exits = positions(grid.exits());
+    let portals = positions(grid.portals());
+    let nearest = |targets: &[(usize, usize)], position: (usize, usize)| {
+        targets
+            .iter()
+            .map(|&target| grid.distance(position, target))
+            .min()
+    };
+    // A way out either goes straight to an exit or first walks to a portal and jumps through it
+    let beyond_portals = portals
+        .iter()
+        .filter_map(|&portal| nearest(&exits, portal))
+        .min();
+    let estimate = |(node, _): State| {
+        let position = grid.position(node);
+        let direct = nearest(&exits, position);
+        let through_portal =
+            nearest(&portals, position)
+                .zip(beyond_portals)
+                .map(|(to_portal, from_portal)| {
+                    to_portal.saturating_add(from_portal).saturating_add(1)
+                });
+        direct
+            .into_iter()
+            .chain(through_portal)
+            .min()
+            .map_or(0, |distance| u32::try_from(distance).unwrap_or(u32::MAX))
+    };
+
+    let mut best = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut settled = Vec::new();
+    let mut done = HashSet::new();
+    let mut heap = BinaryHeap::new();
+    for (source, &entry) in sources.iter().enumerate() {
+        let state = (entry, KeySet::default());
+        let _ = best.insert(state, 0);
+        heap.push(Reverse((estimate(state), 0_u32, state, source)));
+    }
+
+    while let Some(Reverse((_, cost, state, source))) = heap.pop() {
+        if !done.insert(state) {
             continue;
         }
+        settled.push((state, source));
+        if grid.cell(state.0) == Some(Cell::Exit) {
+            return StateSearch {
+                settled,
+                exit: Some((state, source)),
+                parents,
+            };
+        }
+
+        for (next, step) in next_states(grid, state, track_keys) {
+            let step = if solver == Solver::DepthFirst {
+                1
+            } else {
+                step
+            };
+            let next_cost = cost.saturating_add(step);
+            if best.get(&next).is_none_or(|&known| next_cost < known) {
+                let _ = best.insert(next, next_cost);
+                let _ = parents.insert(next, state);
+                heap.push(Reverse((
+                    next_cost.saturating_add(estimate(next)),
+                    next_cost,
+                    next,
+                    source,
+                )));
+            }
+        }
+    }
+
+    StateSearch {
+        settled,
+        exit: None,
+        parents,
+    }
+}
+
+/// Finds every node of a maze that can be walked to from the given ones.
+///
+/// Arrows make reachability one-sided, so a cell reachable from an entry point may not lead back to
+/// it. Doors are only passed once their key has been picked up on the way there.
+pub(crate) fn reachable(grid: &Grid, sources: &[NodeId]) -> HashSet<NodeId> {
+    let track_keys = grid.has_doors();
+    let mut seen = sources
+        .iter()
+        .map(|&source| (source, KeySet::default()))
+        .collect::<HashSet<_>>();
+    let mut queue = seen.iter().copied().collect::<VecDeque<_>>();
+    while let Some(state) = queue.pop_front() {
+        for (next, _) in next_states(grid, state, track_keys) {
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    seen.into_iter().map(|(node, _)| node).collect()
+}
+
+/// Finds the cheapest path from a cell to an exit with Dijkstra's algorithm.
+///
+/// This function returns the path from the given cell to the exit inclusive, taking the cost of the
+/// terrain crossed into account and picking up the keys needed to open the doors on the way, or
+/// `None` if no exit can be reached.
+pub(crate) fn cheapest_path(grid: &Grid, start: NodeId) -> Option<Path> {
+    state_search(grid, &[start], Solver::Dijkstra).path(grid)
+}
+
+/// Records animation steps for a search over keys and positions from every entry point at once.
+///
+/// This function settles states with the search matching the given solver, each state being claimed
+/// by the entry point it is reached from first, until an exit is settled. In mazes with doors, the
+/// states are recorded along with the keys held in them, and otherwise along with their entry
+/// point. The path from every entry point to an exit is then traced, starting with the entry point
+/// that got out first.
+pub(crate) fn record_state_steps(
+    grid: &Grid,
+    entries: &[NodeId],
+    solver: Solver,
+    animation_steps: &mut Vec<AnimationStep>,
+) -> SearchReport {
+    let search = state_search(grid, entries, solver);
+    let doors = grid.has_doors();
+    for &(state, source) in &search.settled {
+        let (node, keys) = state;
+        let (x, y) = grid.position(node);
+        if let Some(&(from, _)) = search.parents.get(&state) {
+            let from = grid.position(from);
+            if grid.is_jump(from, (x, y)) {
+                animation_steps.push(AnimationStep::Jump(from, (x, y)));
+            }
+        }
+        animation_steps.push(if doors {
+            AnimationStep::Reach(x, y, keys)
+        } else {
+            AnimationStep::Visit(x, y, source)
+        });
+    }
+
+    let first = search.exit.map(|(_, source)| source);
+    let paths = entries
+        .iter()
+        .enumerate()
+        .map(|(source, &entry)| {
+            let path = if Some(source) == first {
+                search.path(grid)
+            } else {
+                state_search(grid, &[entry], solver).path(grid)
+            };
+            (grid.position(entry), path)
+        })
+        .collect::<Vec<_>>();
+    record_traces(grid, &paths, first, animation_steps);
 
-        // Check if position is valid and walkable
-        if let Some(row) = map_data.get(new_pos.1) {
-            if let Some(cell) = row.chars().nth(new_pos.0) {
-                // Only explore walkable cells ('3') or exit ('4')
-                if matches!(cell, '3' | '4') {
-                    // Recursively explore from this position
-                    record_animation_steps(map_data, new_pos, current_path, animation_steps);
+    SearchReport { paths, first }
+}
+
+/// Returns the path the pathfinding algorithm found from the entry point to an exit.
+///
+/// This function replays the recorded animation steps until the first one reaching an exit cell,
+/// and returns the path held at that moment, from the entry point to the exit inclusive. Mazes
+/// whose exits the algorithm never reached yield `None`.
+pub(crate) fn solution_path(grid: &Grid, steps: &[AnimationStep]) -> Option<Vec<(usize, usize)>> {
+    let mut path = Vec::new();
+
+    for step in steps {
+        match *step {
+            AnimationStep::Add(x, y) => {
+                path.push((x, y));
+
+                if grid.at((x, y)) == Some(Cell::Exit) {
+                    return Some(path);
                 }
             }
+            AnimationStep::Remove(..) => {
+                let _ = path.pop();
+            }
+            AnimationStep::Visit(..)
+            | AnimationStep::Trace(..)
+            | AnimationStep::Reach(..)
+            | AnimationStep::Collect(..)
+            | AnimationStep::Jump(..) => {}
         }
     }
 
-    // Record removing position during backtrack
-    let _ = current_path.pop();
-    animation_steps.push(AnimationStep::Remove(start.0, start.1));
+    None
+}
+
+/// Returns the order in which the pathfinding algorithm first visited a maze cell.
+///
+/// This function counts the forward moves recorded in the animation steps up to and including the
+/// first one reaching the given cell, so that the entry point is visited at step 1. Cells the
+/// algorithm never reached yield `None`.
+pub(crate) fn visit_order(steps: &[AnimationStep], cell: (usize, usize)) -> Option<usize> {
+    steps
+        .iter()
+        .filter_map(|step| match step {
+            AnimationStep::Add(x, y)
+            | AnimationStep::Visit(x, y, _)
+            | AnimationStep::Reach(x, y, _) => Some((*x, *y)),
+            AnimationStep::Remove(..)
+            | AnimationStep::Trace(..)
+            | AnimationStep::Collect(..)
+            | AnimationStep::Jump(..) => None,
+        })
+        .position(|visited| visited == cell)
+        .map(|position| position + 1)
 }
 
 /// Transforms maze coordinates to screen coordinates for canvas rendering.
@@ -199,38 +906,64 @@ pub(crate) fn record_animation_steps(
 /// standard transformation formulas: coordinate[i] = (n - 1) / 2 - i for rows (ascending order) and
 /// coordinate[i] = i - (n - 1) / 2 for columns (descending order).
 ///
+/// Cells of the rows a hexagonal maze shifts are moved right by half a column. Every cell yields
+/// one point per canvas dot it spans, as given by
+/// [`Topology::dots_per_column`](crate::grid::Topology::dots_per_column), so that hexagonal cells
+/// are drawn twice as wide as their shift.
+///
 /// # Errors
 ///
 /// This function may return errors from coordinate conversion operations.
 pub(crate) fn transform_maze_to_screen_coords(
     maze_coords: &[(usize, usize)],
-    map_data: &[String],
+    grid: &Grid,
 ) -> Result<Vec<(f64, f64)>> {
-    let rows_n = f64::from(u16::try_from(map_data.len())?);
-    let cols_n = f64::from(u16::try_from(
-        map_data
-            .first()
-            .ok_or_eyre("failed to retrieve first element of map data")?
-            .len(),
-    )?);
-
-    maze_coords
-        .iter()
-        .map(|&(col, row)| {
-            // Row transformation: coordinate[i] = (n - 1) / 2 - i
-            let screen_y = (rows_n - 1.) / 2. - f64::from(u16::try_from(row)?);
-
-            // Column transformation: coordinate[i] = i - (n - 1) / 2
-            let screen_x = f64::from(u16::try_from(col)?) - (cols_n - 1.) / 2.;
+    ensure!(
+        grid.height() > 0,
+        "failed to retrieve first row of the maze"
+    );
+    let rows_n = f64::from(u16::try_from(grid.height())?);
+    let cols_n = f64::from(u16::try_from(grid.width())?);
+    let topology = grid.topology();
+    let dots = u16::try_from(topology.dots_per_column())?;
+
+    let mut screen_coords = Vec::with_capacity(maze_coords.len() * usize::from(dots));
+    for &(col, row) in maze_coords {
+        // Row transformation: coordinate[i] = (n - 1) / 2 - i
+        let screen_y = (rows_n - 1.) / 2. - f64::from(u16::try_from(row)?);
+
+        // Column transformation: coordinate[i] = i - (n - 1) / 2
+        let screen_x = f64::from(u16::try_from(col)?) + f64::from(topology.shift(row)) / 2.
+            - (cols_n - 1.) / 2.;
+
+        for dot in 0..dots {
+            screen_coords.push((screen_x + f64::from(dot) / f64::from(dots), screen_y));
+        }
+    }
 
-            Ok((screen_x, screen_y))
-        })
-        .collect()
+    Ok(screen_coords)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        braid::{self, BraidSettings},
+        grid::Topology,
+        map::{Map, Metadata},
+        time::ManualClock,
+    };
+
+    /// Builds the grid of the given rows, which tests expect to form one.
+    fn grid(map_data: &[String]) -> Grid {
+        Grid::new(map_data, Topology::Square).expect("the rows should form a grid")
+    }
+
+    /// Returns the node at the given coordinates, which tests expect to lie within the maze.
+    fn node(grid: &Grid, position: (usize, usize)) -> NodeId {
+        grid.node(position)
+            .expect("the cell should lie within the maze")
+    }
 
     #[test]
     fn test_animation_step_creation() {
@@ -358,6 +1091,27 @@ mod tests {
         assert!(manager.current_path.is_empty());
     }
 
+    #[test]
+    fn test_animation_manager_update_with_manual_clock() {
+        let clock = Rc::new(ManualClock::new());
+        let animation_clock: Rc<dyn Clock> = Rc::<ManualClock>::clone(&clock);
+        let mut manager = AnimationManager::with_clock(animation_clock);
+        manager.steps.push(AnimationStep::Add(1, 2));
+        manager.steps.push(AnimationStep::Add(1, 3));
+
+        manager.update();
+        assert_eq!(manager.current_index, 0);
+
+        clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+        manager.update();
+        manager.update();
+        assert_eq!(manager.current_index, 1);
+
+        clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+        manager.update();
+        assert_eq!(manager.current_path, vec![(1, 2), (1, 3)]);
+    }
+
     #[test]
     fn test_animation_manager_update_loop_restart() {
         let mut manager = AnimationManager::new();
@@ -381,12 +1135,19 @@ mod tests {
     fn test_record_animation_steps_with_exit() {
         let map_data = vec!["11111".to_owned(), "13341".to_owned(), "11111".to_owned()];
 
-        let mut current_path = Vec::new();
+        let mut visited = HashSet::new();
         let mut animation_steps = Vec::new();
 
-        record_animation_steps(&map_data, (1, 1), &mut current_path, &mut animation_steps);
+        let grid = grid(&map_data);
+        let found = record_animation_steps(
+            &grid,
+            node(&grid, (1, 1)),
+            &mut visited,
+            &mut animation_steps,
+        );
 
-        // Should have recorded some steps
+        // Should have recorded some steps on the way to the exit
+        assert!(found);
         assert!(!animation_steps.is_empty());
         // First step should be adding the start position
         match animation_steps
@@ -405,12 +1166,19 @@ mod tests {
     fn test_record_animation_steps_direct_exit() {
         let map_data = vec!["111".to_owned(), "141".to_owned(), "111".to_owned()];
 
-        let mut current_path = Vec::new();
+        let mut visited = HashSet::new();
         let mut animation_steps = Vec::new();
 
-        record_animation_steps(&map_data, (1, 1), &mut current_path, &mut animation_steps);
+        let grid = grid(&map_data);
+        let found = record_animation_steps(
+            &grid,
+            node(&grid, (1, 1)),
+            &mut visited,
+            &mut animation_steps,
+        );
 
         // Should add position then immediately remove it upon finding exit
+        assert!(found);
         assert_eq!(animation_steps.len(), 2);
         match animation_steps
             .first()
@@ -428,12 +1196,629 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_record_animation_steps_open_room() {
+        // The open rooms of dungeons hold countless routes, of which the search may only walk one
+        let map_data = (0..21)
+            .map(|y| {
+                (0..31)
+                    .map(|x| match (x, y) {
+                        (1, 1) => '1',
+                        (30, 19) => '4',
+                        (0 | 30, _) | (_, 0 | 20) => '2',
+                        _ => '3',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        let grid = grid(&map_data);
+        let cells = grid.cells().filter(|&(_, cell)| cell != Cell::Wall).count();
+        let mut steps = Vec::new();
+        assert!(record_animation_steps(
+            &grid,
+            node(&grid, (1, 1)),
+            &mut HashSet::new(),
+            &mut steps
+        ));
+        let forward = steps
+            .iter()
+            .filter(|step| matches!(step, AnimationStep::Add(..)))
+            .count();
+        assert!(forward <= cells);
+    }
+
+    #[test]
+    fn test_record_animation_steps_braided() {
+        // A comb of long teeth, braided into a maze of countless routes through its loops
+        let rows = (0..21)
+            .map(|y| {
+                (0..31)
+                    .map(|x| match (x, y) {
+                        (1, 1) => '1',
+                        (30, 1) => '4',
+                        (0 | 30, _) | (_, 0 | 20) => '2',
+                        (_, 1) => '3',
+                        _ if x % 2 == 1 => '3',
+                        _ => '2',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        let maze = Map {
+            key: "comb".to_owned(),
+            data: rows,
+            metadata: Metadata::default(),
+        };
+        let braid = braid::braid(
+            &maze,
+            &BraidSettings {
+                dead_ends: 100,
+                loops: 40,
+                seed: Some(3),
+            },
+        )
+        .expect("the maze should be braided");
+        assert_eq!(braid.loops, 40);
+
+        let grid = Grid::of(&braid.map).expect("the map should form a grid");
+        let cells = grid.cells().filter(|&(_, cell)| cell != Cell::Wall).count();
+        let entry = node(&grid, (1, 1));
+        let mut steps = Vec::new();
+
+        // The search enters every cell at most once and stops at the exit
+        assert!(record_animation_steps(
+            &grid,
+            entry,
+            &mut HashSet::new(),
+            &mut steps
+        ));
+        let forward = steps
+            .iter()
+            .filter(|step| matches!(step, AnimationStep::Add(..)))
+            .count();
+        assert!(forward <= cells);
+        assert!(solution_path(&grid, &steps).is_some());
+    }
+
+    #[test]
+    fn test_solution_path() {
+        let map_data = vec![
+            "22222".to_owned(),
+            "21332".to_owned(),
+            "23232".to_owned(),
+            "22242".to_owned(),
+        ];
+
+        let grid = grid(&map_data);
+        let entry = *grid
+            .entries()
+            .first()
+            .expect("the maze should have an entry point");
+        let mut steps = Vec::new();
+        assert!(record_animation_steps(
+            &grid,
+            entry,
+            &mut HashSet::new(),
+            &mut steps
+        ));
+
+        assert_eq!(grid.position(entry), (1, 1));
+        assert_eq!(
+            solution_path(&grid, &steps),
+            Some(vec![(1, 1), (2, 1), (3, 1), (3, 2), (3, 3)])
+        );
+        let unfinished = steps
+            .get(..2)
+            .expect("the solver should take several steps");
+        assert_eq!(solution_path(&grid, unfinished), None);
+    }
+
+    #[test]
+    fn test_record_multi_source_steps() {
+        let map_data = vec![
+            "2222222".to_owned(),
+            "2133312".to_owned(),
+            "2222232".to_owned(),
+            "2222242".to_owned(),
+        ];
+
+        let grid = grid(&map_data);
+        let entries = grid.entries();
+        let mut steps = Vec::new();
+        let report = record_multi_source_steps(&grid, &entries, &mut steps);
+
+        assert_eq!(entries, vec![node(&grid, (1, 1)), node(&grid, (5, 1))]);
+        assert_eq!(report.first, Some(1));
+        assert_eq!(
+            report.paths,
+            vec![
+                (
+                    (1, 1),
+                    Some(vec![(1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (5, 2), (5, 3)])
+                ),
+                ((5, 1), Some(vec![(5, 1), (5, 2), (5, 3)])),
+            ]
+        );
+        assert_eq!(
+            report.summary(),
+            "entry 2 at (5, 1) is out first, in 2 moves"
+        );
+        assert_eq!(
+            report.describe_entry((1, 1)).as_deref(),
+            Some("entry 1 at (1, 1): out in 6 moves")
+        );
+        assert_eq!(report.describe_entry((2, 1)), None);
+
+        // The flood is claimed cell by cell, then the winner's path is traced first
+        assert_eq!(
+            steps.get(..4),
+            Some(
+                [
+                    AnimationStep::Visit(1, 1, 0),
+                    AnimationStep::Visit(5, 1, 1),
+                    AnimationStep::Visit(2, 1, 0),
+                    AnimationStep::Visit(5, 2, 1),
+                ]
+                .as_slice()
+            )
+        );
+        let traced = steps
+            .iter()
+            .filter_map(|step| match step {
+                AnimationStep::Trace(x, y, source) => Some(((*x, *y), *source)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(traced.first(), Some(&((5, 1), 1)));
+        assert_eq!(traced.len(), 10);
+    }
+
+    #[test]
+    fn test_record_multi_source_steps_without_exit() {
+        let map_data = vec!["22222".to_owned(), "21312".to_owned(), "22222".to_owned()];
+
+        let mut steps = Vec::new();
+        let grid = grid(&map_data);
+        let report = record_multi_source_steps(&grid, &grid.entries(), &mut steps);
+
+        assert_eq!(report.first, None);
+        assert_eq!(report.summary(), "no entry point reaches an exit");
+        assert_eq!(
+            report.describe_entry((3, 1)).as_deref(),
+            Some("entry 2 at (3, 1): no way out")
+        );
+        assert!(steps
+            .iter()
+            .all(|step| matches!(step, AnimationStep::Visit(..))));
+    }
+
+    /// Creates a maze whose shortest way out wades through mud, while the cheapest one goes around.
+    fn create_muddy_maze() -> Vec<String> {
+        vec![
+            "2222222".to_owned(),
+            "2177734".to_owned(),
+            "2322232".to_owned(),
+            "2333332".to_owned(),
+            "2222222".to_owned(),
+        ]
+    }
+
+    #[test]
+    fn test_record_state_steps_cost_aware() {
+        let grid = grid(&create_muddy_maze());
+        let entries = grid.entries();
+        let detour = Route { steps: 9, cost: 9 };
+
+        for solver in [Solver::Dijkstra, Solver::AStar] {
+            let mut steps = Vec::new();
+            let report = record_state_steps(&grid, &entries, solver, &mut steps);
+
+            assert_eq!(report.first, Some(0));
+            assert_eq!(
+                report.found().and_then(|path| Route::of(&grid, path)),
+                Some(detour)
+            );
+            assert_eq!(steps.first(), Some(&AnimationStep::Visit(1, 1, 0)));
+            assert_eq!(steps.last(), Some(&AnimationStep::Trace(6, 1, 0)));
+        }
+    }
+
+    #[test]
+    fn test_state_search_guided() {
+        let map_data = vec![
+            "2222222222".to_owned(),
+            "2333333332".to_owned(),
+            "2133333334".to_owned(),
+            "2333333332".to_owned(),
+            "2222222222".to_owned(),
+        ];
+
+        let grid = grid(&map_data);
+        let entry = node(&grid, (1, 2));
+        let dijkstra = state_search(&grid, &[entry], Solver::Dijkstra);
+        let astar = state_search(&grid, &[entry], Solver::AStar);
+
+        // A* is drawn towards the exit, so it settles fewer cells for an equally cheap way out
+        assert_eq!(dijkstra.path(&grid).map(|path| path.len()), Some(9));
+        assert_eq!(astar.path(&grid).map(|path| path.len()), Some(9));
+        assert!(astar.settled.len() < dijkstra.settled.len());
+    }
+
+    #[test]
+    fn test_record_keys_and_doors() {
+        let map_data = vec![
+            "222222".to_owned(),
+            "21A334".to_owned(),
+            "232222".to_owned(),
+            "2a2222".to_owned(),
+            "222222".to_owned(),
+        ];
+        let fetch_key = vec![
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (1, 2),
+            (1, 1),
+            (2, 1),
+            (3, 1),
+            (4, 1),
+            (5, 1),
+        ];
+
+        for solver in [Solver::DepthFirst, Solver::Dijkstra, Solver::AStar] {
+            let mut manager = AnimationManager::new();
+            manager.solver = solver;
+            manager
+                .record(grid(&map_data))
+                .expect("the maze has an entry point");
+
+            assert_eq!(manager.found.as_ref(), Some(&fetch_key));
+            assert_eq!(manager.stats.found, Some(Route { steps: 8, cost: 8 }));
+            assert!(manager
+                .steps
+                .contains(&AnimationStep::Reach(1, 1, KeySet::default().with(0))));
+            assert!(manager.steps.contains(&AnimationStep::Collect(1, 3, 0)));
+        }
+
+        // Without its key, the door can't be opened
+        let locked = map_data
+            .iter()
+            .map(|row| row.replace('a', "3"))
+            .collect::<Vec<_>>();
+        let mut manager = AnimationManager::new();
+        manager
+            .record(grid(&locked))
+            .expect("the maze has an entry point");
+        assert_eq!(manager.found, None);
+        assert_eq!(manager.stats, RouteStats::default());
+    }
+
+    #[test]
+    fn test_record_floors() {
+        let map_data = [
+            "22222", "21+22", "22222", "===", "22222", "22-+2", "22222", "===", "22222", "233-2",
+            "24222",
+        ]
+        .map(str::to_owned);
+        let up_two_floors = vec![
+            (1, 1),
+            (2, 1),
+            (7, 1),
+            (8, 1),
+            (13, 1),
+            (12, 1),
+            (11, 1),
+            (11, 2),
+        ];
+
+        let maze = grid(&map_data);
+        assert_eq!(
+            shortest_path(&maze, node(&maze, (1, 1))),
+            Some(up_two_floors.clone())
+        );
+        for solver in [Solver::DepthFirst, Solver::Dijkstra, Solver::AStar] {
+            let mut manager = AnimationManager::new();
+            manager.solver = solver;
+            manager
+                .record(grid(&map_data))
+                .expect("the maze has an entry point");
+
+            assert_eq!(manager.found.as_ref(), Some(&up_two_floors));
+            assert_eq!(manager.stats.found, Some(Route { steps: 7, cost: 7 }));
+            assert!(manager.steps.contains(&AnimationStep::Jump((2, 1), (7, 1))));
+            assert!(manager
+                .steps
+                .contains(&AnimationStep::Jump((8, 1), (13, 1))));
+        }
+    }
+
+    #[test]
+    fn test_record_shortcut() {
+        // The depth-first search goes down and around, while the way out straight ahead is shorter
+        let map_data = ["2222222", "2133334", "2322232", "2333332", "2222222"].map(str::to_owned);
+
+        let mut manager = AnimationManager::new();
+        manager
+            .record(grid(&map_data))
+            .expect("the maze has an entry point");
+        assert_eq!(manager.found.as_ref().map(Vec::len), Some(10));
+        assert_eq!(manager.shortcut, vec![(2, 1), (3, 1), (4, 1)]);
+
+        // The breadth-first way out is the shortest one, which leaves no shortcut
+        manager.solver = Solver::Dijkstra;
+        manager
+            .record(grid(&map_data))
+            .expect("the maze has an entry point");
+        assert_eq!(manager.found.as_ref().map(Vec::len), Some(6));
+        assert!(manager.shortcut.is_empty());
+
+        manager.clear();
+        assert!(manager.shortcut.is_empty());
+    }
+
+    #[test]
+    fn test_record_crossings() {
+        let map_data = [
+            "2222222", "2221222", "433#332", "2223232", "2223332", "2222222",
+        ]
+        .map(str::to_owned);
+        // The way out goes under the crossing first, then back over it
+        let around_and_over = vec![
+            (3, 1),
+            (3, 2),
+            (3, 3),
+            (3, 4),
+            (4, 4),
+            (5, 4),
+            (5, 3),
+            (5, 2),
+            (4, 2),
+            (3, 2),
+            (2, 2),
+            (1, 2),
+            (0, 2),
+        ];
+
+        let maze = grid(&map_data);
+        assert_eq!(
+            shortest_path(&maze, node(&maze, (3, 1))),
+            Some(around_and_over.clone())
+        );
+        for solver in [Solver::DepthFirst, Solver::Dijkstra, Solver::AStar] {
+            let mut manager = AnimationManager::new();
+            manager.solver = solver;
+            manager
+                .record(grid(&map_data))
+                .expect("the maze has an entry point");
+
+            assert_eq!(manager.found.as_ref(), Some(&around_and_over));
+            assert_eq!(
+                manager.stats.found,
+                Some(Route {
+                    steps: 12,
+                    cost: 12
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_portals() {
+        let map_data = vec![
+            "2222222".to_owned(),
+            "21p22p4".to_owned(),
+            "2322232".to_owned(),
+            "2333332".to_owned(),
+            "2222222".to_owned(),
+        ];
+        let through_portal = vec![(1, 1), (2, 1), (5, 1), (6, 1)];
+
+        let maze = grid(&map_data);
+        assert_eq!(
+            shortest_path(&maze, node(&maze, (1, 1))),
+            Some(through_portal.clone())
+        );
+        for solver in [Solver::Dijkstra, Solver::AStar] {
+            let mut manager = AnimationManager::new();
+            manager.solver = solver;
+            manager
+                .record(grid(&map_data))
+                .expect("the maze has an entry point");
+
+            assert_eq!(manager.found.as_ref(), Some(&through_portal));
+            assert_eq!(manager.stats.found, Some(Route { steps: 3, cost: 3 }));
+            assert!(manager.steps.contains(&AnimationStep::Jump((2, 1), (5, 1))));
+        }
+
+        // The depth-first search jumps as well once the only way out is through the portal
+        let walled = vec![
+            "2222222".to_owned(),
+            "21p22p4".to_owned(),
+            "2222222".to_owned(),
+        ];
+        let clock = Rc::new(ManualClock::new());
+        let animation_clock: Rc<dyn Clock> = Rc::<ManualClock>::clone(&clock);
+        let mut manager = AnimationManager::with_clock(animation_clock);
+        manager
+            .record(grid(&walled))
+            .expect("the maze has an entry point");
+        assert_eq!(manager.found.as_ref(), Some(&through_portal));
+        assert_eq!(visit_order(&manager.steps, (5, 1)), Some(3));
+
+        // Jumps flash both ends of the portal until the next step
+        manager.current_index = manager
+            .steps
+            .iter()
+            .position(|step| matches!(step, AnimationStep::Jump(..)))
+            .expect("the solver should jump through the portal");
+        clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+        manager.update();
+        assert_eq!(manager.flash, Some(((2, 1), (5, 1))));
+        clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+        manager.update();
+        assert_eq!(manager.flash, None);
+    }
+
+    #[test]
+    fn test_state_search_guided_through_portals() {
+        // The exit is far down the corridor, but the portal below the entry point lands next to it
+        let map_data = vec![
+            "2".repeat(22),
+            format!("21{}4", "3".repeat(19)),
+            format!("2p{}p2", "2".repeat(18)),
+            "2".repeat(22),
+        ];
+        let through_portal = vec![(1, 1), (1, 2), (20, 2), (20, 1), (21, 1)];
+
+        let grid = grid(&map_data);
+        for solver in [Solver::DepthFirst, Solver::Dijkstra, Solver::AStar] {
+            assert_eq!(
+                state_search(&grid, &[node(&grid, (1, 1))], solver).path(&grid),
+                Some(through_portal.clone()),
+                "{} should jump through the portal",
+                solver.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_one_way_cells() {
+        // The arrow next to the entry point only leads back to it, so the way out goes around
+        let map_data = vec![
+            "2222222".to_owned(),
+            "21<3334".to_owned(),
+            "2322232".to_owned(),
+            "2333332".to_owned(),
+            "2222222".to_owned(),
+        ];
+        let around = vec![
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (2, 3),
+            (3, 3),
+            (4, 3),
+            (5, 3),
+            (5, 2),
+            (5, 1),
+            (6, 1),
+        ];
+
+        for solver in [Solver::DepthFirst, Solver::Dijkstra, Solver::AStar] {
+            let mut manager = AnimationManager::new();
+            manager.solver = solver;
+            manager
+                .record(grid(&map_data))
+                .expect("the maze has an entry point");
+
+            assert_eq!(manager.found.as_ref(), Some(&around));
+        }
+        let grid = grid(&map_data);
+        assert_eq!(shortest_path(&grid, node(&grid, (1, 1))), Some(around));
+    }
+
+    #[test]
+    fn test_record_hex_cells() {
+        // The entry point only touches the open cell below it diagonally
+        let map_data = ["22222", "21222", "22322", "22322", "22422"].map(str::to_owned);
+        let diagonal = vec![(1, 1), (2, 2), (2, 3), (2, 4)];
+
+        for solver in [Solver::DepthFirst, Solver::Dijkstra, Solver::AStar] {
+            let mut manager = AnimationManager::new();
+            manager.solver = solver;
+            manager
+                .record(Grid::new(&map_data, Topology::Hex).expect("the rows form a grid"))
+                .expect("the maze has an entry point");
+            assert_eq!(manager.found.as_ref(), Some(&diagonal));
+
+            manager
+                .record(grid(&map_data))
+                .expect("the maze has an entry point");
+            assert_eq!(manager.found, None);
+        }
+    }
+
+    #[test]
+    fn test_reachable_one_way() {
+        let grid = grid(&["22222", "23>32", "22222"].map(str::to_owned));
+        let reached_from = |position| {
+            reachable(&grid, &[node(&grid, position)])
+                .into_iter()
+                .map(|reached| grid.position(reached))
+                .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(
+            reached_from((1, 1)),
+            HashSet::from([(1, 1), (2, 1), (3, 1)])
+        );
+        assert_eq!(reached_from((3, 1)), HashSet::from([(2, 1), (3, 1)]));
+    }
+
+    #[test]
+    fn test_route_stats() {
+        let map_data = create_muddy_maze();
+        let mut manager = AnimationManager::new();
+
+        for solver in [Solver::DepthFirst, Solver::Dijkstra, Solver::AStar] {
+            manager.solver = solver;
+            manager
+                .record(grid(&map_data))
+                .expect("the maze has an entry point");
+
+            assert!(manager.report.is_none());
+            assert_eq!(
+                manager.stats,
+                RouteStats {
+                    found: Some(Route { steps: 9, cost: 9 }),
+                    shortest: Some(Route { steps: 5, cost: 17 }),
+                    cheapest: Some(Route { steps: 9, cost: 9 }),
+                }
+            );
+        }
+        assert_eq!(Solver::AStar.next(), Solver::DepthFirst);
+    }
+
+    #[test]
+    fn test_animation_manager_record() {
+        let clock = Rc::new(ManualClock::new());
+        let animation_clock: Rc<dyn Clock> = Rc::<ManualClock>::clone(&clock);
+        let mut manager = AnimationManager::with_clock(animation_clock);
+
+        let single = vec!["2222".to_owned(), "2134".to_owned(), "2222".to_owned()];
+        manager
+            .record(grid(&single))
+            .expect("the maze has an entry point");
+        assert!(manager.report.is_none());
+        assert_eq!(manager.steps.first(), Some(&AnimationStep::Add(1, 1)));
+
+        let several = vec!["22222".to_owned(), "21314".to_owned(), "22222".to_owned()];
+        manager
+            .record(grid(&several))
+            .expect("the maze has entry points");
+        assert_eq!(
+            manager.report.as_ref().and_then(|report| report.first),
+            Some(1)
+        );
+
+        clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+        manager.update();
+        clock.advance(Duration::from_millis(ANIMATION_FRAME_DELAY_MS));
+        manager.update();
+        assert_eq!(manager.visited, vec![((1, 1), 0), ((3, 1), 1)]);
+        assert_eq!(manager.head(), Some((3, 1)));
+
+        let none = vec!["2222".to_owned(), "2334".to_owned(), "2222".to_owned()];
+        assert!(manager.record(grid(&none)).is_err());
+    }
+
     #[test]
     fn test_transform_maze_to_screen_coords_basic() {
         let map_data = vec!["111".to_owned(), "131".to_owned(), "111".to_owned()];
 
         let maze_coords = vec![(1, 1)];
-        let result = transform_maze_to_screen_coords(&maze_coords, &map_data)
+        let result = transform_maze_to_screen_coords(&maze_coords, &grid(&map_data))
             .expect("Transform should work with valid data");
 
         assert_eq!(result.len(), 1);
@@ -458,7 +1843,7 @@ mod tests {
         ];
 
         let maze_coords = vec![(0, 0), (2, 2), (4, 4)];
-        let result = transform_maze_to_screen_coords(&maze_coords, &map_data)
+        let result = transform_maze_to_screen_coords(&maze_coords, &grid(&map_data))
             .expect("Transform should work with valid data");
 
         assert_eq!(result.len(), 3);
@@ -492,21 +1877,50 @@ mod tests {
         let map_data = vec!["111".to_owned(), "131".to_owned(), "111".to_owned()];
 
         let maze_coords = vec![];
-        let result = transform_maze_to_screen_coords(&maze_coords, &map_data)
+        let result = transform_maze_to_screen_coords(&maze_coords, &grid(&map_data))
             .expect("Transform should work with empty input");
 
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_transform_maze_to_screen_coords_hex() {
+        let grid = Grid::new(&["333", "333"], Topology::Hex).expect("the rows form a grid");
+
+        let result = transform_maze_to_screen_coords(&[(0, 0), (0, 1)], &grid)
+            .expect("Transform should work with valid data");
+
+        // Hexagonal cells span two dots, and the cells of odd rows are shifted by one of them
+        assert_eq!(
+            result,
+            vec![(-1.0, 0.5), (-0.5, 0.5), (-0.5, -0.5), (0.0, -0.5)]
+        );
+    }
+
     #[test]
     fn test_transform_maze_to_screen_coords_error_empty_map() {
-        let map_data: Vec<String> = vec![];
         let maze_coords = vec![(0, 0)];
 
-        let result = transform_maze_to_screen_coords(&maze_coords, &map_data);
+        let result = transform_maze_to_screen_coords(&maze_coords, &Grid::default());
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_visit_order() {
+        let steps = vec![
+            AnimationStep::Add(1, 1),
+            AnimationStep::Add(2, 1),
+            AnimationStep::Remove(2, 1),
+            AnimationStep::Add(1, 2),
+            AnimationStep::Add(2, 1),
+        ];
+
+        assert_eq!(visit_order(&steps, (1, 1)), Some(1));
+        assert_eq!(visit_order(&steps, (2, 1)), Some(2));
+        assert_eq!(visit_order(&steps, (1, 2)), Some(3));
+        assert_eq!(visit_order(&steps, (5, 5)), None);
+    }
+
     #[test]
     fn test_animation_frame_delay_constant() {
         assert_eq!(ANIMATION_FRAME_DELAY_MS, 200);
@@ -0,0 +1,28 @@
+//! Teleporter pairs.
+//!
+//! This module describes the portal cells of a maze, marked with the letters 'p' to 'u'. Each
+//! letter marks a pair of portals, and stepping onto one lets the walker jump to the other one for
+//! the cost of a single move, without going through the cells in between.
+
+/// Number of distinct portal pairs a maze can hold.
+pub(crate) const PAIR_COUNT: u8 = 6;
+
+/// Colour portals are drawn in, as RGB components.
+pub(crate) const PORTAL_COLOUR: [u8; 3] = [0x00, 0xc8, 0xc8];
+
+/// Returns the index of the pair the portal on the given cell belongs to, if it holds one.
+pub(crate) fn portal(cell: u8) -> Option<u8> {
+    cell.checked_sub(b'p').filter(|&pair| pair < PAIR_COUNT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portal_pairs() {
+        assert_eq!(portal(b'p'), Some(0));
+        assert_eq!(portal(b'u'), Some(5));
+        assert_eq!(portal(b'v'), None);
+    }
+}
@@ -0,0 +1,72 @@
+//! Keys and doors puzzle cells.
+//!
+//! This module describes the lettered keys ('a' to 'e') lying in a maze and the matching doors ('A'
+//! to 'E') that only open once their key has been picked up. Since whether a door can be passed
+//! depends on the keys collected on the way there, mazes with doors are solved by searching over
+//! positions and sets of keys held together.
+
+/// Number of distinct keys, and of matching doors, a maze can hold.
+pub(crate) const KEY_COUNT: u8 = 5;
+
+/// Colour keys are drawn in, as RGB components.
+pub(crate) const KEY_COLOUR: [u8; 3] = [0xff, 0xd7, 0x00];
+
+/// Colour doors are drawn in, as RGB components.
+pub(crate) const DOOR_COLOUR: [u8; 3] = [0xb0, 0x40, 0xff];
+
+/// Set of keys held while walking through a maze.
+///
+/// Each key is stored as a bit, the key 'a' being the lowest one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct KeySet(u8);
+
+impl KeySet {
+    /// Returns this set with the given key added to it.
+    pub(crate) const fn with(self, key: u8) -> Self {
+        Self(self.0 | 1 << key)
+    }
+
+    /// Returns whether the given key is part of this set.
+    pub(crate) const fn contains(self, key: u8) -> bool {
+        self.0 & 1 << key != 0
+    }
+
+    /// Returns the number of keys in this set.
+    pub(crate) const fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+/// Returns the index of the key lying on the given cell, if it holds one.
+pub(crate) fn key(cell: u8) -> Option<u8> {
+    cell.checked_sub(b'a').filter(|&key| key < KEY_COUNT)
+}
+
+/// Returns the index of the key opening the door on the given cell, if it holds one.
+pub(crate) fn door(cell: u8) -> Option<u8> {
+    cell.checked_sub(b'A').filter(|&key| key < KEY_COUNT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_and_doors() {
+        assert_eq!(key(b'a'), Some(0));
+        assert_eq!(key(b'e'), Some(4));
+        assert_eq!(key(b'f'), None);
+        assert_eq!(door(b'C'), Some(2));
+        assert_eq!(door(b'c'), None);
+    }
+
+    #[test]
+    fn test_key_set() {
+        let keys = KeySet::default().with(0).with(3).with(3);
+
+        assert!(keys.contains(0));
+        assert!(keys.contains(3));
+        assert!(!keys.contains(1));
+        assert_eq!(keys.len(), 2);
+    }
+}
@@ -0,0 +1,552 @@
+//! Recording and replaying of input sessions.
+//!
+//! This module contains the plain-text format in which input events are logged, along with the
+//! recorder writing them to disk as they happen and the replay feeding them back to the event
+//! handlers, either in a live session or headlessly against an off-screen terminal.
+
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{LineWriter, Write as _},
+    path::Path,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::{bail, eyre, OptionExt as _, Result, WrapErr as _};
+use ratatui::{
+    backend::TestBackend,
+    crossterm::event::{Event, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    Terminal,
+};
+
+use crate::{
+    events::POLL_TIMEOUT,
+    input::EventQueue,
+    keymap,
+    pathfinding::AnimationManager,
+    time::{Clock, ManualClock},
+    types::Screen,
+    App,
+};
+
+/// First line of every recording, identifying the format of the file.
+pub(crate) const HEADER: &str = "# labyrintuine input recording";
+
+/// Size of the off-screen terminal used by headless replays until a resize event is replayed.
+pub(crate) const HEADLESS_SIZE: (u16, u16) = (80, 24);
+
+/// Writer logging input events to a file as they happen.
+///
+/// This structure writes every event on its own line, prefixed with the amount of milliseconds
+/// elapsed since the recording started. Lines are flushed as soon as they're written, so that the
+/// recording survives the application crashing.
+pub(crate) struct Recorder {
+    /// File the events are written to.
+    writer: LineWriter<File>,
+    /// Instant the recording started at.
+    start: Instant,
+}
+
+impl Recorder {
+    /// Creates a recorder writing to the file at the given path, truncating it if it exists.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file can't be created.
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .wrap_err_with(|| format!("failed to create recording {}", path.display()))?;
+        let mut writer = LineWriter::new(file);
+        writeln!(writer, "{HEADER}")?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// Logs an event along with the time elapsed since the recording started.
+    ///
+    /// Events that can't be represented in the recording format, such as focus changes or key
+    /// presses of keys that can't be bound, are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the event can't be written to the file.
+    pub(crate) fn record(&mut self, event: &Event) -> Result<()> {
+        if let Some(event) = format_event(event) {
+            writeln!(self.writer, "{} {event}", self.start.elapsed().as_millis())
+                .wrap_err("failed to write to the input recording")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Sequence of recorded input events to be fed back to the event handlers.
+///
+/// This structure holds the events that haven't been replayed yet, each along with the time at
+/// which it should be replayed relative to the start of the replay.
+#[derive(Debug)]
+pub(crate) struct Replay {
+    /// Events pending to be replayed, in order.
+    events: VecDeque<(Duration, Event)>,
+    /// Instant the replay started at.
+    start: Instant,
+}
+
+impl Replay {
+    /// Loads a recording from the file at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file can't be read or parsed.
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read recording {}", path.display()))?;
+
+        Self::parse(&contents)
+            .wrap_err_with(|| format!("failed to parse recording {}", path.display()))
+    }
+
+    /// Parses a recording from its contents.
+    ///
+    /// Blank lines and lines starting with '#' are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error pointing at the first line that can't be parsed.
+    pub(crate) fn parse(contents: &str) -> Result<Self> {
+        let events = contents
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(idx, line)| {
+                parse_line(line).wrap_err_with(|| format!("invalid event on line {}", idx + 1))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            events,
+            start: Instant::now(),
+        })
+    }
+
+    /// Returns the time left until the next event is due, if there's any event left.
+    pub(crate) fn time_until_next(&self) -> Option<Duration> {
+        self.events
+            .front()
+            .map(|&(time, _)| time.saturating_sub(self.start.elapsed()))
+    }
+
+    /// Takes the next event if it's due to be replayed.
+    pub(crate) fn next_due(&mut self) -> Option<Event> {
+        if self.time_until_next()? > Duration::ZERO {
+            return None;
+        }
+
+        self.pop()
+    }
+
+    /// Takes the next event regardless of when it's due.
+    pub(crate) fn pop(&mut self) -> Option<Event> {
+        self.pop_timed().map(|(_, event)| event)
+    }
+
+    /// Takes the next event regardless of when it's due, along with the time it's due at.
+    pub(crate) fn pop_timed(&mut self) -> Option<(Duration, Event)> {
+        self.events.pop_front()
+    }
+}
+
+/// Parses a single line of a recording into the time it's due at and its event.
+fn parse_line(line: &str) -> Result<(Duration, Event)> {
+    let (time, event) = line
+        .split_once(' ')
+        .ok_or_else(|| eyre!("expected `<milliseconds> <event>`, found `{line}`"))?;
+    let time = time
+        .parse()
+        .wrap_err_with(|| format!("invalid timestamp `{time}`"))?;
+
+    Ok((Duration::from_millis(time), parse_event(event)?))
+}
+
+/// Formats an event as written in a recording, if it can be represented in it.
+pub(crate) fn format_event(event: &Event) -> Option<String> {
+    match event {
+        Event::Key(key) => keymap::key_token(key.code).map(|token| format!("key {token}")),
+        Event::Mouse(mouse) => {
+            let kind = match mouse.kind {
+                MouseEventKind::Down(button) => format!("down-{}", button_name(button)),
+                MouseEventKind::Up(button) => format!("up-{}", button_name(button)),
+                MouseEventKind::Drag(button) => format!("drag-{}", button_name(button)),
+                MouseEventKind::Moved => "moved".to_owned(),
+                MouseEventKind::ScrollDown => "scroll-down".to_owned(),
+                MouseEventKind::ScrollUp => "scroll-up".to_owned(),
+                MouseEventKind::ScrollLeft => "scroll-left".to_owned(),
+                MouseEventKind::ScrollRight => "scroll-right".to_owned(),
+            };
+
+            Some(format!("mouse {kind} {} {}", mouse.column, mouse.row))
+        }
+        Event::Resize(width, height) => Some(format!("resize {width} {height}")),
+        Event::FocusGained | Event::FocusLost | Event::Paste(_) => None,
+    }
+}
+
+/// Parses an event as written in a recording.
+///
+/// # Errors
+///
+/// This function returns an error if the event isn't in the recording format.
+pub(crate) fn parse_event(event: &str) -> Result<Event> {
+    let mut fields = event.split(' ');
+    let kind = fields.next().unwrap_or_default();
+    let arguments = fields.collect::<Vec<_>>();
+
+    match (kind, arguments.as_slice()) {
+        ("key", &[token]) => {
+            let code = keymap::parse_key(token).ok_or_else(|| eyre!("unknown key `{token}`"))?;
+            Ok(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+        }
+        ("mouse", &[kind, column, row]) => Ok(Event::Mouse(MouseEvent {
+            kind: parse_mouse_kind(kind)?,
+            column: column.parse()?,
+            row: row.parse()?,
+            modifiers: KeyModifiers::NONE,
+        })),
+        ("resize", &[width, height]) => Ok(Event::Resize(width.parse()?, height.parse()?)),
+        _ => bail!("unknown event `{event}`"),
+    }
+}
+
+/// Returns the name of a mouse button as written in a recording.
+const fn button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    }
+}
+
+/// Parses the kind of a mouse event as written in a recording.
+fn parse_mouse_kind(kind: &str) -> Result<MouseEventKind> {
+    let button = |name: &str| match name {
+        "left" => Ok(MouseButton::Left),
+        "right" => Ok(MouseButton::Right),
+        "middle" => Ok(MouseButton::Middle),
+        _ => Err(eyre!("unknown mouse button `{name}`")),
+    };
+
+    match kind.split_once('-') {
+        Some(("down", name)) => Ok(MouseEventKind::Down(button(name)?)),
+        Some(("up", name)) => Ok(MouseEventKind::Up(button(name)?)),
+        Some(("drag", name)) => Ok(MouseEventKind::Drag(button(name)?)),
+        Some(("scroll", "down")) => Ok(MouseEventKind::ScrollDown),
+        Some(("scroll", "up")) => Ok(MouseEventKind::ScrollUp),
+        Some(("scroll", "left")) => Ok(MouseEventKind::ScrollLeft),
+        Some(("scroll", "right")) => Ok(MouseEventKind::ScrollRight),
+        None if kind == "moved" => Ok(MouseEventKind::Moved),
+        _ => bail!("unknown mouse event `{kind}`"),
+    }
+}
+
+/// Replays every pending event against an off-screen terminal and checks the final state.
+///
+/// This function runs the same loop iterations as a live session, so that the handlers see the same
+/// layout they would on screen. Time is simulated with a manual clock: between two events, the
+/// clock is advanced in steps of at most [`POLL_TIMEOUT`] with a frame drawn after each one, just
+/// like the main loop redraws after every poll timeout, which makes the solver animation advance
+/// deterministically. Once every event has been replayed or the application exits, the final state
+/// of the application is compared against the given `(field, value)` expectations.
+///
+/// # Errors
+///
+/// This function returns fatal errors from the event handlers, and an error listing every
+/// expectation that didn't hold.
+pub(crate) fn run_headless(app: &mut App, expectations: &[(String, String)]) -> Result<()> {
+    let mut replay = app.replay.take().ok_or_eyre("no recording to replay")?;
+    let (width, height) = HEADLESS_SIZE;
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+
+    let clock = Rc::new(ManualClock::new());
+    let animation_clock: Rc<dyn Clock> = Rc::<ManualClock>::clone(&clock);
+    app.animation_manager = AnimationManager::with_clock(animation_clock);
+    let mut elapsed = Duration::ZERO;
+
+    while !app.exit {
+        let Some((time, event)) = replay.pop_timed() else {
+            break;
+        };
+
+        // Let the frames between the previous event and this one play out
+        while elapsed < time && !app.exit {
+            let step = time.saturating_sub(elapsed).min(POLL_TIMEOUT);
+            clock.advance(step);
+            elapsed += step;
+
+            if elapsed < time {
+                app.tick(&mut terminal, &mut EventQueue::default())?;
+            }
+        }
+
+        if let Event::Resize(width, height) = event {
+            terminal.backend_mut().resize(width, height);
+        }
+        app.tick(&mut terminal, &mut EventQueue::new([event]))?;
+    }
+
+    check_expectations(app, expectations)
+}
+
+/// Compares the state of the application against a list of `(field, value)` expectations.
+///
+/// # Errors
+///
+/// This function returns an error listing every expectation that didn't hold, or naming the first
+/// field that doesn't exist.
+pub(crate) fn check_expectations(app: &App, expectations: &[(String, String)]) -> Result<()> {
+    let mut mismatches = Vec::new();
+
+    for (field, expected) in expectations {
+        let found = state_value(app, field)?;
+        if found != *expected {
+            mismatches.push(format!("  {field}: expected `{expected}`, found `{found}`"));
+        }
+    }
+
+    if !mismatches.is_empty() {
+        bail!(
+            "the replay ended in an unexpected state:\n{}",
+            mismatches.join("\n")
+        );
+    }
+
+    Ok(())
+}
+
+/// Fields of the application state that can be checked at the end of a headless replay.
+pub(crate) const STATE_FIELDS: [&str; 12] = [
+    "screen",
+    "exit",
+    "map",
+    "cursor",
+    "offset",
+    "zoom",
+    "follow",
+    "camera",
+    "inspected",
+    "errors",
+    "help",
+    "step",
+];
+
+/// Formats the value of a field of the application state.
+///
+/// # Errors
+///
+/// This function returns an error if the field isn't one of [`STATE_FIELDS`].
+pub(crate) fn state_value(app: &App, field: &str) -> Result<String> {
+    let value = match field {
+        "screen" => match app.screen {
+            Screen::MainMenu(item) => format!("MainMenu({item:?})"),
+            Screen::OptionsMenu(item) => format!("OptionsMenu({item:?})"),
+            Screen::InGame => "InGame".to_owned(),
+            Screen::MapMenu => "MapMenu".to_owned(),
+        },
+        "exit" => app.exit.to_string(),
+        "map" => app.map.key.clone(),
+        "cursor" => app
+            .viewport_map
+            .as_ref()
+            .map_or_else(|| "none".to_owned(), |map| map.key.clone()),
+        "offset" => app.viewport_offset.to_string(),
+        "zoom" => format!("{:?}", app.camera.zoom),
+        "follow" => app.camera.follow.to_string(),
+        "camera" => format!("{},{}", app.camera.column, app.camera.row),
+        "inspected" => app.inspected_cell.map_or_else(
+            || "none".to_owned(),
+            |(column, row)| format!("{column},{row}"),
+        ),
+        "errors" => app.errors.len().to_string(),
+        "help" => app.help.to_string(),
+        "step" => app.animation_manager.current_index.to_string(),
+        _ => bail!(
+            "unknown state field `{field}`, expected one of: {}",
+            STATE_FIELDS.join(", ")
+        ),
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyCode;
+
+    use super::*;
+    use crate::{
+        testing::TempDir,
+        types::{MainMenuItem, OptionsMenuItem},
+    };
+
+    #[test]
+    fn test_format_and_parse_events_round_trip() {
+        let events = [
+            Event::Key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Event::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)),
+            Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 12,
+                row: 3,
+                modifiers: KeyModifiers::NONE,
+            }),
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::ScrollDown,
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+            }),
+            Event::Resize(120, 40),
+        ];
+
+        for event in events {
+            let formatted = format_event(&event).expect("the event should be representable");
+            let parsed = parse_event(&formatted).expect("formatted events should parse");
+
+            assert_eq!(parsed, event, "`{formatted}` should round-trip");
+        }
+    }
+
+    #[test]
+    fn test_unrepresentable_events_are_skipped() {
+        assert_eq!(format_event(&Event::FocusGained), None);
+        assert_eq!(
+            format_event(&Event::Key(KeyEvent::new(
+                KeyCode::F(5),
+                KeyModifiers::NONE
+            ))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_recording() {
+        let contents = format!("{HEADER}\n0 resize 80 24\n\n150 key j\n300 key enter\n");
+
+        let mut replay = Replay::parse(&contents).expect("the recording should parse");
+
+        assert_eq!(replay.pop(), Some(Event::Resize(80, 24)));
+        assert_eq!(
+            replay.time_until_next().map(|time| time > Duration::ZERO),
+            Some(true)
+        );
+        assert_eq!(replay.next_due(), None);
+        assert!(replay.pop().is_some());
+        assert!(replay.pop().is_some());
+        assert_eq!(replay.time_until_next(), None);
+    }
+
+    #[test]
+    fn test_parse_recording_errors_point_at_line() {
+        let err = Replay::parse("0 key j\n10 key\n").expect_err("missing keys should fail");
+
+        assert_eq!(
+            format!("{err:#}"),
+            "invalid event on line 2: unknown event `key`"
+        );
+        assert!(Replay::parse("later key j").is_err());
+        assert!(Replay::parse("0 mouse down-thumb 1 1").is_err());
+    }
+
+    #[test]
+    fn test_recorder_writes_replayable_file() {
+        let dir = TempDir::new("recording");
+        let path = dir.join("recording.txt");
+
+        let mut recorder = Recorder::create(&path).expect("the recording should be created");
+        recorder
+            .record(&Event::Key(KeyEvent::new(
+                KeyCode::Down,
+                KeyModifiers::NONE,
+            )))
+            .expect("the event should be recorded");
+        recorder
+            .record(&Event::FocusLost)
+            .expect("skipped events shouldn't fail");
+        drop(recorder);
+
+        let mut replay = Replay::load(&path).expect("the recording should load");
+
+        assert_eq!(
+            replay.pop(),
+            Some(Event::Key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)))
+        );
+        assert_eq!(replay.pop(), None);
+    }
+
+    #[test]
+    fn test_run_headless_reaches_expected_state() {
+        let mut app = App::new();
+        app.replay = Some(
+            Replay::parse("0 key j\n10 key l\n20 key k\n30 key esc\n")
+                .expect("the recording should parse"),
+        );
+        let expectations = [
+            ("screen".to_owned(), "MainMenu(StartGame)".to_owned()),
+            ("exit".to_owned(), "false".to_owned()),
+        ];
+
+        run_headless(&mut app, &expectations).expect("the expectations should hold");
+
+        assert_eq!(app.screen, Screen::MainMenu(MainMenuItem::StartGame));
+    }
+
+    #[test]
+    fn test_run_headless_reports_mismatches() {
+        let mut app = App::new();
+        app.replay =
+            Some(Replay::parse("0 key j\n10 key l\n").expect("the recording should parse"));
+        let expectations = [
+            ("screen".to_owned(), "MapMenu".to_owned()),
+            ("help".to_owned(), "false".to_owned()),
+        ];
+
+        let err = run_headless(&mut app, &expectations).expect_err("the screen should differ");
+
+        assert_eq!(app.screen, Screen::OptionsMenu(OptionsMenuItem::Map));
+        assert_eq!(
+            err.to_string(),
+            "the replay ended in an unexpected state:\n  screen: expected `MapMenu`, found \
+             `OptionsMenu(Map)`"
+        );
+    }
+
+    #[test]
+    fn test_run_headless_advances_animation_deterministically() {
+        let mut app = App::new();
+        app.replay =
+            Some(Replay::parse("0 key l\n1000 key q\n").expect("the recording should parse"));
+        let expectations = [
+            ("screen".to_owned(), "InGame".to_owned()),
+            ("exit".to_owned(), "true".to_owned()),
+            ("step".to_owned(), "4".to_owned()),
+        ];
+
+        run_headless(&mut app, &expectations).expect("the expectations should hold");
+    }
+
+    #[test]
+    fn test_state_value_unknown_field() {
+        let app = App::new();
+
+        assert!(state_value(&app, "score").is_err());
+        assert_eq!(
+            state_value(&app, "cursor").expect("the field should exist"),
+            "none"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Terminal session setup and teardown.
+//!
+//! This module contains the functions that put the terminal in raw mode on the alternate screen,
+//! along with the guard and hooks that bring it back to a sane state on every exit path, including
+//! errors bubbling out of the main loop and panics.
+
+use std::{
+    io::{self, Stdout},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use color_eyre::{config::HookBuilder, eyre::Result};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    crossterm::{
+        event::{DisableMouseCapture, EnableMouseCapture},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    Terminal,
+};
+
+/// Whether the terminal is currently set up for the application.
+///
+/// This static makes [`restore_terminal`] idempotent, so that the panic hook and the guard dropped
+/// while unwinding don't both try to leave the alternate screen.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Guard restoring the terminal when it goes out of scope.
+///
+/// This structure owns the terminal used by the application and calls its restore function when
+/// dropped. Since the guard is dropped both when returning early through the `?` operator and when
+/// unwinding from a panic, the terminal is restored no matter how the session ends.
+pub struct TerminalGuard<B: Backend> {
+    /// Terminal owned by the guard.
+    terminal: Terminal<B>,
+    /// Function restoring the terminal to its original state.
+    restore: fn(),
+}
+
+impl<B: Backend> TerminalGuard<B> {
+    /// Wraps a terminal in a guard calling the given function when dropped.
+    pub const fn new(terminal: Terminal<B>, restore: fn()) -> Self {
+        Self { terminal, restore }
+    }
+
+    /// Returns a mutable reference to the guarded terminal.
+    pub const fn terminal_mut(&mut self) -> &mut Terminal<B> {
+        &mut self.terminal
+    }
+}
+
+impl<B: Backend> Drop for TerminalGuard<B> {
+    fn drop(&mut self) {
+        (self.restore)();
+    }
+}
+
+/// Installs the error report and panic hooks.
+///
+/// This function installs `color_eyre`'s hooks, wrapping the panic hook so that the terminal is
+/// restored before the panic report is printed. Error reports returned from `main` are printed
+/// after the [`TerminalGuard`] has been dropped, so they are already printed on a sane screen.
+///
+/// # Errors
+///
+/// This function returns an error if the hooks have already been installed.
+pub fn install_hooks() -> Result<()> {
+    let (panic_hook, eyre_hook) = HookBuilder::default().into_hooks();
+    eyre_hook.install()?;
+
+    let panic_hook = panic_hook.into_panic_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        panic_hook(info);
+    }));
+
+    Ok(())
+}
+
+/// Puts the terminal in raw mode on the alternate screen with mouse capture enabled.
+///
+/// This function returns a guard owning the terminal, which restores it with [`restore_terminal`]
+/// when dropped.
+///
+/// # Errors
+///
+/// This function returns an error if the terminal can't be set up, in which case it's restored
+/// before returning.
+pub fn init_terminal() -> Result<TerminalGuard<CrosstermBackend<Stdout>>> {
+    ACTIVE.store(true, Ordering::SeqCst);
+
+    let setup = || -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        Terminal::new(CrosstermBackend::new(io::stdout()))
+    };
+
+    match setup() {
+        Ok(terminal) => Ok(TerminalGuard::new(terminal, restore_terminal)),
+        Err(err) => {
+            restore_terminal();
+            Err(err.into())
+        }
+    }
+}
+
+/// Restores the terminal to the state it was in before [`init_terminal`] was called.
+///
+/// This function is best-effort: failures are ignored, as it runs on paths where the application
+/// is already exiting. Calling it more than once has no further effect.
+pub fn restore_terminal() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        disable_raw_mode().unwrap_or_default();
+        execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{panic, time::Duration};
+
+    use color_eyre::eyre::eyre;
+    use ratatui::{backend::TestBackend, crossterm::event::Event};
+
+    use super::*;
+    use crate::{input::EventSource, App};
+
+    /// Restoration flag for the error path test.
+    static RESTORED_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+    /// Restoration flag for the panic path test.
+    static RESTORED_ON_PANIC: AtomicBool = AtomicBool::new(false);
+
+    /// Restoration flag for the success path test.
+    static RESTORED_ON_SUCCESS: AtomicBool = AtomicBool::new(false);
+
+    /// Event source failing to read events, as the terminal does once its input is gone.
+    #[derive(Default)]
+    struct FailingEvents {
+        /// Whether the terminal had already been restored when reading events failed.
+        restored: Option<bool>,
+    }
+
+    impl EventSource for FailingEvents {
+        fn next_event(&mut self, _timeout: Duration) -> Result<Option<Event>> {
+            self.restored = Some(RESTORED_ON_ERROR.load(Ordering::SeqCst));
+            Err(eyre!("failed to read terminal events"))
+        }
+    }
+
+    /// Creates a guarded test terminal with known dimensions.
+    fn create_test_guard(restore: fn()) -> TerminalGuard<TestBackend> {
+        let terminal = Terminal::new(TestBackend::new(80, 24)).expect("failed to create terminal");
+        TerminalGuard::new(terminal, restore)
+    }
+
+    #[test]
+    fn test_guard_restores_on_error() {
+        let guard = create_test_guard(|| RESTORED_ON_ERROR.store(true, Ordering::SeqCst));
+        let mut events = FailingEvents::default();
+
+        let result = App::new().run_guarded_with_events(guard, &mut events);
+
+        assert_eq!(
+            events.restored,
+            Some(false),
+            "the terminal shouldn't be restored while the session is running"
+        );
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("failed to read terminal events".to_owned()),
+            "the error should reach the caller"
+        );
+        assert!(
+            RESTORED_ON_ERROR.load(Ordering::SeqCst),
+            "the terminal should be restored before the error is reported"
+        );
+    }
+
+    #[test]
+    fn test_guard_restores_on_panic() {
+        let result = panic::catch_unwind(|| {
+            let _guard = create_test_guard(|| RESTORED_ON_PANIC.store(true, Ordering::SeqCst));
+            panic!("drawing failed");
+        });
+
+        assert!(result.is_err(), "the panic should propagate");
+        assert!(
+            RESTORED_ON_PANIC.load(Ordering::SeqCst),
+            "the terminal should be restored while unwinding"
+        );
+    }
+
+    #[test]
+    fn test_guard_restores_on_success() {
+        let mut guard = create_test_guard(|| RESTORED_ON_SUCCESS.store(true, Ordering::SeqCst));
+        let mut app = App::new();
+        app.exit = true;
+
+        app.run(guard.terminal_mut())
+            .expect("an exiting app should return successfully");
+        drop(guard);
+
+        assert!(
+            RESTORED_ON_SUCCESS.load(Ordering::SeqCst),
+            "the terminal should be restored once the session ends"
+        );
+    }
+
+    #[test]
+    fn test_restore_terminal_is_idempotent() {
+        restore_terminal();
+        restore_terminal();
+
+        assert!(!ACTIVE.load(Ordering::SeqCst));
+    }
+}
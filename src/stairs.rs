@@ -0,0 +1,50 @@
+//! Stairs between floors.
+//!
+//! This module describes the stair cells of multi-level mazes, whose floors are written one after
+//! the other in map files, from the ground floor up, separated by `===` lines. Stairs up, marked
+//! with '+', lead to the same cell of the floor above for the cost of a single move, where stairs
+//! down, marked with '-', lead back.
+
+/// Line separating the floors of a multi-level maze in map files.
+pub(crate) const FLOOR_DELIMITER: &str = "===";
+
+/// Colour stairs are drawn in, as RGB components.
+pub(crate) const STAIRS_COLOUR: [u8; 3] = [0xb0, 0x80, 0xff];
+
+/// Returns the number of floors the stairs on the given cell climb, negative when they go down, if
+/// it holds stairs.
+pub(crate) const fn levels(cell: u8) -> Option<isize> {
+    match cell {
+        b'+' => Some(1),
+        b'-' => Some(-1),
+        _ => None,
+    }
+}
+
+/// Splits the rows of a map into its floors, from the ground floor up.
+pub(crate) fn floors<Row: AsRef<str>>(rows: &[Row]) -> impl Iterator<Item = &[Row]> {
+    rows.split(|row| row.as_ref().trim() == FLOOR_DELIMITER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stairs() {
+        assert_eq!(levels(b'+'), Some(1));
+        assert_eq!(levels(b'-'), Some(-1));
+        assert_eq!(levels(b'3'), None);
+    }
+
+    #[test]
+    fn test_floors() {
+        let rows = ["222", "212", "===", "232", "222"];
+
+        assert_eq!(
+            floors(&rows).collect::<Vec<_>>(),
+            vec![["222", "212"].as_slice(), ["232", "222"].as_slice()]
+        );
+        assert_eq!(floors(&rows[..2]).count(), 1);
+    }
+}
@@ -0,0 +1,71 @@
+//! Terrain of the walkable maze cells and the cost of moving through them.
+//!
+//! This module describes the cells beyond the plain open path ('3') that the solver can walk
+//! through, each with the cost of stepping onto it. Other walkable cells cost a single unit, as
+//! [`Cell::cost`](crate::grid::Cell::cost) tells.
+
+/// Walkable cell type that is costlier to cross than an open path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Terrain {
+    /// Character marking the terrain in the map format.
+    pub byte: u8,
+    /// Name of the terrain, as listed in the help overlay.
+    pub name: &'static str,
+    /// Cost of stepping onto a cell of this terrain.
+    pub cost: u32,
+    /// Colour the terrain is drawn in, as RGB components.
+    pub colour: [u8; 3],
+}
+
+/// Every terrain of the map format, from the cheapest to the costliest to cross.
+pub(crate) const TERRAINS: [Terrain; 5] = [
+    Terrain {
+        byte: b'5',
+        name: "grass",
+        cost: 2,
+        colour: [0x6a, 0xbe, 0x30],
+    },
+    Terrain {
+        byte: b'6',
+        name: "sand",
+        cost: 3,
+        colour: [0xe0, 0xc0, 0x70],
+    },
+    Terrain {
+        byte: b'7',
+        name: "mud",
+        cost: 5,
+        colour: [0x8b, 0x5a, 0x2b],
+    },
+    Terrain {
+        byte: b'8',
+        name: "water",
+        cost: 8,
+        colour: [0x40, 0x80, 0xe0],
+    },
+    Terrain {
+        byte: b'9',
+        name: "deep water",
+        cost: 12,
+        colour: [0x20, 0x40, 0xa0],
+    },
+];
+
+/// Returns the terrain marked by the given character of the map format, if any.
+pub(crate) fn terrain(cell: u8) -> Option<&'static Terrain> {
+    TERRAINS.iter().find(|terrain| terrain.byte == cell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terrains() {
+        assert_eq!(terrain(b'7').map(|terrain| terrain.cost), Some(5));
+        assert_eq!(terrain(b'3'), None);
+        assert!(TERRAINS
+            .windows(2)
+            .all(|pair| matches!(pair, [cheap, dear] if cheap.cost < dear.cost)));
+    }
+}
@@ -0,0 +1,45 @@
+//! Helpers shared by the tests of several modules.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Directory holding the files written by a test, removed along with them once dropped.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory named after the given test in the temporary directory.
+    ///
+    /// The name of the directory also holds the identifier of the test process, so that concurrent
+    /// test runs don't write to the same one.
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("labyrintuine-{name}-{}", std::process::id()));
+        // A directory left over by an earlier run that was killed would mix its files in
+        if path.exists() {
+            fs::remove_dir_all(&path).expect("failed to remove stale test directory");
+        }
+        fs::create_dir_all(&path).expect("failed to create test directory");
+
+        Self(path)
+    }
+
+    /// Returns the path of the directory.
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Returns the path of the entry of the given name in the directory.
+    pub(crate) fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        // Panicking while unwinding from a failed test would abort and hide its failure
+        if !std::thread::panicking() {
+            fs::remove_dir_all(&self.0).expect("failed to remove test directory");
+        }
+    }
+}
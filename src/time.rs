@@ -0,0 +1,121 @@
+//! Sources of time for the parts of the application driven by the clock.
+//!
+//! This module contains the clock trait through which the animation reads the current time, along
+//! with the system clock used at runtime and a manual clock that tests can advance at will, as well
+//! as the calendar date written into the files the application creates.
+
+use std::{
+    cell::Cell,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Source of the current time.
+pub(crate) trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// Clock reading the time from the operating system.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock whose time only moves forward when told to.
+///
+/// This structure lets tests and headless replays step through time-driven behavior, such as the
+/// solver animation, deterministically.
+#[derive(Debug)]
+pub(crate) struct ManualClock {
+    /// Current time of the clock.
+    now: Cell<Instant>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    /// Creates a manual clock stopped at the current system time.
+    pub(crate) fn new() -> Self {
+        Self {
+            now: Cell::new(Instant::now()),
+        }
+    }
+
+    /// Moves the clock forward by the given amount of time.
+    pub(crate) fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+/// Returns the current date in UTC, formatted as `YYYY-MM-DD`.
+pub(crate) fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400);
+    let (year, month, day) = civil_date(days);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Converts an amount of days since the Unix epoch to a `(year, month, day)` date.
+///
+/// This function counts days from 0000-03-01 instead, so that leap days fall at the end of each
+/// year, and splits them into eras of 400 years, each of which has the same amount of days.
+const fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_date() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(20_744), (2026, 10, 18));
+    }
+
+    #[test]
+    fn test_manual_clock_only_moves_when_advanced() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(
+            clock.now().duration_since(start),
+            Duration::from_millis(250)
+        );
+    }
+}
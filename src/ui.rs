@@ -1,25 +1,31 @@
 //! User interface rendering functions for all application screens.
 
-use std::rc::Rc;
+use std::{borrow::Cow, rc::Rc};
 
 use color_eyre::eyre::{OptionExt as _, Result};
 use ratatui::{
-    layout::{Alignment, Constraint, Flex, Layout, Rect},
+    layout::{Alignment, Constraint, Flex, Layout, Position, Rect},
     style::{Color, Style},
     symbols::{Marker, DOT},
-    text::Line,
+    text::{Line, Span},
     widgets::{
-        canvas::{Canvas, Points},
-        Block, BorderType, Borders, Clear,
+        canvas::{Canvas, Points, Rectangle},
+        Block, BorderType, Borders, Clear, Padding, Paragraph, Wrap,
     },
     Frame,
 };
 
 use crate::{
+    braid::BraidSettings,
+    camera::{Camera, ZoomLevel},
+    grid::{Cell, Grid},
+    keymap::{Action, Keymap},
     map::Map,
-    pathfinding,
+    oneway,
+    pathfinding::{self, Route},
+    portal, puzzle, stairs, terrain,
     types::{MainMenuItem, MenuType, OptionsMenuItem, Screen},
-    App,
+    weave, App,
 };
 
 /// Updates the application UI based on the persistent state.
@@ -31,16 +37,441 @@ use crate::{
 ///
 /// This function may return errors from drawing operations or data conversion failures.
 pub(crate) fn draw(app: &mut App, frame: &mut Frame) -> Result<()> {
-    match &app.screen {
-        Screen::MainMenu(item) => main_menu(frame, *item),
-        Screen::OptionsMenu(item) => options_menu(frame, *item),
-        Screen::InGame => in_game(app, frame)?,
-        Screen::MapMenu => map_menu(app, frame)?,
+    app.area = frame.area();
+
+    let (width, height) = required_size(app);
+    if frame.area().width < width || frame.area().height < height {
+        too_small(frame, width, height);
+    } else {
+        match &app.screen {
+            Screen::MainMenu(item) => main_menu(frame, *item, &app.keymap),
+            Screen::OptionsMenu(item) => options_menu(frame, *item, &app.keymap),
+            Screen::InGame => in_game(app, frame)?,
+            Screen::MapMenu => map_menu(app, frame)?,
+        }
+    }
+
+    if app.help {
+        help_overlay(frame, app);
+    }
+
+    if let Some(message) = app.errors.front() {
+        error_popup(frame, message, app.errors.len() - 1);
     }
 
     Ok(())
 }
 
+/// Renders a modal popup with the message of a recoverable error.
+///
+/// This function draws the popup over whatever screen is being displayed, along with the amount of
+/// errors still pending after the current one and a hint on how to dismiss it.
+pub(crate) fn error_popup(frame: &mut Frame, message: &str, pending: usize) {
+    let area = frame.area();
+    let width = (area.width / 2).max(40);
+
+    let block = Block::bordered()
+        .title(if pending == 0 {
+            "Error".to_owned()
+        } else {
+            format!("Error ({pending} more)")
+        })
+        .title_bottom("(any key) dismiss")
+        .title_alignment(Alignment::Center)
+        .style(Color::Red)
+        .border_type(BorderType::Rounded);
+    let paragraph = Paragraph::new(message)
+        .wrap(Wrap { trim: true })
+        .block(block);
+
+    // Estimate the wrapped height of the message, leaving some slack for word boundaries
+    let inner_width = usize::from(width.saturating_sub(2)).max(1);
+    let lines: usize = message
+        .lines()
+        .map(|line| (line.chars().count() + line.chars().count() / 8).div_ceil(inner_width))
+        .map(|rows| rows.max(1))
+        .sum();
+    let height = u16::try_from(lines).unwrap_or(u16::MAX).saturating_add(2);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Renders a modal overlay listing the key bindings available on the current screen.
+///
+/// This function draws the actions that can be performed on the current screen along with every key
+/// bound to them in the active keymap, followed by the legend of the map format and the meaning of
+/// the colours used on the screen.
+pub(crate) fn help_overlay(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+
+    let bindings = help_actions(&app.screen)
+        .iter()
+        .map(|&(actions, description)| {
+            let keys = actions
+                .iter()
+                .filter_map(|&action| app.keymap.key_names(action))
+                .collect::<Vec<_>>();
+            if keys.is_empty() {
+                ("unbound".to_owned(), description)
+            } else {
+                (keys.join(", "), description)
+            }
+        })
+        .collect::<Vec<_>>();
+    let key_width = bindings
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![Line::raw("Key bindings")];
+    lines.extend(
+        bindings
+            .iter()
+            .map(|(keys, description)| Line::raw(format!("  {keys:<key_width$}  {description}"))),
+    );
+    lines.push(Line::raw(""));
+    lines.push(Line::raw("Map legend, with the cost of crossing terrain"));
+    lines.push(Line::from(vec![
+        Span::raw(format!(
+            "  {}  ",
+            MAP_LEGEND
+                .iter()
+                .map(|(cell, description)| format!("{cell} {description}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+        )),
+        Span::styled("+- stairs", rgb(stairs::STAIRS_COLOUR)),
+        Span::styled("  # crossing", rgb(weave::CROSSING_COLOUR)),
+    ]));
+    let mut terrain = Vec::new();
+    for terrain_type in &terrain::TERRAINS {
+        terrain.push(Span::raw("  "));
+        terrain.push(Span::styled(
+            format!(
+                "{} {} ({})",
+                char::from(terrain_type.byte),
+                terrain_type.name,
+                terrain_type.cost
+            ),
+            rgb(terrain_type.colour),
+        ));
+    }
+    lines.push(Line::from(terrain));
+    lines.push(Line::from(vec![
+        Span::raw("  "),
+        Span::styled("a-e keys", rgb(puzzle::KEY_COLOUR)),
+        Span::raw("  "),
+        Span::styled("A-E doors opened by their key", rgb(puzzle::DOOR_COLOUR)),
+        Span::raw("  "),
+        Span::styled("p-u portals", rgb(portal::PORTAL_COLOUR)),
+        Span::raw("  "),
+        Span::styled("^v<> one-way", rgb(oneway::ARROW_COLOUR)),
+    ]));
+    lines.push(Line::raw(""));
+    lines.push(Line::raw("Colours"));
+    lines.extend(
+        help_colours(&app.screen)
+            .iter()
+            .map(|&(style, name, description)| {
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(format!("{name:<14}"), style),
+                    Span::raw(description),
+                ])
+            }),
+    );
+
+    // Drop the blank lines between sections before letting the overlay run off the screen
+    if lines.len() + 2 > usize::from(area.height) {
+        lines.retain(|line| line.width() > 0);
+    }
+
+    let content_width = lines.iter().map(Line::width).max().unwrap_or(0);
+    let width = u16::try_from(content_width)
+        .unwrap_or(u16::MAX)
+        .saturating_add(4);
+    let height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2);
+
+    let block = Block::bordered()
+        .title("Help")
+        .title_bottom("(any key) close")
+        .title_alignment(Alignment::Center)
+        .style(Color::Green)
+        .border_type(BorderType::Rounded);
+    let paragraph = Paragraph::new(lines)
+        .block(block.padding(Padding::horizontal(1)))
+        .style(Color::White);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Meaning of each cell of the map format, as listed in the help overlay.
+///
+/// Terrain cells are listed after these, along with their cost, from [`terrain::TERRAINS`].
+pub(crate) const MAP_LEGEND: [(char, &str); 4] = [
+    ('1', "entry point"),
+    ('2', "wall"),
+    ('3', "open path"),
+    ('4', "exit"),
+];
+
+/// Returns the actions available on the given screen, along with what they do there.
+///
+/// Actions that only differ by direction are grouped together, the same way as in the navigation
+/// hints.
+pub(crate) const fn help_actions(screen: &Screen) -> &'static [(&'static [Action], &'static str)] {
+    match screen {
+        Screen::MainMenu(_) => &[
+            (&[Action::Up], "move the cursor up"),
+            (&[Action::Down], "move the cursor down"),
+            (&[Action::Select], "open the item under the cursor"),
+            (&[Action::Help], "show this help"),
+            (&[Action::Quit], "quit the game"),
+        ],
+        Screen::OptionsMenu(_) => &[
+            (&[Action::Up], "move the cursor up"),
+            (&[Action::Down], "move the cursor down"),
+            (&[Action::Select], "open the item under the cursor"),
+            (&[Action::Back], "return to the main menu"),
+            (&[Action::Help], "show this help"),
+            (&[Action::Quit], "quit the game"),
+        ],
+        Screen::MapMenu => &[
+            (&[Action::Up], "move the cursor up"),
+            (&[Action::Down], "move the cursor down"),
+            (&[Action::Select], "use the map under the cursor"),
+            (&[Action::Back], "return to the options menu"),
+            (&[Action::Help], "show this help"),
+            (&[Action::Quit], "quit the game"),
+        ],
+        Screen::InGame => &[
+            (&[Action::Back], "return to the main menu"),
+            (
+                &[
+                    Action::PanLeft,
+                    Action::PanDown,
+                    Action::PanUp,
+                    Action::PanRight,
+                ],
+                "pan the camera left, down, up or right",
+            ),
+            (&[Action::ZoomIn, Action::ZoomOut], "zoom in or out"),
+            (
+                &[Action::ToggleFollow, Action::ToggleStats],
+                "toggle following the solver or the statistics panel",
+            ),
+            (
+                &[Action::CycleSolver, Action::Braid],
+                "switch to the next solver or braid the maze",
+            ),
+            (
+                &[Action::CycleFloor],
+                "show the next floor, or every floor side by side",
+            ),
+            (
+                &[Action::ExportCast, Action::ExportImage],
+                "export the animation as asciicast or the maze as SVG and PNG",
+            ),
+            (&[Action::Help], "show this help"),
+            (&[Action::Quit], "quit the game"),
+        ],
+    }
+}
+
+/// Returns the colours used on the given screen, along with their names and what they stand for.
+pub(crate) const fn help_colours(
+    screen: &Screen,
+) -> &'static [(Style, &'static str, &'static str)] {
+    /// Style of menu items and walls.
+    const GREEN: Style = Style::new().fg(Color::Green);
+    /// Style of the item under the cursor.
+    const ACTIVE: Style = Style::new().fg(Color::White).bg(Color::Green);
+    /// Style of the solver's path.
+    const RED: Style = Style::new().fg(Color::Red);
+    /// Style of the shortest way out where the solver strays from it.
+    const GRAY: Style = Style::new().fg(SHORTCUT_COLOUR);
+    /// Style of the cells reached from further entry points.
+    const CYAN: Style = Style::new().fg(Color::Cyan);
+    /// Style of the keys picked up and of jumps through portals and stairs.
+    const WHITE: Style = Style::new().fg(Color::White);
+    /// Style of the inspected cell and the minimap viewport.
+    const YELLOW: Style = Style::new().fg(Color::Yellow);
+    /// Style of the minimap walls.
+    const DARK_GRAY: Style = Style::new().fg(Color::DarkGray);
+
+    match screen {
+        Screen::MainMenu(_) | Screen::OptionsMenu(_) => &[
+            (GREEN, "green", "menu items"),
+            (ACTIVE, "white on green", "item under the cursor"),
+        ],
+        Screen::MapMenu => &[
+            (GREEN, "green", "available maps"),
+            (ACTIVE, "white on green", "map under the cursor"),
+            (GREEN, "green dot", "map currently in use"),
+        ],
+        Screen::InGame => &[
+            (GREEN, "green", "walls"),
+            (RED, "red", "path being explored by the solver"),
+            (
+                GRAY,
+                "gray",
+                "shortest way out, where the solver's way out strays",
+            ),
+            (
+                CYAN,
+                "cyan, magenta",
+                "further entries or more keys held, lighter on the way out",
+            ),
+            (
+                WHITE,
+                "white",
+                "keys picked up, both ends of a portal or stairs jump",
+            ),
+            (YELLOW, "yellow", "inspected cell and minimap viewport"),
+            (DARK_GRAY, "dark gray", "minimap walls"),
+        ],
+    }
+}
+
+/// Navigation hints shown in the bottom border of the main and options menus.
+pub(crate) const MENU_HINTS: &[(&[Action], &str)] = &[
+    (&[Action::Down], "down"),
+    (&[Action::Up], "up"),
+    (&[Action::Select], "select"),
+    (&[Action::Help], "help"),
+];
+
+/// Navigation hints shown in the bottom border of the map menu.
+pub(crate) const MAP_MENU_HINTS: &[(&[Action], &str)] = &[
+    (&[Action::Down], "down"),
+    (&[Action::Up], "up"),
+    (&[Action::Select], "select"),
+    (&[Action::Back], "return"),
+    (&[Action::Help], "help"),
+];
+
+/// Minimum height of the map menu, fitting its borders and a single list entry.
+pub(crate) const MAP_MENU_MIN_HEIGHT: u16 = 3;
+
+/// Minimum size of the in-game maze viewport as a `(width, height)` pair of terminal cells.
+///
+/// Mazes that don't fit in the terminal at the farthest zoom level can still be panned across, as
+/// long as the viewport is at least this big.
+pub(crate) const MIN_VIEWPORT: (u16, u16) = (16, 4);
+
+/// Height of the tooltip block at the bottom of the in-game screen.
+pub(crate) const TOOLTIP_HEIGHT: u16 = 3;
+
+/// Computes the minimum terminal size needed to render the current screen.
+///
+/// This function returns the smallest `(width, height)` pair of terminal cells for which the
+/// current screen can be drawn without clipping its borders or navigation hints. For the in-game
+/// screen, this is the size of the maze at the farthest zoom level, capped at [`MIN_VIEWPORT`], or
+/// the size of the braid panel while it's open if that is larger.
+pub(crate) fn required_size(app: &App) -> (u16, u16) {
+    match app.screen {
+        Screen::MainMenu(_) => (
+            hint_width(&app.keymap.hints(MENU_HINTS)),
+            u16::from(MenuType::MainMenu(3).value() + 2),
+        ),
+        Screen::OptionsMenu(_) => (
+            hint_width(&app.keymap.hints(MENU_HINTS)),
+            u16::from(MenuType::OptionsMenu(2).value() + 2),
+        ),
+        Screen::MapMenu => (
+            hint_width(&app.keymap.hints(MAP_MENU_HINTS)),
+            MAP_MENU_MIN_HEIGHT,
+        ),
+        Screen::InGame => {
+            let (column_resolution, row_resolution) = ZoomLevel::Far.resolution();
+            let floor = stairs::floors(&app.map.data).next().unwrap_or_default();
+            let maze_columns = floor.first().map_or(0, String::len);
+            let maze_rows = floor.len();
+
+            let width = u16::try_from(maze_columns.div_ceil(column_resolution))
+                .unwrap_or(u16::MAX)
+                .min(MIN_VIEWPORT.0);
+            let height = u16::try_from(maze_rows.div_ceil(row_resolution))
+                .unwrap_or(u16::MAX)
+                .min(MIN_VIEWPORT.1);
+
+            // The braid panel is a modal, which must fit in the maze area while it's open
+            let (width, height) = app.braiding.map_or((width, height), |settings| {
+                let (panel_width, panel_height) =
+                    braid_panel_size(&braid_panel_lines(&app.keymap, &settings));
+                (width.max(panel_width), height.max(panel_height))
+            });
+
+            (width, height.saturating_add(TOOLTIP_HEIGHT))
+        }
+    }
+}
+
+/// Returns the width of a bordered block whose bottom title holds the given hints.
+pub(crate) fn hint_width(hints: &str) -> u16 {
+    u16::try_from(hints.chars().count())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2)
+}
+
+/// Renders a warning asking the user to enlarge the terminal.
+///
+/// This function replaces the contents of the current screen with a message stating the minimum
+/// terminal size required to draw it, instead of rendering a clipped or garbled frame.
+pub(crate) fn too_small(frame: &mut Frame, width: u16, height: u16) {
+    clear(frame);
+
+    let area = frame.area();
+    let message = Paragraph::new(vec![
+        Line::raw(format!("terminal too small: need {width}x{height}")),
+        Line::raw(format!("current size: {}x{}", area.width, area.height)),
+    ])
+    .style(Color::Yellow)
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+
+    let message_area = centered(area, area.width, 2.min(area.height));
+    frame.render_widget(message, message_area);
+}
+
+/// Returns a rectangle of the given size centered within the given area.
+///
+/// The size is clamped to the dimensions of the area, so the returned rectangle never goes past its
+/// boundaries.
+pub(crate) fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [horizontal] = Layout::horizontal([Constraint::Length(width.min(area.width))])
+        .flex(Flex::Center)
+        .areas(area);
+    let [space] = Layout::vertical([Constraint::Length(height.min(area.height))])
+        .flex(Flex::Center)
+        .areas(horizontal);
+
+    space
+}
+
+/// Computes the area of the bordered block holding the map list.
+///
+/// This function is shared by the rendering code and the resize handler, so that the height of the
+/// list viewport can be known before the next frame is drawn.
+pub(crate) fn map_menu_area(area: Rect, keymap: &Keymap) -> Rect {
+    let width = (area.width / 5 * 2).max(hint_width(&keymap.hints(MAP_MENU_HINTS)));
+    let height = (area.height / 5).max(MAP_MENU_MIN_HEIGHT);
+
+    centered(area, width, height)
+}
+
+/// Computes the amount of map entries that fit in the map list for a terminal of the given size.
+pub(crate) fn map_list_height(area: Rect, keymap: &Keymap) -> usize {
+    map_menu_area(area, keymap).height.saturating_sub(2).into()
+}
+
 /// Clears the terminal screen by rendering a [`Clear`] widget.
 ///
 /// This function renders a clear widget over the entire area of the frame to prepare for
@@ -55,40 +486,94 @@ pub(crate) fn clear(frame: &mut Frame) {
 /// This function creates the common layout and block structure used by both main and options menus.
 /// The generic part includes the centered positioning and border styling, while the specific menu
 /// content is handled by the caller using the [`MenuType`] parameter.
-#[expect(
-    clippy::indexing_slicing,
-    reason = "The collection is created in-place with few, known elements; there is no risk of bad indexing."
-)]
-pub(crate) fn init_menu(frame: &mut Frame, menu: MenuType) -> Rc<[Rect]> {
-    let space = Layout::vertical([
-        Constraint::Percentage(40),
-        Constraint::Percentage(20),
-        Constraint::Percentage(40),
-    ])
-    .split(frame.area())[1];
-    let space = Layout::horizontal([
-        Constraint::Percentage(40),
-        Constraint::Percentage(20),
-        Constraint::Percentage(40),
-    ])
-    .split(space)[1];
-
-    let layout = Layout::vertical([Constraint::Max(u16::from(menu.value() + 2))])
-        .flex(Flex::Center)
-        .split(space)[0];
+pub(crate) fn init_menu(frame: &mut Frame, menu: MenuType, keymap: &Keymap) -> Rc<[Rect]> {
+    let (layout, items) = menu_layout(frame.area(), &menu, keymap);
 
     let block = Block::bordered()
         .title(menu.repr())
-        .title_bottom("(j) down / (k) up / (l) select")
+        .title_bottom(keymap.hints(MENU_HINTS))
         .title_alignment(Alignment::Center)
         .style(Color::Green)
         .border_type(BorderType::Rounded);
 
-    let inner_space = block.inner(layout);
-
     frame.render_widget(block, layout);
 
-    Layout::vertical(vec![Constraint::Max(1); menu.value() as usize]).split(inner_space)
+    items
+}
+
+/// Computes the layout of the main and options menus for a terminal of the given size.
+///
+/// This function returns the area of the bordered menu block along with the area of each of its
+/// items, so that both the rendering code and the mouse handler agree on where items are.
+pub(crate) fn menu_layout(area: Rect, menu: &MenuType, keymap: &Keymap) -> (Rect, Rc<[Rect]>) {
+    let layout = centered(
+        area,
+        (area.width / 5).max(hint_width(&keymap.hints(MENU_HINTS))),
+        u16::from(menu.value() + 2),
+    );
+    let inner_space = Block::bordered().inner(layout);
+
+    (
+        layout,
+        Layout::vertical(vec![Constraint::Max(1); menu.value() as usize]).split(inner_space),
+    )
+}
+
+/// Computes the layout of the in-game screen for a terminal of the given size.
+///
+/// This function returns the area in which the maze canvas is drawn and the area of the tooltip
+/// block at the bottom of the screen, in that order.
+pub(crate) fn in_game_layout(area: Rect) -> (Rect, Rect) {
+    Layout::vertical([
+        Constraint::Min(1),                 // Maze and padding area
+        Constraint::Length(TOOLTIP_HEIGHT), // Tooltip block
+    ])
+    .areas::<2>(area)
+    .into()
+}
+
+/// Returns the maze cell drawn at the given terminal position on the in-game screen.
+///
+/// This function reverses the mapping performed by the maze canvas using the camera state of the
+/// last drawn frame. When zoomed out, a terminal cell covers several maze cells, in which case the
+/// top-left one is returned. Positions outside of the maze yield `None`, as do the half cells that
+/// shifted rows of hexagonal mazes leave empty. Cells of a floor shown on its own are returned with
+/// their coordinates in the whole maze.
+pub(crate) fn maze_cell_at(app: &App, position: Position) -> Option<(usize, usize)> {
+    let (maze_area, _) = in_game_layout(app.area);
+    if !maze_area.contains(position) {
+        return None;
+    }
+
+    let grid = Grid::of(&app.map).ok()?;
+    let floor = shown_floor(&app.camera, &grid);
+    let view = floor.map_or(Cow::Borrowed(&grid), |floor| Cow::Owned(grid.floor(floor)));
+    let maze_columns = view.width();
+    let maze_rows = view.height();
+    let topology = view.topology();
+    let dots = i64::try_from(topology.dots_per_column()).ok()?;
+    let (canvas_columns, canvas_rows) = app.camera.visible_cells(maze_area.width, maze_area.height);
+    let visible = (canvas_columns / topology.dots_per_column(), canvas_rows);
+    let (origin_column, origin_row) = app.camera.origin((maze_columns, maze_rows), visible);
+    let (column_resolution, row_resolution) = app.camera.zoom.resolution();
+
+    let row = origin_row.checked_add(
+        i64::from(position.y - maze_area.y).checked_mul(i64::try_from(row_resolution).ok()?)?,
+    )?;
+    let row = usize::try_from(row).ok().filter(|&row| row < maze_rows)?;
+
+    // Hexagonal cells span two dots, the cells of shifted rows starting one dot further right
+    let dot = origin_column.checked_mul(dots)?.checked_add(
+        i64::from(position.x - maze_area.x).checked_mul(i64::try_from(column_resolution).ok()?)?,
+    )?;
+    let column = dot
+        .checked_sub(i64::from(topology.shift(row)) * dots / 2)?
+        .checked_div_euclid(dots)?;
+    let column = usize::try_from(column)
+        .ok()
+        .filter(|&column| column < maze_columns)?;
+
+    Some(floor.map_or((column, row), |floor| grid.on_floor(floor, (column, row))))
 }
 
 /// Renders the main menu screen with navigation options.
@@ -103,10 +588,10 @@ pub(crate) fn init_menu(frame: &mut Frame, menu: MenuType) -> Rc<[Rect]> {
     clippy::missing_asserts_for_indexing,
     reason = "The collection is created in-place with few, known elements; there is no risk of bad indexing."
 )]
-pub(crate) fn main_menu(frame: &mut Frame, item: MainMenuItem) {
+pub(crate) fn main_menu(frame: &mut Frame, item: MainMenuItem, keymap: &Keymap) {
     clear(frame);
 
-    let inner_layout = init_menu(frame, MenuType::MainMenu(3));
+    let inner_layout = init_menu(frame, MenuType::MainMenu(3), keymap);
 
     let content_style = Style::default().fg(Color::Green);
     let active_content_style = Style::default().fg(Color::White).bg(Color::Green);
@@ -149,10 +634,10 @@ pub(crate) fn main_menu(frame: &mut Frame, item: MainMenuItem) {
     clippy::missing_asserts_for_indexing,
     reason = "The collection is created in-place with few, known elements; there is no risk of bad indexing."
 )]
-pub(crate) fn options_menu(frame: &mut Frame, item: OptionsMenuItem) {
+pub(crate) fn options_menu(frame: &mut Frame, item: OptionsMenuItem, keymap: &Keymap) {
     clear(frame);
 
-    let inner_layout = init_menu(frame, MenuType::OptionsMenu(2));
+    let inner_layout = init_menu(frame, MenuType::OptionsMenu(2), keymap);
 
     let content_style = Style::default().fg(Color::Green);
     let active_content_style = Style::default().fg(Color::White).bg(Color::Green);
@@ -183,34 +668,13 @@ pub(crate) fn options_menu(frame: &mut Frame, item: OptionsMenuItem) {
 /// # Errors
 ///
 /// This function may return errors if the viewport map cannot be retrieved.
-#[expect(
-    clippy::indexing_slicing,
-    reason = "The collection is created in-place with few, known elements; there is no risk of bad indexing."
-)]
-#[expect(
-    clippy::missing_asserts_for_indexing,
-    reason = "The collection is created in-place with few, known elements; there is no risk of bad indexing."
-)]
 pub(crate) fn map_menu(app: &mut App, frame: &mut Frame) -> Result<()> {
     clear(frame);
 
-    let space = Layout::horizontal([
-        Constraint::Percentage(30),
-        Constraint::Fill(1),
-        Constraint::Percentage(30),
-    ])
-    .split(frame.area())[1];
-    let space = Layout::vertical([
-        Constraint::Percentage(40),
-        Constraint::Fill(1),
-        Constraint::Percentage(40),
-    ])
-    .split(space)[1];
-
-    let layout = Layout::vertical([Constraint::Min(1)]).split(space)[0];
+    let layout = map_menu_area(frame.area(), &app.keymap);
     let block = Block::bordered()
         .title_top("Map list")
-        .title_bottom("(j) down / (k) up / (l) select / (h) return")
+        .title_bottom(app.keymap.hints(MAP_MENU_HINTS))
         .title_alignment(Alignment::Center)
         .style(Color::Green)
         .border_type(BorderType::Rounded);
@@ -220,21 +684,22 @@ pub(crate) fn map_menu(app: &mut App, frame: &mut Frame) -> Result<()> {
     frame.render_widget(block, layout);
 
     app.viewport_height = inner_space.height.into();
+    app.clamp_viewport_offset();
 
-    let inner_layout = Layout::horizontal([Constraint::Percentage(5), Constraint::Percentage(100)])
-        .split(inner_space);
-    let inner_selector = Layout::vertical(vec![Constraint::Max(1); inner_space.height.into()])
-        .split(inner_layout[0]);
-    let inner_list = Layout::vertical(vec![Constraint::Max(1); inner_space.height.into()])
-        .split(inner_layout[1]);
+    let [inner_selector, inner_list] =
+        Layout::horizontal([Constraint::Percentage(5), Constraint::Percentage(100)])
+            .areas(inner_space);
 
-    let mut viewport_maps: Vec<&Map> = app.maps.iter().skip(app.viewport_offset).collect();
-    viewport_maps.truncate(inner_space.height.into());
+    let viewport_maps = app
+        .maps
+        .iter()
+        .skip(app.viewport_offset)
+        .zip(inner_selector.rows().zip(inner_list.rows()));
 
     let content_style = Style::default().fg(Color::Green);
     let active_content_style = Style::default().fg(Color::White).bg(Color::Green);
 
-    for (idx, map) in viewport_maps.into_iter().enumerate() {
+    for (map, (selector_area, entry_area)) in viewport_maps {
         let viewport_map = app
             .viewport_map
             .clone()
@@ -264,13 +729,57 @@ pub(crate) fn map_menu(app: &mut App, frame: &mut Frame) -> Result<()> {
             )
         };
 
-        frame.render_widget(selector, inner_selector[idx]);
-        frame.render_widget(entry, inner_list[idx]);
+        frame.render_widget(selector, selector_area);
+        frame.render_widget(entry, entry_area);
+    }
+
+    app.rate_viewport_map();
+    if let (Some(map), Some((_, rating))) = (&app.viewport_map, &app.viewport_rating) {
+        map_details(frame, map, rating, layout);
     }
 
     Ok(())
 }
 
+/// Renders the metadata of the cursor-selected map below the map list.
+///
+/// This function lists the header fields of the map in a bordered block right under the map list,
+/// followed by the given difficulty rating of the maze, as tall as the fields need and the space
+/// left on screen allows. Nothing is drawn if there's no room for at least one line.
+pub(crate) fn map_details(frame: &mut Frame, map: &Map, rating: &str, list_area: Rect) {
+    let fields = map.metadata.fields();
+    let mut lines = if fields.is_empty() {
+        vec![Line::raw("no details given for this map")]
+    } else {
+        fields
+            .into_iter()
+            .map(|(field, value)| Line::raw(format!("{field}: {value}")))
+            .collect()
+    };
+    lines.push(Line::raw(rating));
+
+    let available = frame.area().bottom().saturating_sub(list_area.bottom());
+    let height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2)
+        .min(available);
+    if height < 3 {
+        return;
+    }
+
+    let block = Block::bordered()
+        .title_top("Details")
+        .title_alignment(Alignment::Center)
+        .style(Color::Green)
+        .border_type(BorderType::Rounded);
+    let details = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    frame.render_widget(
+        details,
+        Rect::new(list_area.x, list_area.bottom(), list_area.width, height),
+    );
+}
+
 /// Renders the in-game screen with maze visualization and pathfinding solution.
 ///
 /// This function displays the currently selected labyrinth and runs the pathfinding algorithm to
@@ -281,166 +790,652 @@ pub(crate) fn map_menu(app: &mut App, frame: &mut Frame) -> Result<()> {
 ///
 /// This function may return errors from coordinate conversion operations or entry point
 /// detection.
-#[expect(
-    clippy::too_many_lines,
-    reason = "UI rendering function requires many lines for layout and drawing operations."
-)]
 pub(crate) fn in_game(app: &mut App, frame: &mut Frame) -> Result<()> {
     clear(frame);
 
     // Initialize animation steps if not already done
     if app.animation_manager.steps.is_empty() {
-        // Find the maze entry point (marked with '1')
-        let entry_point = app
-            .map
-            .data
-            .iter()
-            .enumerate()
-            .find_map(|(row, line)| {
-                line.bytes()
-                    .enumerate()
-                    .find_map(|(col, char)| (char == b'1').then_some((col, row)))
-            })
-            .ok_or_eyre("failed to retrieve entry point in map")?;
-
-        // Record animation steps
-        let mut initial_path = Vec::new();
-        pathfinding::record_animation_steps(
-            &app.map.data,
-            entry_point,
-            &mut initial_path,
-            &mut app.animation_manager.steps,
-        );
+        app.animation_manager.record(Grid::of(&app.map)?)?;
+    }
 
-        app.animation_manager.reset();
+    // Show the floor the solver is on, taking the stairs along with it
+    let grid = &app.animation_manager.grid;
+    let head = app.animation_manager.head();
+    if app.camera.follow && app.camera.floor.is_some() {
+        if let Some(head) = head {
+            app.camera.floor = Some(grid.floor_of(head));
+        }
     }
+    let floor = shown_floor(&app.camera, grid);
+    let view = floor.map_or(Cow::Borrowed(grid), |floor| Cow::Owned(grid.floor(floor)));
 
-    let maze_rows = app.map.data.len();
-    let maze_columns = app
-        .map
-        .data
-        .first()
-        .ok_or_eyre("failed to retrieve maze in selected map")?
-        .len();
+    let maze_rows = view.height();
+    let maze_columns = view.width();
+    let dots = view.topology().dots_per_column();
 
     // Create overall layout: maze area + tooltip at bottom
-    let overall_layout = Layout::vertical([
-        Constraint::Min(1),    // Maze and padding area
-        Constraint::Length(3), // Tooltip block
-    ])
-    .split(frame.area());
-
-    let maze_content_area = *overall_layout
-        .first()
-        .ok_or_eyre("failed to get maze content area from layout")?;
-    let tooltip_full_area = *overall_layout
-        .last()
-        .ok_or_eyre("failed to get tooltip area from layout")?;
-
-    // Center the tooltip horizontally like the maze
-    let tooltip_area = Layout::horizontal([
-        Constraint::Min(1),
-        Constraint::Length(u16::try_from(maze_columns)?),
-        Constraint::Min(1),
-    ])
-    .split(tooltip_full_area)
-    .get(1)
-    .copied()
-    .ok_or_eyre("failed to get centered tooltip area from horizontal layout")?;
-
-    // Create maze layout within the content area
-    let main_layout = Layout::vertical([
-        Constraint::Min(1),
-        Constraint::Length(u16::try_from(maze_rows)?),
-        Constraint::Min(1),
-    ])
-    .split(maze_content_area);
-
-    let maze_area = main_layout
-        .get(1)
-        .ok_or_eyre("failed to get maze area from layout")?;
+    let (maze_area, tooltip_area) = in_game_layout(frame.area());
+
+    // Move the camera before computing the visible region of the maze
+    let canvas = app.camera.visible_cells(maze_area.width, maze_area.height);
+    let visible = (canvas.0 / dots, canvas.1);
+    if app.camera.follow {
+        if let Some(head) = head.and_then(|head| grid.project(floor, head)) {
+            app.camera.keep_in_view(head, visible);
+        }
+    }
+    app.camera.clamp((maze_columns, maze_rows), visible);
 
-    let space = Layout::horizontal([
-        Constraint::Min(1),
-        Constraint::Length(u16::try_from(maze_columns)?),
-        Constraint::Min(1),
-    ])
-    .split(*maze_area)
-    .get(1)
-    .copied()
-    .ok_or_eyre("failed to get maze space from horizontal layout")?;
+    let (x_bounds, y_bounds) = viewport_bounds(app, (maze_columns, maze_rows), canvas, dots)?;
 
     // Pre-compute screen coordinates to handle errors before closures
-    let mut wall_coords = Vec::new();
-    for (row_idx, row) in app.map.data.iter().enumerate() {
-        for (col_idx, cell) in row.bytes().enumerate() {
-            if cell == b'2' {
-                wall_coords.push((col_idx, row_idx));
-            }
-        }
-    }
     let wall_screen_coords =
-        pathfinding::transform_maze_to_screen_coords(&wall_coords, &app.map.data)?;
-    let animation_screen_coords = pathfinding::transform_maze_to_screen_coords(
-        &app.animation_manager.current_path,
-        &app.map.data,
-    )?;
+        pathfinding::transform_maze_to_screen_coords(&wall_coordinates(&view), &view)?;
+    let cell_layers = cell_layers(&view)?;
+    let solver_layers = solver_layers(app, &view, floor)?;
+
+    // Arrows and crossings only get their glyph when each maze cell has a terminal cell of its own
+    let glyphs = if app.camera.zoom == ZoomLevel::Close {
+        cell_glyphs(&view)?
+    } else {
+        Vec::new()
+    };
 
     let maze = Canvas::default()
-        .x_bounds([
-            (-rounded_div::i32(space.width.into(), 2)).into(),
-            (rounded_div::i32(space.width.into(), 2)).into(),
-        ])
-        .y_bounds([
-            (-rounded_div::i32(space.height.into(), 2)).into(),
-            (rounded_div::i32(space.height.into(), 2)).into(),
-        ])
-        .marker(Marker::Dot)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .marker(app.camera.zoom.marker())
         .paint(|ctx| {
-            // Render pre-computed wall coordinates
+            // Render pre-computed wall, terrain, key and door coordinates
             ctx.draw(&Points {
                 coords: &wall_screen_coords,
                 color: Color::Green,
             });
+            for (coords, color) in &cell_layers {
+                ctx.draw(&Points {
+                    coords,
+                    color: *color,
+                });
+            }
         });
     let solution = Canvas::default()
-        .x_bounds([
-            (-rounded_div::i32(space.width.into(), 2)).into(),
-            (rounded_div::i32(space.width.into(), 2)).into(),
-        ])
-        .y_bounds([
-            (-rounded_div::i32(space.height.into(), 2)).into(),
-            (rounded_div::i32(space.height.into(), 2)).into(),
-        ])
-        .marker(Marker::Dot)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .marker(app.camera.zoom.marker())
         .paint(|ctx| {
             // Render pre-computed animation coordinates
-            ctx.draw(&Points {
-                coords: &animation_screen_coords,
-                color: Color::Red,
-            });
+            for (coords, color) in &solver_layers {
+                ctx.draw(&Points {
+                    coords,
+                    color: *color,
+                });
+            }
+
+            // Keep glyphs on top, so that crossings still read as bridges over the solver's path
+            for &((x, y), glyph, colour) in &glyphs {
+                ctx.print(x, y, Span::styled(glyph.to_string(), colour));
+            }
         });
 
-    frame.render_widget(maze, space);
-    frame.render_widget(solution, space);
+    frame.render_widget(maze, maze_area);
+    frame.render_widget(solution, maze_area);
+
+    // Only show the minimap when part of the maze is out of view
+    if maze_columns > visible.0 || maze_rows > visible.1 {
+        minimap(
+            frame,
+            maze_area,
+            &wall_screen_coords,
+            (x_bounds, y_bounds),
+            (maze_columns, maze_rows),
+        );
+    }
+
+    if app.stats {
+        stats_panel(app, frame, maze_area);
+    }
+    if let Some(settings) = app.braiding {
+        braid_panel(&app.keymap, &settings, frame, maze_area);
+    }
+
+    if grid.floors() > 1 {
+        floor_indicator(frame, maze_area, floor, grid.floors());
+    }
+
+    app.reach_inspected_cell();
+    tooltip(app, frame, tooltip_area);
+
+    Ok(())
+}
+
+/// Returns the floor of the maze shown on its own on the in-game screen, or `None` if every floor
+/// is shown side by side, as they are when the maze has a single one.
+pub(crate) fn shown_floor(camera: &Camera, grid: &Grid) -> Option<usize> {
+    camera
+        .floor
+        .filter(|&floor| grid.floors() > 1 && floor < grid.floors())
+}
+
+/// Renders the floor indicator in the top-left corner of the maze area.
+///
+/// This function tells which floor of a multi-level maze is shown, counted from the ground floor as
+/// the first one, or that every floor is shown side by side. The indicator is left out when the
+/// maze area is too small to hold it.
+pub(crate) fn floor_indicator(frame: &mut Frame, area: Rect, floor: Option<usize>, floors: usize) {
+    let label = floor.map_or_else(
+        || format!("all {floors}"),
+        |floor| format!("{} of {floors}", floor + 1),
+    );
+    let width = u16::try_from(label.len().max("Floor".len()))
+        .unwrap_or(u16::MAX)
+        .saturating_add(2);
+    if width > area.width || area.height < 3 {
+        return;
+    }
+
+    let indicator_area = Rect {
+        x: area.x,
+        y: area.y,
+        width,
+        height: 3,
+    };
+    let indicator = Paragraph::new(label).block(
+        Block::bordered()
+            .title("Floor")
+            .style(Style::default().fg(rgb(stairs::STAIRS_COLOUR)))
+            .border_type(BorderType::Plain),
+    );
+
+    frame.render_widget(Clear, indicator_area);
+    frame.render_widget(indicator, indicator_area);
+}
+
+/// Returns the terminal colour matching the given RGB components.
+pub(crate) const fn rgb([red, green, blue]: [u8; 3]) -> Color {
+    Color::Rgb(red, green, blue)
+}
+
+/// Returns the colour a walkable cell is drawn in, unless it's a plain open path, entry or exit.
+pub(crate) const fn floor_colour(cell: Cell) -> Option<Color> {
+    match cell {
+        Cell::Key(_) => Some(rgb(puzzle::KEY_COLOUR)),
+        Cell::Door(_) => Some(rgb(puzzle::DOOR_COLOUR)),
+        Cell::Portal(_) => Some(rgb(portal::PORTAL_COLOUR)),
+        Cell::Arrow(_) => Some(rgb(oneway::ARROW_COLOUR)),
+        Cell::Stairs(_) => Some(rgb(stairs::STAIRS_COLOUR)),
+        Cell::Crossing => Some(rgb(weave::CROSSING_COLOUR)),
+        Cell::Terrain(terrain) => Some(rgb(terrain.colour)),
+        Cell::Entry | Cell::Wall | Cell::Open | Cell::Exit => None,
+    }
+}
+
+/// Computes the screen coordinates of the terrain, key, door, portal, arrow and crossing cells of a
+/// maze, one layer per colour.
+///
+/// # Errors
+///
+/// This function may return errors from coordinate conversion operations.
+pub(crate) fn cell_layers(grid: &Grid) -> Result<Vec<Layer>> {
+    let mut groups: Vec<(Vec<(usize, usize)>, Color)> = Vec::new();
+    for (position, cell) in grid.cells() {
+        let Some(colour) = floor_colour(cell) else {
+            continue;
+        };
+        match groups.iter_mut().find(|(_, group)| *group == colour) {
+            Some((cells, _)) => cells.push(position),
+            None => groups.push((vec![position], colour)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(cells, colour)| {
+            Ok((
+                pathfinding::transform_maze_to_screen_coords(&cells, grid)?,
+                colour,
+            ))
+        })
+        .collect()
+}
+
+/// Glyph drawn over a maze cell, with the screen coordinates and colour it's drawn with.
+type Glyph = ((f64, f64), char, Color);
+
+/// Computes the screen coordinates of the arrows and crossings of a maze, along with the glyph and
+/// colour each one is drawn with.
+///
+/// # Errors
+///
+/// This function may return errors from coordinate conversion operations.
+pub(crate) fn cell_glyphs(grid: &Grid) -> Result<Vec<Glyph>> {
+    let (cells, glyphs): (Vec<_>, Vec<_>) = grid
+        .cells()
+        .filter_map(|(position, cell)| match cell {
+            Cell::Arrow(direction) => Some((
+                position,
+                (oneway::glyph(direction)?, rgb(oneway::ARROW_COLOUR)),
+            )),
+            Cell::Crossing => Some((
+                position,
+                (weave::CROSSING_GLYPH, rgb(weave::CROSSING_COLOUR)),
+            )),
+            _ => None,
+        })
+        .unzip();
+
+    Ok(pathfinding::transform_maze_to_screen_coords(&cells, grid)?
+        .into_iter()
+        .step_by(grid.topology().dots_per_column())
+        .zip(glyphs)
+        .map(|(coords, (glyph, colour))| (coords, glyph, colour))
+        .collect())
+}
+
+/// Describes a way out of a maze by its number of moves and its cost.
+fn describe_route(route: Option<Route>) -> String {
+    route.map_or_else(
+        || "no way out".to_owned(),
+        |Route { steps, cost }| format!("{steps} steps, cost {cost}"),
+    )
+}
+
+/// Key hints shown in the braid panel, one line per group.
+pub(crate) const BRAID_PANEL_HINTS: [&[(&[Action], &str)]; 2] = [
+    &[
+        (&[Action::Up, Action::Down], "dead ends"),
+        (&[Action::ZoomIn, Action::ZoomOut], "loops"),
+    ],
+    &[(&[Action::Select], "braid"), (&[Action::Back], "cancel")],
+];
+
+/// Returns the lines of the braid panel, the given settings above the keys changing them.
+fn braid_panel_lines(keymap: &Keymap, settings: &BraidSettings) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::raw(format!("dead ends removed  {:>3}%", settings.dead_ends)),
+        Line::raw(format!("loops added        {:>4}", settings.loops)),
+        Line::raw(""),
+    ];
+    lines.extend(
+        BRAID_PANEL_HINTS
+            .iter()
+            .map(|hints| Line::raw(keymap.hints(hints))),
+    );
+    lines
+}
+
+/// Returns the `(width, height)` of the braid panel showing the given lines, borders included.
+fn braid_panel_size(lines: &[Line]) -> (u16, u16) {
+    let width = lines
+        .iter()
+        .map(Line::width)
+        .max()
+        .map_or(0, |width| u16::try_from(width).unwrap_or(u16::MAX))
+        .saturating_add(2);
+    let height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2);
+    (width, height)
+}
+
+/// Renders the braid panel in the middle of the maze area.
+///
+/// This function shows the share of dead ends to remove and the number of loops to add picked so
+/// far, above the keys changing them and applying the braiding. The panel is left out when the maze
+/// area is too small to hold it, which [`required_size`] prevents while the panel is open.
+pub(crate) fn braid_panel(
+    keymap: &Keymap,
+    settings: &BraidSettings,
+    frame: &mut Frame,
+    area: Rect,
+) {
+    let lines = braid_panel_lines(keymap, settings);
+    let (width, height) = braid_panel_size(&lines);
+    if width > area.width || height > area.height {
+        return;
+    }
+
+    let panel_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let panel = Paragraph::new(lines).block(
+        Block::bordered()
+            .title("Braid")
+            .style(Style::default().fg(Color::Green))
+            .border_type(BorderType::Plain),
+    );
+
+    frame.render_widget(Clear, panel_area);
+    frame.render_widget(panel, panel_area);
+}
+
+/// Renders the statistics panel in the bottom-left corner of the maze area.
+///
+/// This function compares the way out found by the current solver with the one taking the fewest
+/// moves and the one with the lowest cost, so that mazes where the shortest route isn't the
+/// cheapest one stand out. The panel is left out when the maze area is too small to hold it.
+pub(crate) fn stats_panel(app: &App, frame: &mut Frame, area: Rect) {
+    let stats = app.animation_manager.stats;
+    let lines = [
+        ("found", stats.found),
+        ("shortest", stats.shortest),
+        ("cheapest", stats.cheapest),
+    ]
+    .map(|(label, route)| Line::raw(format!("{label:<9}{}", describe_route(route))));
+
+    let width = lines
+        .iter()
+        .map(Line::width)
+        .max()
+        .map_or(0, |width| u16::try_from(width).unwrap_or(u16::MAX))
+        .saturating_add(2);
+    let height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2);
+    if width > area.width || height > area.height {
+        return;
+    }
+
+    let panel_area = Rect {
+        x: area.x,
+        y: area.bottom().saturating_sub(height),
+        width,
+        height,
+    };
+    let panel = Paragraph::new(lines.to_vec()).block(
+        Block::bordered()
+            .title(format!("Stats: {}", app.animation_manager.solver.name()))
+            .style(Style::default().fg(Color::Green))
+            .border_type(BorderType::Plain),
+    );
+
+    frame.render_widget(Clear, panel_area);
+    frame.render_widget(panel, panel_area);
+}
+
+/// Colours of the flood and of the path out for each entry point of a multi-source search.
+///
+/// Entry points beyond the palette reuse its colours in the same order.
+pub(crate) const SOURCE_COLOURS: [(Color, Color); 4] = [
+    (Color::Red, Color::LightRed),
+    (Color::Cyan, Color::LightCyan),
+    (Color::Magenta, Color::LightMagenta),
+    (Color::Blue, Color::LightBlue),
+];
+
+/// Colour of the cells of the shortest way out that the solver's way out misses, which no other
+/// layer is drawn in.
+pub(crate) const SHORTCUT_COLOUR: Color = Color::Gray;
+
+/// Screen coordinates of cells drawn in the same colour.
+pub(crate) type Layer = (Vec<(f64, f64)>, Color);
+
+/// Computes the screen coordinates of everything the solver has drawn so far, layer by layer.
+///
+/// This function returns the cells of the shortest way out that the solver's way out misses, then
+/// the cells reached by a multi-source search or by a search over keys and positions, then the
+/// paths out of it, then the path explored by the depth-first search, the keys picked up along with
+/// both ends of the jump through a portal or stairs just made, and finally the inspected cell, each
+/// with the colour to draw it in, so that later layers are drawn on top of earlier ones. Cells
+/// reached while holding keys take the colour of the number of keys held. Only the cells lying on
+/// the given floor are kept when one is given, placed in the view of that floor.
+///
+/// # Errors
+///
+/// This function may return errors from coordinate conversion operations.
+pub(crate) fn solver_layers(app: &App, view: &Grid, floor: Option<usize>) -> Result<Vec<Layer>> {
+    let manager = &app.animation_manager;
+    let grid = &manager.grid;
+    let screen = |cells: &[(usize, usize)]| {
+        let shown = cells
+            .iter()
+            .filter_map(|&cell| grid.project(floor, cell))
+            .collect::<Vec<_>>();
+        pathfinding::transform_maze_to_screen_coords(&shown, view)
+    };
+    let mut layers = vec![(screen(&manager.shortcut)?, SHORTCUT_COLOUR)];
+
+    let reached = manager
+        .reached
+        .iter()
+        .map(|&(cell, keys)| (cell, usize::try_from(keys.len()).unwrap_or(0)))
+        .collect::<Vec<_>>();
+    for (cells, traced) in [
+        (&manager.visited, false),
+        (&reached, false),
+        (&manager.traced, true),
+    ] {
+        for (source, &(flood, path)) in SOURCE_COLOURS.iter().enumerate() {
+            let cells = cells
+                .iter()
+                .filter(|(_, owner)| owner % SOURCE_COLOURS.len() == source)
+                .map(|&(cell, _)| cell)
+                .collect::<Vec<_>>();
+            if !cells.is_empty() {
+                layers.push((screen(&cells)?, if traced { path } else { flood }));
+            }
+        }
+    }
+
+    layers.push((screen(&manager.current_path)?, Color::Red));
+    layers.push((
+        screen(
+            &manager
+                .collected
+                .iter()
+                .map(|&(cell, _)| cell)
+                .chain(manager.flash.into_iter().flat_map(<[_; 2]>::from))
+                .collect::<Vec<_>>(),
+        )?,
+        Color::White,
+    ));
+    layers.push((
+        screen(&app.inspected_cell.into_iter().collect::<Vec<_>>())?,
+        Color::Yellow,
+    ));
+
+    Ok(layers)
+}
 
+/// Renders the in-game tooltip at the bottom of the screen.
+///
+/// This function draws the key hints as the title of the tooltip block and, inside it, a summary of
+/// the map's metadata above the details of the maze cell last clicked by the user.
+pub(crate) fn tooltip(app: &App, frame: &mut Frame, tooltip_area: Rect) {
     // Render tooltip as a block at the bottom center with top border
     let tooltip_block = Block::bordered()
-        .title("(h) return to menu")
+        .title(app.keymap.hints(&[
+            (&[Action::Back], "return to menu"),
+            (
+                &[
+                    Action::PanLeft,
+                    Action::PanDown,
+                    Action::PanUp,
+                    Action::PanRight,
+                ],
+                "pan",
+            ),
+            (&[Action::ZoomIn, Action::ZoomOut], "zoom"),
+            (
+                &[Action::ToggleFollow],
+                if app.camera.follow {
+                    "follow: on"
+                } else {
+                    "follow: off"
+                },
+            ),
+            (&[Action::Help], "help"),
+        ]))
         .title_alignment(Alignment::Center)
         .style(Style::default().fg(Color::Green))
         .border_type(BorderType::Plain)
         .borders(Borders::TOP);
 
-    frame.render_widget(tooltip_block, tooltip_area);
+    // Show the last notice, or describe the cell last clicked by the user, if any
+    let report = app.animation_manager.report.as_ref();
+    let grid = &app.animation_manager.grid;
+    let inspection = match app.inspected_cell {
+        Some(cell) => {
+            let name = cell_name(grid, cell);
+            match (
+                report.and_then(|report| report.describe_entry(cell)),
+                pathfinding::visit_order(&app.animation_manager.steps, cell),
+            ) {
+                (Some(entry), _) => entry,
+                (None, Some(order)) => format!("{name}: visited at step {order}"),
+                (None, None)
+                    if app
+                        .inspected_reach
+                        .as_ref()
+                        .is_some_and(|&(reached, _, reachable)| reached == cell && reachable) =>
+                {
+                    format!("{name}: not visited by the solver")
+                }
+                (None, None) => format!("{name}: out of reach of every entry"),
+            }
+        }
+        None => "(click) inspect a cell".to_owned(),
+    };
+    let inspection = app.status.clone().unwrap_or(inspection);
+
+    // Mazes with several entry points also tell which one gets out first
+    let summary = app.map.metadata.summary(&app.map.key);
+    let summary = match report {
+        Some(report) => format!("{summary} \u{2014} {}", report.summary()),
+        None => summary,
+    };
+    let tooltip = Paragraph::new(vec![Line::raw(summary), Line::raw(inspection)])
+        .alignment(Alignment::Center)
+        .block(tooltip_block);
+
+    frame.render_widget(tooltip, tooltip_area);
+}
 
-    Ok(())
+/// Names a maze cell by its coordinates, given within its floor along with the floor in
+/// multi-level mazes.
+pub(crate) fn cell_name(grid: &Grid, cell: (usize, usize)) -> String {
+    if grid.floors() > 1 {
+        let (column, row) = grid.local(cell);
+        format!(
+            "cell ({column}, {row}) on floor {}",
+            grid.floor_of(cell) + 1
+        )
+    } else {
+        let (column, row) = cell;
+        format!("cell ({column}, {row})")
+    }
+}
+
+/// Collects the coordinates of every wall cell in the maze.
+///
+/// This function returns the `(column, row)` pairs of all wall cells of the maze, in the same
+/// coordinate space used by the pathfinding algorithm.
+pub(crate) fn wall_coordinates(grid: &Grid) -> Vec<(usize, usize)> {
+    grid.cells()
+        .filter(|&(_, cell)| cell == Cell::Wall)
+        .map(|(position, _)| position)
+        .collect()
+}
+
+/// Computes the canvas bounds of the region of the maze visible through the camera.
+///
+/// This function returns the horizontal and vertical bounds in the screen coordinate space produced
+/// by [`pathfinding::transform_maze_to_screen_coords`] for a canvas of the given amount of
+/// `(columns, rows)` dots, sized so that every maze cell lands exactly on the given amount of
+/// horizontal dots, and a single vertical one, at the camera's zoom level.
+///
+/// # Errors
+///
+/// This function may return errors from coordinate conversion operations.
+pub(crate) fn viewport_bounds(
+    app: &App,
+    maze: (usize, usize),
+    canvas: (usize, usize),
+    dots: usize,
+) -> Result<([f64; 2], [f64; 2])> {
+    let (origin_column, origin_row) = app.camera.origin(maze, (canvas.0 / dots, canvas.1));
+
+    let columns_n = f64::from(u32::try_from(maze.0)?);
+    let rows_n = f64::from(u32::try_from(maze.1)?);
+    let visible_columns =
+        f64::from(u32::try_from(canvas.0.saturating_sub(1))?) / f64::from(u32::try_from(dots)?);
+    let visible_rows = f64::from(u32::try_from(canvas.1.saturating_sub(1))?);
+
+    let left = f64::from(i32::try_from(origin_column)?) - (columns_n - 1.) / 2.;
+    let top = (rows_n - 1.) / 2. - f64::from(i32::try_from(origin_row)?);
+
+    Ok(([left, left + visible_columns], [top - visible_rows, top]))
+}
+
+/// Renders a minimap of the whole maze in the top-right corner of the given area.
+///
+/// This function draws a scaled-down view of the maze walls along with a rectangle outlining the
+/// region currently visible through the camera, so that users know where they are when zoomed in.
+pub(crate) fn minimap(
+    frame: &mut Frame,
+    area: Rect,
+    wall_screen_coords: &[(f64, f64)],
+    viewport: ([f64; 2], [f64; 2]),
+    maze: (usize, usize),
+) {
+    let (maze_columns, maze_rows) = maze;
+    let ([left, right], [bottom, top]) = viewport;
+
+    let width = u16::try_from(maze_columns.div_ceil(2))
+        .unwrap_or(u16::MAX)
+        .clamp(8, (area.width / 3).max(8))
+        .saturating_add(2)
+        .min(area.width);
+    let height = u16::try_from(maze_rows.div_ceil(4))
+        .unwrap_or(u16::MAX)
+        .clamp(4, (area.height / 3).max(4))
+        .saturating_add(2)
+        .min(area.height);
+    let minimap_area = Rect {
+        x: area.right().saturating_sub(width),
+        y: area.y,
+        width,
+        height,
+    };
+
+    let half_columns = f64::from(u16::try_from(maze_columns).unwrap_or(u16::MAX)) / 2.;
+    let half_rows = f64::from(u16::try_from(maze_rows).unwrap_or(u16::MAX)) / 2.;
+
+    let minimap = Canvas::default()
+        .block(
+            Block::bordered()
+                .title("Map")
+                .style(Color::Green)
+                .border_type(BorderType::Rounded),
+        )
+        .x_bounds([-half_columns, half_columns])
+        .y_bounds([-half_rows, half_rows])
+        .marker(Marker::Braille)
+        .paint(|ctx| {
+            ctx.draw(&Points {
+                coords: wall_screen_coords,
+                color: Color::DarkGray,
+            });
+            ctx.layer();
+            ctx.draw(&Rectangle {
+                x: left,
+                y: bottom,
+                width: right - left,
+                height: top - bottom,
+                color: Color::Yellow,
+            });
+        });
+
+    frame.render_widget(Clear, minimap_area);
+    frame.render_widget(minimap, minimap_area);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pathfinding::AnimationManager;
+    use crate::{map::Metadata, pathfinding::AnimationManager};
     use ratatui::{backend::TestBackend, Terminal};
 
     /// Creates a minimal test app for UI testing.
@@ -459,12 +1454,13 @@ mod tests {
         Map {
             key: "test_map".to_owned(),
             data: vec![
-                "222222222".to_owned(),
-                "2100000002".to_owned(),
-                "2020202002".to_owned(),
-                "2000203002".to_owned(),
-                "222222222".to_owned(),
+                "2222222222".to_owned(),
+                "2133333332".to_owned(),
+                "2323232332".to_owned(),
+                "2333233342".to_owned(),
+                "2222222222".to_owned(),
             ],
+            metadata: Metadata::default(),
         }
     }
 
@@ -539,7 +1535,7 @@ mod tests {
         let mut terminal = create_test_terminal();
 
         let result = terminal.draw(|frame| {
-            let layout = init_menu(frame, MenuType::MainMenu(3));
+            let layout = init_menu(frame, MenuType::MainMenu(3), &Keymap::default());
             assert_eq!(layout.len(), 3, "main menu should have 3 items");
         });
 
@@ -551,7 +1547,7 @@ mod tests {
         let mut terminal = create_test_terminal();
 
         let result = terminal.draw(|frame| {
-            let layout = init_menu(frame, MenuType::OptionsMenu(2));
+            let layout = init_menu(frame, MenuType::OptionsMenu(2), &Keymap::default());
             assert_eq!(layout.len(), 2, "options menu should have 2 items");
         });
 
@@ -563,7 +1559,7 @@ mod tests {
         let mut terminal = create_test_terminal();
 
         let result = terminal.draw(|frame| {
-            main_menu(frame, MainMenuItem::StartGame);
+            main_menu(frame, MainMenuItem::StartGame, &Keymap::default());
         });
 
         assert!(
@@ -577,7 +1573,7 @@ mod tests {
         let mut terminal = create_test_terminal();
 
         let result = terminal.draw(|frame| {
-            main_menu(frame, MainMenuItem::Options);
+            main_menu(frame, MainMenuItem::Options, &Keymap::default());
         });
 
         assert!(
@@ -591,7 +1587,7 @@ mod tests {
         let mut terminal = create_test_terminal();
 
         let result = terminal.draw(|frame| {
-            main_menu(frame, MainMenuItem::Quit);
+            main_menu(frame, MainMenuItem::Quit, &Keymap::default());
         });
 
         assert!(
@@ -605,7 +1601,7 @@ mod tests {
         let mut terminal = create_test_terminal();
 
         let result = terminal.draw(|frame| {
-            options_menu(frame, OptionsMenuItem::Map);
+            options_menu(frame, OptionsMenuItem::Map, &Keymap::default());
         });
 
         assert!(
@@ -619,7 +1615,7 @@ mod tests {
         let mut terminal = create_test_terminal();
 
         let result = terminal.draw(|frame| {
-            options_menu(frame, OptionsMenuItem::Back);
+            options_menu(frame, OptionsMenuItem::Back, &Keymap::default());
         });
 
         assert!(
@@ -638,6 +1634,7 @@ mod tests {
             Map {
                 key: "second_map".to_owned(),
                 data: vec!["222".to_owned(), "213".to_owned(), "222".to_owned()],
+                metadata: Metadata::default(),
             },
         ];
         app.viewport_map = app.maps.first().cloned();
@@ -652,6 +1649,335 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_map_menu_and_tooltip_show_metadata() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        let mut map = Map::default();
+        map.metadata.title = Some("Spiral".to_owned());
+        map.metadata.author = Some("Ada".to_owned());
+        app.maps = vec![map.clone()];
+        app.viewport_map = Some(map.clone());
+        app.map = map;
+
+        let mut contents = Vec::new();
+        for screen in [Screen::MapMenu, Screen::InGame] {
+            app.screen = screen;
+            let _ = terminal
+                .draw(|frame| {
+                    draw(&mut app, frame).expect("drawing should succeed in test");
+                })
+                .expect("failed to draw to the test terminal");
+            contents.push(
+                terminal
+                    .backend()
+                    .buffer()
+                    .content()
+                    .iter()
+                    .map(ratatui::buffer::Cell::symbol)
+                    .collect::<String>(),
+            );
+        }
+
+        assert!(
+            contents
+                .first()
+                .is_some_and(|menu| menu.contains("author: Ada") && menu.contains("rating: 2/5")),
+            "the map menu should list the metadata and the rating of the map under the cursor"
+        );
+        assert!(
+            contents
+                .last()
+                .is_some_and(|game| game.contains("Spiral by Ada")),
+            "the tooltip should summarise the metadata of the map being played"
+        );
+    }
+
+    #[test]
+    fn test_in_game_reports_multi_source_search() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.map = Map {
+            key: "two_entries".to_owned(),
+            data: vec![
+                "2222222".to_owned(),
+                "2133312".to_owned(),
+                "2222232".to_owned(),
+                "2222242".to_owned(),
+            ],
+            metadata: Metadata::default(),
+        };
+        app.screen = Screen::InGame;
+        app.inspected_cell = Some((1, 1));
+
+        let _ = terminal
+            .draw(|frame| {
+                in_game(&mut app, frame).expect("drawing should succeed in test");
+            })
+            .expect("failed to draw to the test terminal");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+
+        assert!(
+            contents.contains("entry 2 at (5, 1) is out first, in 2 moves"),
+            "the tooltip should tell which entry point gets out first"
+        );
+        assert!(
+            contents.contains("entry 1 at (1, 1): out in 6 moves"),
+            "inspecting an entry point should describe its path out"
+        );
+    }
+
+    #[test]
+    fn test_in_game_draws_arrows_and_reachability() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.map = Map {
+            key: "one_way".to_owned(),
+            data: vec![
+                "2222222".to_owned(),
+                "21>3<34".to_owned(),
+                "2222222".to_owned(),
+            ],
+            metadata: Metadata::default(),
+        };
+        app.screen = Screen::InGame;
+        app.inspected_cell = Some((5, 1));
+
+        let _ = terminal
+            .draw(|frame| {
+                in_game(&mut app, frame).expect("drawing should succeed in test");
+            })
+            .expect("failed to draw to the test terminal");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+
+        assert!(
+            contents.contains('\u{2192}') && contents.contains('\u{2190}'),
+            "arrows should be drawn with their glyph at the closest zoom level"
+        );
+        assert!(
+            contents.contains("cell (5, 1): out of reach of every entry"),
+            "the cell past the arrow pointing back should be out of reach"
+        );
+    }
+
+    #[test]
+    fn test_in_game_draws_crossings() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.map = Map {
+            key: "weave".to_owned(),
+            data: [
+                "2222222", "2221222", "433#332", "2223232", "2223332", "2222222",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+            metadata: Metadata::default(),
+        };
+        app.screen = Screen::InGame;
+        app.inspected_cell = Some((3, 2));
+
+        let _ = terminal
+            .draw(|frame| {
+                in_game(&mut app, frame).expect("drawing should succeed in test");
+            })
+            .expect("failed to draw to the test terminal");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+
+        assert!(
+            contents.contains(weave::CROSSING_GLYPH),
+            "crossings should be drawn as bridges at the closest zoom level"
+        );
+        assert!(
+            !contents.contains("out of reach"),
+            "the crossing should be reached through its tunnel"
+        );
+    }
+
+    #[test]
+    fn test_in_game_shows_floors() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.map = Map {
+            key: "tower".to_owned(),
+            data: ["22222", "21+32", "22222", "===", "22222", "23-34", "22222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        };
+        app.screen = Screen::InGame;
+        app.area = Rect::new(0, 0, 80, 24);
+        app.camera.follow = false;
+        app.camera.floor = Some(1);
+
+        // Cells clicked on a floor shown on its own are placed in the whole maze
+        let clicked = maze_cell_at(&app, Position::new(38, 10));
+        assert_eq!(clicked, Some((6, 1)));
+        app.inspected_cell = clicked;
+
+        let mut draw = |app: &mut App| {
+            let _ = terminal
+                .draw(|frame| {
+                    in_game(app, frame).expect("drawing should succeed in test");
+                })
+                .expect("failed to draw to the test terminal");
+            terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(ratatui::buffer::Cell::symbol)
+                .collect::<String>()
+        };
+
+        let contents = draw(&mut app);
+        assert!(
+            contents.contains("2 of 2"),
+            "the floor shown should be told"
+        );
+        assert!(
+            contents.contains("cell (1, 1) on floor 2"),
+            "cells should be named within their floor"
+        );
+
+        app.camera.floor = None;
+        assert!(draw(&mut app).contains("all 2"));
+        assert_eq!(maze_cell_at(&app, Position::new(38, 10)), Some((3, 1)));
+    }
+
+    #[test]
+    fn test_solver_layers_shortcut() {
+        let mut app = create_test_app();
+        app.map = Map {
+            key: "detour".to_owned(),
+            data: ["2222222", "2133334", "2322232", "2333332", "2222222"]
+                .map(str::to_owned)
+                .to_vec(),
+            metadata: Metadata::default(),
+        };
+        let grid = Grid::of(&app.map).expect("the map should form a grid");
+        app.animation_manager
+            .record(grid.clone())
+            .expect("the maze has an entry point");
+
+        // The shortcut comes first, in a colour no other layer is drawn in
+        let layers = solver_layers(&app, &grid, None).expect("the layers should be computed");
+        let (shortcut, others) = layers.split_first().expect("the shortcut should be drawn");
+        assert_eq!(shortcut.1, SHORTCUT_COLOUR);
+        assert_eq!(shortcut.0.len(), 3);
+        assert!(others.iter().all(|&(_, colour)| colour != SHORTCUT_COLOUR));
+        assert!(SOURCE_COLOURS
+            .iter()
+            .all(|&(flood, path)| flood != SHORTCUT_COLOUR && path != SHORTCUT_COLOUR));
+    }
+
+    #[test]
+    fn test_in_game_draws_braid_panel() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.screen = Screen::InGame;
+        app.braiding = Some(BraidSettings {
+            dead_ends: 30,
+            loops: 2,
+            seed: None,
+        });
+
+        let _ = terminal
+            .draw(|frame| {
+                in_game(&mut app, frame).expect("drawing should succeed in test");
+            })
+            .expect("failed to draw to the test terminal");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+
+        assert!(
+            contents.contains("dead ends removed   30%")
+                && contents.contains("loops added           2")
+                && contents.contains("(l) braid / (h) cancel"),
+            "the braid panel should show the settings picked and the keys changing them"
+        );
+    }
+
+    #[test]
+    fn test_in_game_stats_panel_and_terrain_help() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.map = Map {
+            key: "muddy".to_owned(),
+            data: vec![
+                "2222222".to_owned(),
+                "2177734".to_owned(),
+                "2322232".to_owned(),
+                "2333332".to_owned(),
+                "2222222".to_owned(),
+            ],
+            metadata: Metadata::default(),
+        };
+        app.screen = Screen::InGame;
+
+        let mut contents = Vec::new();
+        for help in [false, true] {
+            app.help = help;
+            let _ = terminal
+                .draw(|frame| {
+                    draw(&mut app, frame).expect("drawing should succeed in test");
+                })
+                .expect("failed to draw to the test terminal");
+            contents.push(
+                terminal
+                    .backend()
+                    .buffer()
+                    .content()
+                    .iter()
+                    .map(ratatui::buffer::Cell::symbol)
+                    .collect::<String>(),
+            );
+        }
+
+        assert!(
+            contents
+                .first()
+                .is_some_and(|game| game.contains("Stats: depth-first")
+                    && game.contains("shortest 5 steps, cost 17")
+                    && game.contains("cheapest 9 steps, cost 9")),
+            "the stats panel should compare the shortest and cheapest ways out"
+        );
+        assert!(
+            contents
+                .last()
+                .is_some_and(|help| help.contains("7 mud (5)")
+                    && help.contains("a-e keys")
+                    && help.contains("p-u portals")
+                    && help.contains("^v<> one-way")
+                    && help.contains("+- stairs  # crossing")
+                    && help.contains("switch to the next solver")
+                    && help.contains("minimap walls")),
+            "the in-game help should list the terrain and still fit on the screen"
+        );
+    }
+
     #[test]
     fn test_map_menu_empty_viewport_map_error() {
         let mut app = create_test_app();
@@ -705,6 +2031,7 @@ mod tests {
                 "200002".to_owned(),
                 "222222".to_owned(),
             ],
+            metadata: Metadata::default(),
         };
         app.animation_manager = AnimationManager::new();
 
@@ -739,4 +2066,198 @@ mod tests {
         assert_eq!(main_menu.value(), 3);
         assert_eq!(options_menu.value(), 2);
     }
+
+    #[test]
+    fn test_in_game_with_map_bigger_than_terminal() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+
+        let mut data = vec!["2".repeat(200)];
+        data.push(format!("21{}4", "3".repeat(197)));
+        data.extend(std::iter::repeat_n("2".repeat(200), 60));
+        app.map = Map {
+            key: "big_map".to_owned(),
+            data,
+            metadata: Metadata::default(),
+        };
+
+        let result = terminal.draw(|frame| {
+            in_game(&mut app, frame).expect("in-game should render oversized mazes");
+        });
+
+        assert!(
+            result.is_ok(),
+            "rendering in-game with an oversized map should succeed"
+        );
+        assert_eq!(app.camera.row, 0, "camera should stay within the maze");
+    }
+
+    #[test]
+    fn test_draw_too_small_terminal() {
+        let mut app = create_test_app();
+        let mut terminal =
+            Terminal::new(TestBackend::new(40, 4)).expect("failed to create test terminal");
+        app.screen = Screen::InGame;
+
+        let result = terminal.draw(|frame| {
+            draw(&mut app, frame).expect("drawing should succeed on tiny terminals");
+        });
+
+        assert!(result.is_ok(), "drawing on a tiny terminal should succeed");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+        assert!(
+            contents.contains("terminal too small: need 16x7"),
+            "a warning with the required size should be shown"
+        );
+        assert!(
+            app.animation_manager.steps.is_empty(),
+            "the maze shouldn't be drawn on a tiny terminal"
+        );
+    }
+
+    #[test]
+    fn test_required_size_small_maze() {
+        let mut app = create_test_app();
+        app.screen = Screen::InGame;
+        app.map = Map {
+            key: "tiny".to_owned(),
+            data: vec!["2222".to_owned(), "2134".to_owned(), "2222".to_owned()],
+            metadata: Metadata::default(),
+        };
+
+        assert_eq!(required_size(&app), (2, 1 + TOOLTIP_HEIGHT));
+
+        // The braid panel takes every key while open, so it must never be too big to be seen
+        app.braiding = Some(BraidSettings::default());
+        let (width, height) = required_size(&app);
+        let mut terminal = Terminal::new(TestBackend::new(width, height))
+            .expect("failed to create the test terminal");
+        let _ = terminal
+            .draw(|frame| {
+                in_game(&mut app, frame).expect("drawing should succeed in test");
+            })
+            .expect("failed to draw to the test terminal");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+        assert!(contents.contains("dead ends removed"));
+    }
+
+    #[test]
+    fn test_map_list_height() {
+        assert_eq!(
+            map_list_height(Rect::new(0, 0, 80, 24), &Keymap::default()),
+            2
+        );
+        assert_eq!(
+            map_list_height(Rect::new(0, 0, 80, 2), &Keymap::default()),
+            0
+        );
+        assert_eq!(
+            map_list_height(Rect::new(0, 0, 200, 60), &Keymap::default()),
+            10
+        );
+    }
+
+    #[test]
+    fn test_draw_error_popup() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.errors
+            .push_back("failed to read broken.labmap".to_owned());
+        app.errors
+            .push_back("skipped file with non-UTF-8 name".to_owned());
+
+        let result = terminal.draw(|frame| {
+            draw(&mut app, frame).expect("drawing should succeed in test");
+        });
+
+        assert!(result.is_ok(), "drawing an error popup should succeed");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+        assert!(
+            contents.contains("Error (1 more)"),
+            "the popup should show the amount of pending errors"
+        );
+        assert!(
+            contents.contains("failed to read broken.labmap"),
+            "the popup should show the first error"
+        );
+    }
+
+    #[test]
+    fn test_draw_help_overlay() {
+        let mut app = create_test_app();
+        let mut terminal = create_test_terminal();
+        app.screen = Screen::MapMenu;
+        app.help = true;
+
+        let result = terminal.draw(|frame| {
+            draw(&mut app, frame).expect("drawing should succeed in test");
+        });
+
+        assert!(result.is_ok(), "drawing the help overlay should succeed");
+        let contents = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+        assert!(
+            contents.contains("return to the options menu"),
+            "the overlay should describe the actions of the current screen"
+        );
+        assert!(
+            contents.contains("j, \u{2193}, s"),
+            "the overlay should list every key bound to an action"
+        );
+        assert!(
+            contents.contains("1 entry point  2 wall"),
+            "the overlay should explain the map legend"
+        );
+        assert!(
+            contents.contains("map currently in use"),
+            "the overlay should explain the colours of the current screen"
+        );
+    }
+
+    #[test]
+    fn test_help_actions_are_bound_by_default() {
+        let keymap = Keymap::default();
+        let screens = [
+            Screen::MainMenu(MainMenuItem::StartGame),
+            Screen::OptionsMenu(OptionsMenuItem::Map),
+            Screen::MapMenu,
+            Screen::InGame,
+        ];
+
+        for screen in &screens {
+            for &action in help_actions(screen)
+                .iter()
+                .flat_map(|(actions, _)| *actions)
+            {
+                assert!(
+                    keymap.key_names(action).is_some(),
+                    "{} should be bound by default",
+                    action.name()
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,34 @@
+//! Weave crossings.
+//!
+//! This module describes the crossing cells of weave mazes, marked with '#', where a corridor
+//! running east to west passes over one running north to south. Both corridors go straight through
+//! the crossing without meeting, so that it stands for two nodes of the maze graph: the bridge,
+//! entered and left sideways, and the tunnel beneath it, entered and left from above or below.
+
+/// Character marking a crossing in the map format.
+pub(crate) const CROSSING: u8 = b'#';
+
+/// Colour crossings are drawn in, as RGB components.
+pub(crate) const CROSSING_COLOUR: [u8; 3] = [0xc8, 0xa0, 0x60];
+
+/// Glyph crossings are drawn with, the double bridge running over the single tunnel.
+pub(crate) const CROSSING_GLYPH: char = '\u{256a}';
+
+/// Returns whether a move with the given step goes through the tunnel of a crossing rather than
+/// over its bridge.
+pub(crate) const fn goes_under((dx, _): (isize, isize)) -> bool {
+    dx == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_goes_under() {
+        assert!(goes_under((0, -1)));
+        assert!(goes_under((0, 1)));
+        assert!(!goes_under((1, 0)));
+        assert!(!goes_under((-1, 0)));
+    }
+}